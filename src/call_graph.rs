@@ -0,0 +1,395 @@
+//! Static call graph of a module. Direct calls are exact; indirect calls
+//! through a table are resolved to every function an element segment can
+//! place in that table whose signature matches the call.
+
+use features::Features;
+use std::collections::BTreeSet;
+use std::io::Error;
+use types::element_section::{ElementItems, ElementMode, ElementSegment};
+use types::{FunctionType, Operator, SubType, WasmModule, WasmSectionBody};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum CallKind {
+    /// `call` and `return_call`.
+    Direct,
+    /// `call_indirect` and `return_call_indirect`.
+    Indirect,
+    /// `ref.func`, after which the function may be called by reference.
+    Reference,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct CallEdge {
+    pub caller: u32,
+    pub callee: u32,
+    pub kind: CallKind,
+}
+
+#[derive(Debug)]
+pub struct CallGraph {
+    pub function_count: u32,
+    pub edges: Vec<CallEdge>,
+    /// Exported functions and the start function.
+    pub roots: Vec<u32>,
+    callees: Vec<Vec<u32>>,
+    callers: Vec<Vec<u32>>,
+    components: Vec<Vec<u32>>,
+    recursive: Vec<bool>,
+}
+
+impl CallGraph {
+    pub fn from_module(module: &WasmModule) -> Result<CallGraph, Error> {
+        CallGraph::from_module_with_features(module, &Features::default())
+    }
+
+    pub fn from_module_with_features(
+        module: &WasmModule,
+        features: &Features,
+    ) -> Result<CallGraph, Error> {
+        let types: Vec<&SubType> = module
+            .sections
+            .iter()
+            .filter_map(|section| match section.body {
+                WasmSectionBody::Types(ref types) => Some(types),
+                _ => None,
+            })
+            .flat_map(|types| types.types())
+            .collect();
+        let signature = |type_index: u32| {
+            types
+                .get(type_index as usize)
+                .and_then(|sub_type| sub_type.func_type())
+        };
+
+        let functions: Vec<_> = module.functions().collect();
+        let function_count = functions.len() as u32;
+        let table_functions = TableFunctions::from_module(module)?;
+
+        let mut edges = BTreeSet::new();
+        for function in &functions {
+            let body = match function.body {
+                Some(body) => body,
+                None => continue,
+            };
+            let caller = function.index;
+            let mut edge = |callee: u32, kind| {
+                if callee < function_count {
+                    edges.insert(CallEdge {
+                        caller,
+                        callee,
+                        kind,
+                    });
+                }
+            };
+
+            for operator in body.operators_with_features(features)? {
+                match operator {
+                    Operator::Call(callee) | Operator::ReturnCall(callee) => {
+                        edge(callee, CallKind::Direct)
+                    }
+                    Operator::CallIndirect {
+                        type_index,
+                        table_index,
+                    }
+                    | Operator::ReturnCallIndirect {
+                        type_index,
+                        table_index,
+                    } => {
+                        let expected = signature(type_index);
+                        for callee in table_functions.candidates(table_index) {
+                            let actual = functions
+                                .get(callee as usize)
+                                .and_then(|function| function.signature);
+                            if expected.is_some() && same_signature(expected, actual) {
+                                edge(callee, CallKind::Indirect);
+                            }
+                        }
+                    }
+                    Operator::RefFunc(callee) => edge(callee, CallKind::Reference),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut roots = BTreeSet::new();
+        for function in &functions {
+            if !function.export_names.is_empty() {
+                roots.insert(function.index);
+            }
+        }
+        for section in &module.sections {
+            if let WasmSectionBody::Start(ref start) = section.body {
+                roots.insert(start.index);
+            }
+        }
+
+        let mut callees = vec![Vec::new(); function_count as usize];
+        let mut callers = vec![Vec::new(); function_count as usize];
+        for edge in &edges {
+            callees[edge.caller as usize].push(edge.callee);
+            callers[edge.callee as usize].push(edge.caller);
+        }
+        for list in callees.iter_mut().chain(callers.iter_mut()) {
+            list.sort();
+            list.dedup();
+        }
+
+        let components = strongly_connected_components(&callees);
+        let mut recursive = vec![false; function_count as usize];
+        for component in &components {
+            for &function in component {
+                recursive[function as usize] =
+                    component.len() > 1 || callees[function as usize].contains(&function);
+            }
+        }
+
+        Ok(CallGraph {
+            function_count,
+            edges: edges.into_iter().collect(),
+            roots: roots.into_iter().collect(),
+            callees,
+            callers,
+            components,
+            recursive,
+        })
+    }
+
+    /// Functions that may call `function`.
+    pub fn callers(&self, function: u32) -> &[u32] {
+        self.callers
+            .get(function as usize)
+            .map_or(&[], |callers| callers.as_slice())
+    }
+
+    /// Functions `function` may call.
+    pub fn callees(&self, function: u32) -> &[u32] {
+        self.callees
+            .get(function as usize)
+            .map_or(&[], |callees| callees.as_slice())
+    }
+
+    /// Every function that can be reached by following calls from `roots`,
+    /// including the roots themselves.
+    pub fn reachable_from(&self, roots: &[u32]) -> BTreeSet<u32> {
+        let mut reached = BTreeSet::new();
+        let mut pending: Vec<u32> = roots
+            .iter()
+            .cloned()
+            .filter(|&root| root < self.function_count)
+            .collect();
+
+        while let Some(function) = pending.pop() {
+            if reached.insert(function) {
+                pending.extend(self.callees(function));
+            }
+        }
+
+        reached
+    }
+
+    /// Every function reachable from the exports and the start function.
+    pub fn reachable(&self) -> BTreeSet<u32> {
+        self.reachable_from(&self.roots)
+    }
+
+    /// Groups functions into strongly connected components, callees before
+    /// their callers. A component of more than one function, or of one
+    /// that calls itself, is a set of mutually recursive functions.
+    pub fn strongly_connected_components(&self) -> &[Vec<u32>] {
+        &self.components
+    }
+
+    pub fn is_recursive(&self, function: u32) -> bool {
+        self.recursive
+            .get(function as usize)
+            .cloned()
+            .unwrap_or(false)
+    }
+}
+
+/// The components `CallGraph::strongly_connected_components` returns.
+fn strongly_connected_components(callees: &[Vec<u32>]) -> Vec<Vec<u32>> {
+    let count = callees.len();
+    let mut index: Vec<Option<usize>> = vec![None; count];
+    let mut low = vec![0; count];
+    let mut on_stack = vec![false; count];
+    let mut stack = Vec::new();
+    let mut next = 0;
+    let mut components = Vec::new();
+
+    // Tarjan's algorithm, with an explicit stack of (function, next
+    // callee to visit) so deep call chains cannot overflow.
+    for root in 0..count {
+        if index[root].is_some() {
+            continue;
+        }
+
+        let mut work = vec![(root, 0)];
+        index[root] = Some(next);
+        low[root] = next;
+        next += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some((function, child)) = work.pop() {
+            if let Some(&callee) = callees[function].get(child) {
+                work.push((function, child + 1));
+
+                let callee = callee as usize;
+                match index[callee] {
+                    None => {
+                        index[callee] = Some(next);
+                        low[callee] = next;
+                        next += 1;
+                        stack.push(callee);
+                        on_stack[callee] = true;
+                        work.push((callee, 0));
+                    }
+                    Some(callee_index) if on_stack[callee] => {
+                        low[function] = low[function].min(callee_index);
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            if let Some(&(parent, _)) = work.last() {
+                low[parent] = low[parent].min(low[function]);
+            }
+
+            if Some(low[function]) == index[function] {
+                let mut component = Vec::new();
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member] = false;
+                    component.push(member as u32);
+                    if member == function {
+                        break;
+                    }
+                }
+                component.sort();
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+/// The functions element segments can place in each table. Passive
+/// segments may be copied into any table with `table.init`.
+struct TableFunctions {
+    active: Vec<(u32, u32)>,
+    passive: Vec<u32>,
+}
+
+impl TableFunctions {
+    fn from_module(module: &WasmModule) -> Result<TableFunctions, Error> {
+        let mut tables = TableFunctions {
+            active: Vec::new(),
+            passive: Vec::new(),
+        };
+
+        for section in &module.sections {
+            let elements = match section.body {
+                WasmSectionBody::Element(ref elements) => elements,
+                _ => continue,
+            };
+
+            for segment in &elements.entries {
+                let functions = segment_functions(segment)?;
+                match segment.mode {
+                    ElementMode::Active { table_index, .. } => tables.active.extend(
+                        functions
+                            .into_iter()
+                            .map(|function| (table_index, function)),
+                    ),
+                    ElementMode::Passive => tables.passive.extend(functions),
+                    ElementMode::Declarative => {}
+                }
+            }
+        }
+
+        Ok(tables)
+    }
+
+    fn candidates(&self, table_index: u32) -> BTreeSet<u32> {
+        self.active
+            .iter()
+            .filter(|&&(table, _)| table == table_index)
+            .map(|&(_, function)| function)
+            .chain(self.passive.iter().cloned())
+            .collect()
+    }
+}
+
+fn segment_functions(segment: &ElementSegment) -> Result<Vec<u32>, Error> {
+    match segment.items {
+        ElementItems::Functions(ref functions) => Ok(functions.clone()),
+        ElementItems::Expressions(ref expressions) => {
+            let mut functions = Vec::new();
+            for expression in expressions {
+                for operator in expression.operators()? {
+                    if let Operator::RefFunc(function) = operator {
+                        functions.push(function);
+                    }
+                }
+            }
+            Ok(functions)
+        }
+    }
+}
+
+fn same_signature(expected: Option<&FunctionType>, actual: Option<&FunctionType>) -> bool {
+    match (expected, actual) {
+        (Some(expected), Some(actual)) => {
+            expected.param_types == actual.param_types
+                && expected.return_types == actual.return_types
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::*;
+
+    #[test]
+    fn test_call_graph() {
+        // (elem (i32.const 0) $b)
+        // (func $a (export "a") (call_indirect (type $v) (i32.const 0)))
+        // (func $b (call $c)) (func $c (call $b)) (func $d)
+        let module = module(&[
+            (TYPE, &[0x01, 0x60, 0x00, 0x00]),
+            (FUNCTION, &[0x04, 0x00, 0x00, 0x00, 0x00]),
+            (TABLE, &[0x01, 0x70, 0x00, 0x01]),
+            (EXPORT, &[0x01, 0x01, 0x61, 0x00, 0x00]),
+            (ELEMENT, &[0x01, 0x00, 0x41, 0x00, 0x0b, 0x01, 0x01]),
+            (
+                CODE,
+                &code(&[
+                    &[0x00, 0x41, 0x00, 0x11, 0x00, 0x00, 0x0b],
+                    &[0x00, 0x10, 0x02, 0x0b],
+                    &[0x00, 0x10, 0x01, 0x0b],
+                    &[0x00, 0x0b],
+                ]),
+            ),
+        ]);
+        let graph = CallGraph::from_module(&module).unwrap();
+
+        assert_eq!(graph.roots, vec![0]);
+        assert_eq!(graph.callees(0), &[1]);
+        assert_eq!(graph.callers(1), &[0, 2]);
+        assert!(graph.is_recursive(1) && !graph.is_recursive(0));
+        assert_eq!(
+            graph.strongly_connected_components(),
+            &[vec![1, 2], vec![0], vec![3]]
+        );
+        assert_eq!(
+            graph.reachable().into_iter().collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+    }
+}
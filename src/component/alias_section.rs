@@ -0,0 +1,66 @@
+use super::Sort;
+use byteorder::ReadBytesExt;
+use std::io::{Error, Read};
+use types::{read_index, read_name, read_vec};
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct AliasSection {
+    pub count: u32,
+    pub entries: Vec<Alias>,
+}
+
+impl AliasSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<AliasSection, Error> {
+        let entries = read_vec(reader, Alias::from_reader)?;
+
+        Ok(AliasSection {
+            count: entries.len() as u32,
+            entries,
+        })
+    }
+}
+
+/// Brings a definition from elsewhere into one of the local index spaces.
+#[derive(Debug, PartialEq, Serialize)]
+pub enum Alias {
+    InstanceExport {
+        sort: Sort,
+        instance: u32,
+        name: String,
+    },
+    CoreInstanceExport {
+        sort: Sort,
+        instance: u32,
+        name: String,
+    },
+    /// A definition of an enclosing component, `count` levels out.
+    Outer { sort: Sort, count: u32, index: u32 },
+}
+
+impl Alias {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<Alias, Error> {
+        let sort = Sort::from_reader(reader)?;
+
+        match reader.read_u8()? {
+            0x00 => Ok(Alias::InstanceExport {
+                sort,
+                instance: read_index(reader)?,
+                name: read_name(reader)?,
+            }),
+            0x01 => Ok(Alias::CoreInstanceExport {
+                sort,
+                instance: read_index(reader)?,
+                name: read_name(reader)?,
+            }),
+            0x02 => Ok(Alias::Outer {
+                sort,
+                count: read_index(reader)?,
+                index: read_index(reader)?,
+            }),
+            target => Err(Error::other(format!(
+                "Unknown alias target 0x{:02x}",
+                target
+            ))),
+        }
+    }
+}
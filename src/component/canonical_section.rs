@@ -0,0 +1,120 @@
+use byteorder::ReadBytesExt;
+use std::io::{Error, Read};
+use types::{read_index, read_vec};
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CanonicalSection {
+    pub count: u32,
+    pub entries: Vec<CanonicalFunction>,
+}
+
+impl CanonicalSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<CanonicalSection, Error> {
+        let entries = read_vec(reader, CanonicalFunction::from_reader)?;
+
+        Ok(CanonicalSection {
+            count: entries.len() as u32,
+            entries,
+        })
+    }
+}
+
+/// Functions produced by the canonical ABI, which adapts between core
+/// functions and component functions.
+#[derive(Debug, PartialEq, Serialize)]
+pub enum CanonicalFunction {
+    Lift {
+        core_func_index: u32,
+        options: Vec<CanonicalOption>,
+        type_index: u32,
+    },
+    Lower {
+        func_index: u32,
+        options: Vec<CanonicalOption>,
+    },
+    ResourceNew(u32),
+    ResourceDrop(u32),
+    ResourceDropAsync(u32),
+    ResourceRep(u32),
+}
+
+impl CanonicalFunction {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<CanonicalFunction, Error> {
+        match reader.read_u8()? {
+            0x00 => {
+                expect_byte(reader, 0x00)?;
+                let core_func_index = read_index(reader)?;
+                let options = read_vec(reader, CanonicalOption::from_reader)?;
+                let type_index = read_index(reader)?;
+                Ok(CanonicalFunction::Lift {
+                    core_func_index,
+                    options,
+                    type_index,
+                })
+            }
+            0x01 => {
+                expect_byte(reader, 0x00)?;
+                let func_index = read_index(reader)?;
+                let options = read_vec(reader, CanonicalOption::from_reader)?;
+                Ok(CanonicalFunction::Lower {
+                    func_index,
+                    options,
+                })
+            }
+            0x02 => Ok(CanonicalFunction::ResourceNew(read_index(reader)?)),
+            0x03 => Ok(CanonicalFunction::ResourceDrop(read_index(reader)?)),
+            0x07 => Ok(CanonicalFunction::ResourceDropAsync(read_index(reader)?)),
+            0x04 => Ok(CanonicalFunction::ResourceRep(read_index(reader)?)),
+            kind => Err(Error::other(format!(
+                "Unknown canonical function 0x{:02x}",
+                kind
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum CanonicalOption {
+    Utf8,
+    Utf16,
+    CompactUtf16,
+    Memory(u32),
+    Realloc(u32),
+    PostReturn(u32),
+    Async,
+    Callback(u32),
+    CoreType(u32),
+    Gc,
+}
+
+impl CanonicalOption {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<CanonicalOption, Error> {
+        match reader.read_u8()? {
+            0x00 => Ok(CanonicalOption::Utf8),
+            0x01 => Ok(CanonicalOption::Utf16),
+            0x02 => Ok(CanonicalOption::CompactUtf16),
+            0x03 => Ok(CanonicalOption::Memory(read_index(reader)?)),
+            0x04 => Ok(CanonicalOption::Realloc(read_index(reader)?)),
+            0x05 => Ok(CanonicalOption::PostReturn(read_index(reader)?)),
+            0x06 => Ok(CanonicalOption::Async),
+            0x07 => Ok(CanonicalOption::Callback(read_index(reader)?)),
+            0x08 => Ok(CanonicalOption::CoreType(read_index(reader)?)),
+            0x09 => Ok(CanonicalOption::Gc),
+            option => Err(Error::other(format!(
+                "Unknown canonical option 0x{:02x}",
+                option
+            ))),
+        }
+    }
+}
+
+fn expect_byte<T: Read>(reader: &mut T, expected: u8) -> Result<(), Error> {
+    let byte = reader.read_u8()?;
+    if byte != expected {
+        return Err(Error::other(format!(
+            "Expected 0x{:02x} but found 0x{:02x}",
+            expected, byte
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use super::{read_extern_name, ExternDesc, SortIndex};
+use byteorder::ReadBytesExt;
+use std::io::{Error, Read};
+use types::read_vec;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ComponentExportSection {
+    pub count: u32,
+    pub entries: Vec<ComponentExport>,
+}
+
+impl ComponentExportSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<ComponentExportSection, Error> {
+        let entries = read_vec(reader, ComponentExport::from_reader)?;
+
+        Ok(ComponentExportSection {
+            count: entries.len() as u32,
+            entries,
+        })
+    }
+}
+
+/// An export of a definition, optionally ascribed a more abstract type.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ComponentExport {
+    pub name: String,
+    pub sort_index: SortIndex,
+    pub desc: Option<ExternDesc>,
+}
+
+impl ComponentExport {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<ComponentExport, Error> {
+        let name = read_extern_name(reader)?;
+        let sort_index = SortIndex::from_reader(reader)?;
+        let desc = match reader.read_u8()? {
+            0x00 => None,
+            0x01 => Some(ExternDesc::from_reader(reader)?),
+            flag => {
+                return Err(Error::other(format!(
+                    "Invalid export type flag 0x{:02x}",
+                    flag
+                )))
+            }
+        };
+
+        Ok(ComponentExport {
+            name,
+            sort_index,
+            desc,
+        })
+    }
+}
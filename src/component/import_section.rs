@@ -0,0 +1,36 @@
+use super::{read_extern_name, ExternDesc};
+use std::io::{Error, Read};
+use types::read_vec;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ComponentImportSection {
+    pub count: u32,
+    pub entries: Vec<ComponentImport>,
+}
+
+impl ComponentImportSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<ComponentImportSection, Error> {
+        let entries = read_vec(reader, ComponentImport::from_reader)?;
+
+        Ok(ComponentImportSection {
+            count: entries.len() as u32,
+            entries,
+        })
+    }
+}
+
+/// An import with a WIT-style name such as `wasi:cli/stdout@0.2.0` or a
+/// plain kebab-case name.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ComponentImport {
+    pub name: String,
+    pub desc: ExternDesc,
+}
+
+impl ComponentImport {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<ComponentImport, Error> {
+        let name = read_extern_name(reader)?;
+        let desc = ExternDesc::from_reader(reader)?;
+        Ok(ComponentImport { name, desc })
+    }
+}
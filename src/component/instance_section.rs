@@ -0,0 +1,123 @@
+use super::{CoreSort, SortIndex};
+use byteorder::ReadBytesExt;
+use std::io::{Error, Read};
+use types::{read_index, read_name, read_vec};
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CoreInstanceSection {
+    pub count: u32,
+    pub entries: Vec<CoreInstance>,
+}
+
+impl CoreInstanceSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<CoreInstanceSection, Error> {
+        let entries = read_vec(reader, CoreInstance::from_reader)?;
+
+        Ok(CoreInstanceSection {
+            count: entries.len() as u32,
+            entries,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum CoreInstance {
+    /// Instantiates a core module, satisfying each import module name with
+    /// a core instance.
+    Instantiate {
+        module_index: u32,
+        args: Vec<(String, u32)>,
+    },
+    /// Bundles existing core definitions into an instance.
+    FromExports(Vec<(String, CoreSort, u32)>),
+}
+
+impl CoreInstance {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<CoreInstance, Error> {
+        match reader.read_u8()? {
+            0x00 => {
+                let module_index = read_index(reader)?;
+                let args = read_vec(reader, |reader| {
+                    let name = read_name(reader)?;
+                    match reader.read_u8()? {
+                        0x12 => Ok((name, read_index(reader)?)),
+                        kind => Err(Error::other(format!(
+                            "Invalid instantiation argument kind 0x{:02x}",
+                            kind
+                        ))),
+                    }
+                })?;
+                Ok(CoreInstance::Instantiate { module_index, args })
+            }
+            0x01 => Ok(CoreInstance::FromExports(read_vec(reader, |reader| {
+                let name = read_name(reader)?;
+                let sort = CoreSort::from_reader(reader)?;
+                let index = read_index(reader)?;
+                Ok((name, sort, index))
+            })?)),
+            kind => Err(Error::other(format!(
+                "Unknown core instance kind 0x{:02x}",
+                kind
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct InstanceSection {
+    pub count: u32,
+    pub entries: Vec<ComponentInstance>,
+}
+
+impl InstanceSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<InstanceSection, Error> {
+        let entries = read_vec(reader, ComponentInstance::from_reader)?;
+
+        Ok(InstanceSection {
+            count: entries.len() as u32,
+            entries,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum ComponentInstance {
+    Instantiate {
+        component_index: u32,
+        args: Vec<(String, SortIndex)>,
+    },
+    FromExports(Vec<(String, SortIndex)>),
+}
+
+impl ComponentInstance {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<ComponentInstance, Error> {
+        let read_arg = |reader: &mut T| {
+            let name = read_name(reader)?;
+            let sort_index = SortIndex::from_reader(reader)?;
+            Ok((name, sort_index))
+        };
+
+        match reader.read_u8()? {
+            0x00 => {
+                let component_index = read_index(reader)?;
+                let args = read_vec(reader, read_arg)?;
+                Ok(ComponentInstance::Instantiate {
+                    component_index,
+                    args,
+                })
+            }
+            0x01 => Ok(ComponentInstance::FromExports(read_vec(
+                reader,
+                |reader| {
+                    let name = super::read_extern_name(reader)?;
+                    let sort_index = SortIndex::from_reader(reader)?;
+                    Ok((name, sort_index))
+                },
+            )?)),
+            kind => Err(Error::other(format!(
+                "Unknown instance kind 0x{:02x}",
+                kind
+            ))),
+        }
+    }
+}
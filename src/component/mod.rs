@@ -0,0 +1,391 @@
+//! Component model binaries. A component shares the magic number of a core
+//! module but has version 0x0d and layer 1, and its sections describe how
+//! core modules and other components are instantiated and wired together.
+
+pub mod alias_section;
+pub mod canonical_section;
+pub mod export_section;
+pub mod import_section;
+pub mod instance_section;
+pub mod type_section;
+pub mod wit;
+
+pub use self::alias_section::{Alias, AliasSection};
+pub use self::canonical_section::{CanonicalFunction, CanonicalOption, CanonicalSection};
+pub use self::export_section::{ComponentExport, ComponentExportSection};
+pub use self::import_section::{ComponentImport, ComponentImportSection};
+pub use self::instance_section::{
+    ComponentInstance, CoreInstance, CoreInstanceSection, InstanceSection,
+};
+pub use self::type_section::{
+    ComponentFuncType, ComponentType, ComponentTypeDecl, ComponentTypeSection, ComponentValType,
+    CoreType, CoreTypeSection, DefinedType, LabeledType, ModuleTypeDecl, PrimitiveValType,
+    VariantCase,
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use leb128::ReadLeb128Ext;
+use std::io::{Cursor, Error, Read};
+use types::{read_bytes, read_index, read_name, read_vec, CustomSection, WasmModule};
+
+static WASM_MAGIC_NUMBER: u32 = 0x6d736100;
+pub static COMPONENT_VERSION: u16 = 0x0d;
+pub static COMPONENT_LAYER: u16 = 0x01;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Component {
+    pub version: u16,
+    pub layer: u16,
+    pub sections: Vec<ComponentSection>,
+}
+
+impl Component {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<Component, Error> {
+        let magic = reader.read_u32::<LittleEndian>()?;
+
+        if magic != WASM_MAGIC_NUMBER {
+            return Err(Error::other(format!(
+                "Magic number 0x{:x} is not the expected value 0x{:x}",
+                magic, WASM_MAGIC_NUMBER
+            )));
+        }
+
+        let version = reader.read_u16::<LittleEndian>()?;
+        let layer = reader.read_u16::<LittleEndian>()?;
+
+        if version != COMPONENT_VERSION || layer != COMPONENT_LAYER {
+            return Err(Error::other(format!(
+                "Unknown component version {} layer {}",
+                version, layer
+            )));
+        }
+
+        let mut sections = Vec::new();
+
+        while let Some(section) = ComponentSection::from_reader(reader)? {
+            sections.push(section);
+        }
+
+        Ok(Component {
+            version,
+            layer,
+            sections,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ComponentSection {
+    pub payload_len: u32,
+    pub name: Option<String>,
+    pub body: ComponentSectionBody,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum ComponentSectionBody {
+    Custom(Box<CustomSection>),
+    CoreModule(Box<WasmModule>),
+    CoreInstance(Box<CoreInstanceSection>),
+    CoreType(Box<CoreTypeSection>),
+    Component(Box<Component>),
+    Instance(Box<InstanceSection>),
+    Alias(Box<AliasSection>),
+    Type(Box<ComponentTypeSection>),
+    Canonical(Box<CanonicalSection>),
+    Start(Box<ComponentStart>),
+    Import(Box<ComponentImportSection>),
+    Export(Box<ComponentExportSection>),
+}
+
+impl ComponentSection {
+    fn from_reader<T: Read>(reader: &mut T) -> Result<Option<ComponentSection>, Error> {
+        let code = match reader.read_u8() {
+            Ok(code) => code,
+            Err(_) => return Ok(None),
+        };
+
+        let (payload_len, _) = reader.leb128_unsigned()?;
+        let payload = read_bytes(reader, payload_len as u64)?;
+
+        let mut payload = Cursor::new(payload);
+        let mut name = None;
+
+        let body = match code {
+            0 => {
+                name = Some(read_name(&mut payload)?);
+                let len = payload_len as usize - payload.position() as usize;
+                ComponentSectionBody::Custom(Box::new(CustomSection::from_reader(
+                    &mut payload,
+                    len,
+                )?))
+            }
+            // Core modules are complete binaries, header included.
+            1 => ComponentSectionBody::CoreModule(Box::new(::parse(payload)?)),
+            2 => ComponentSectionBody::CoreInstance(Box::new(CoreInstanceSection::from_reader(
+                &mut payload,
+            )?)),
+            3 => ComponentSectionBody::CoreType(Box::new(CoreTypeSection::from_reader(
+                &mut payload,
+            )?)),
+            4 => ComponentSectionBody::Component(Box::new(Component::from_reader(&mut payload)?)),
+            5 => ComponentSectionBody::Instance(Box::new(InstanceSection::from_reader(
+                &mut payload,
+            )?)),
+            6 => ComponentSectionBody::Alias(Box::new(AliasSection::from_reader(&mut payload)?)),
+            7 => ComponentSectionBody::Type(Box::new(ComponentTypeSection::from_reader(
+                &mut payload,
+            )?)),
+            8 => ComponentSectionBody::Canonical(Box::new(CanonicalSection::from_reader(
+                &mut payload,
+            )?)),
+            9 => ComponentSectionBody::Start(Box::new(ComponentStart::from_reader(&mut payload)?)),
+            10 => ComponentSectionBody::Import(Box::new(ComponentImportSection::from_reader(
+                &mut payload,
+            )?)),
+            11 => ComponentSectionBody::Export(Box::new(ComponentExportSection::from_reader(
+                &mut payload,
+            )?)),
+            _ => {
+                return Err(Error::other(format!(
+                    "Unknown component section id {}",
+                    code
+                )))
+            }
+        };
+
+        Ok(Some(ComponentSection {
+            payload_len: payload_len as u32,
+            name,
+            body,
+        }))
+    }
+}
+
+/// The kinds of core definitions a component can refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum CoreSort {
+    Func,
+    Table,
+    Memory,
+    Global,
+    Tag,
+    Type,
+    Module,
+    Instance,
+}
+
+impl CoreSort {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<CoreSort, Error> {
+        match reader.read_u8()? {
+            0x00 => Ok(CoreSort::Func),
+            0x01 => Ok(CoreSort::Table),
+            0x02 => Ok(CoreSort::Memory),
+            0x03 => Ok(CoreSort::Global),
+            0x04 => Ok(CoreSort::Tag),
+            0x10 => Ok(CoreSort::Type),
+            0x11 => Ok(CoreSort::Module),
+            0x12 => Ok(CoreSort::Instance),
+            sort => Err(Error::other(format!("Unknown core sort 0x{:02x}", sort))),
+        }
+    }
+}
+
+/// The index spaces of a component. Core definitions live in separate
+/// spaces from their component-level counterparts.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Sort {
+    Core(CoreSort),
+    Func,
+    Value,
+    Type,
+    Component,
+    Instance,
+}
+
+impl Sort {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<Sort, Error> {
+        match reader.read_u8()? {
+            0x00 => Ok(Sort::Core(CoreSort::from_reader(reader)?)),
+            0x01 => Ok(Sort::Func),
+            0x02 => Ok(Sort::Value),
+            0x03 => Ok(Sort::Type),
+            0x04 => Ok(Sort::Component),
+            0x05 => Ok(Sort::Instance),
+            sort => Err(Error::other(format!("Unknown sort 0x{:02x}", sort))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SortIndex {
+    pub sort: Sort,
+    pub index: u32,
+}
+
+impl SortIndex {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<SortIndex, Error> {
+        let sort = Sort::from_reader(reader)?;
+        let index = read_index(reader)?;
+        Ok(SortIndex { sort, index })
+    }
+}
+
+/// The type of an import or export.
+#[derive(Debug, PartialEq, Serialize)]
+pub enum ExternDesc {
+    Module(u32),
+    Func(u32),
+    Value(ValueBound),
+    Type(TypeBound),
+    Component(u32),
+    Instance(u32),
+}
+
+impl ExternDesc {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<ExternDesc, Error> {
+        match Sort::from_reader(reader)? {
+            Sort::Core(CoreSort::Module) => Ok(ExternDesc::Module(read_index(reader)?)),
+            Sort::Func => Ok(ExternDesc::Func(read_index(reader)?)),
+            Sort::Value => Ok(ExternDesc::Value(ValueBound::from_reader(reader)?)),
+            Sort::Type => Ok(ExternDesc::Type(TypeBound::from_reader(reader)?)),
+            Sort::Component => Ok(ExternDesc::Component(read_index(reader)?)),
+            Sort::Instance => Ok(ExternDesc::Instance(read_index(reader)?)),
+            sort => Err(Error::other(format!(
+                "{:?} cannot be imported or exported",
+                sort
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum ValueBound {
+    Eq(u32),
+    Type(ComponentValType),
+}
+
+impl ValueBound {
+    fn from_reader<T: Read>(reader: &mut T) -> Result<ValueBound, Error> {
+        match reader.read_u8()? {
+            0x00 => Ok(ValueBound::Eq(read_index(reader)?)),
+            0x01 => Ok(ValueBound::Type(ComponentValType::from_reader(reader)?)),
+            bound => Err(Error::other(format!("Unknown value bound 0x{:02x}", bound))),
+        }
+    }
+}
+
+/// An imported type is either equal to a known type or a fresh resource.
+#[derive(Debug, PartialEq, Serialize)]
+pub enum TypeBound {
+    Eq(u32),
+    SubResource,
+}
+
+impl TypeBound {
+    fn from_reader<T: Read>(reader: &mut T) -> Result<TypeBound, Error> {
+        match reader.read_u8()? {
+            0x00 => Ok(TypeBound::Eq(read_index(reader)?)),
+            0x01 => Ok(TypeBound::SubResource),
+            bound => Err(Error::other(format!("Unknown type bound 0x{:02x}", bound))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ComponentStart {
+    pub func_index: u32,
+    pub arguments: Vec<u32>,
+    pub results: u32,
+}
+
+impl ComponentStart {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<ComponentStart, Error> {
+        let func_index = read_index(reader)?;
+        let arguments = read_vec(reader, read_index)?;
+        let results = read_index(reader)?;
+
+        Ok(ComponentStart {
+            func_index,
+            arguments,
+            results,
+        })
+    }
+}
+
+/// Reads an import or export name. A leading 0x01 marked interface names
+/// in older binaries and is otherwise treated like 0x00.
+pub fn read_extern_name<T: Read>(reader: &mut T) -> Result<String, Error> {
+    match reader.read_u8()? {
+        0x00 | 0x01 => read_name(reader),
+        kind => Err(Error::other(format!("Unknown name kind 0x{:02x}", kind))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::*;
+    use types::WasmSectionBody;
+
+    #[test]
+    fn test_parse_component() {
+        let bytes = [
+            0x00, 0x61, 0x73, 0x6d, 0x0d, 0x00, 0x01, 0x00, // header
+            0x01, 0x08, 0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // core module
+            0x07, 0x06, 0x01, 0x72, 0x01, 0x01, 0x61, 0x79, // (record (field "a" u32))
+            0x0a, 0x07, 0x01, 0x00, 0x01, 0x78, 0x03, 0x00,
+            0x00, // (import "x" (type (eq 0)))
+        ];
+
+        let component = Component::from_reader(&mut Cursor::new(&bytes[..])).unwrap();
+        assert_eq!(component.sections.len(), 3);
+
+        match component.sections[0].body {
+            ComponentSectionBody::CoreModule(ref module) => assert_eq!(module.version, 1),
+            ref body => panic!("unexpected section {:?}", body),
+        }
+
+        match component.sections[1].body {
+            ComponentSectionBody::Type(ref section) => assert_eq!(
+                section.entries,
+                vec![ComponentType::Defined(DefinedType::Record(vec![
+                    LabeledType {
+                        name: "a".to_string(),
+                        ty: ComponentValType::Primitive(PrimitiveValType::U32),
+                    },
+                ]))]
+            ),
+            ref body => panic!("unexpected section {:?}", body),
+        }
+
+        match component.sections[2].body {
+            ComponentSectionBody::Import(ref section) => assert_eq!(
+                section.entries,
+                vec![ComponentImport {
+                    name: "x".to_string(),
+                    desc: ExternDesc::Type(TypeBound::Eq(0)),
+                }]
+            ),
+            ref body => panic!("unexpected section {:?}", body),
+        }
+    }
+
+    #[test]
+    fn test_malformed_core_module() {
+        let bytes = [
+            0x00, 0x61, 0x73, 0x6d, 0x0d, 0x00, 0x01, 0x00, // header
+            0x01, 0x0b, 0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // core module
+            0x01, 0x01, 0x05, // with a truncated type section
+        ];
+
+        assert!(Component::from_reader(&mut Cursor::new(&bytes[..])).is_err());
+
+        // Embedded in a module, the component-type section is kept as raw bytes.
+        let module = module(&[(CUSTOM, &custom("component-type:x", &bytes))]);
+        match module.sections[0].body {
+            WasmSectionBody::Custom(_) => {}
+            ref body => panic!("unexpected section {:?}", body),
+        }
+    }
+}
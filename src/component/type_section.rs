@@ -0,0 +1,392 @@
+use super::{read_extern_name, Alias, ComponentImport, ExternDesc};
+use byteorder::ReadBytesExt;
+use leb128::ReadLeb128Ext;
+use std::io::{Error, Read};
+use types::import_section::ImportEntry;
+use types::{read_index, read_name, read_vec, ExternalKind, RecGroup, ValueType};
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CoreTypeSection {
+    pub count: u32,
+    pub entries: Vec<CoreType>,
+}
+
+impl CoreTypeSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<CoreTypeSection, Error> {
+        let entries = read_vec(reader, CoreType::from_reader)?;
+
+        Ok(CoreTypeSection {
+            count: entries.len() as u32,
+            entries,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum CoreType {
+    Rec(RecGroup),
+    Module(Vec<ModuleTypeDecl>),
+}
+
+impl CoreType {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<CoreType, Error> {
+        // 0x50 is both a module type and a non-final GC sub type, so the
+        // latter is prefixed with 0x00 at the top level of a component.
+        match reader.read_u8()? {
+            0x00 => match reader.read_u8()? {
+                0x50 => Ok(CoreType::Rec(RecGroup::from_reader(
+                    &mut (&[0x50][..]).chain(&mut *reader),
+                )?)),
+                byte => Err(Error::other(format!(
+                    "Expected a non-final sub type but found 0x{:02x}",
+                    byte
+                ))),
+            },
+            0x50 => Ok(CoreType::Module(read_vec(
+                reader,
+                ModuleTypeDecl::from_reader,
+            )?)),
+            byte => Ok(CoreType::Rec(RecGroup::from_reader(
+                &mut (&[byte][..]).chain(&mut *reader),
+            )?)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum ModuleTypeDecl {
+    Import(ImportEntry),
+    Type(RecGroup),
+    OuterAlias { count: u32, index: u32 },
+    Export { name: String, kind: ExternalKind },
+}
+
+impl ModuleTypeDecl {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<ModuleTypeDecl, Error> {
+        match reader.read_u8()? {
+            0x00 => Ok(ModuleTypeDecl::Import(ImportEntry::from_reader(reader)?)),
+            0x01 => Ok(ModuleTypeDecl::Type(RecGroup::from_reader(reader)?)),
+            0x02 => {
+                let (kind, target) = (reader.read_u8()?, reader.read_u8()?);
+                if kind != 0x10 || target != 0x01 {
+                    return Err(Error::other("Module types may only alias outer types"));
+                }
+                Ok(ModuleTypeDecl::OuterAlias {
+                    count: read_index(reader)?,
+                    index: read_index(reader)?,
+                })
+            }
+            0x03 => Ok(ModuleTypeDecl::Export {
+                name: read_name(reader)?,
+                kind: ExternalKind::from_reader(reader)?,
+            }),
+            decl => Err(Error::other(format!(
+                "Unknown module type declaration 0x{:02x}",
+                decl
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ComponentTypeSection {
+    pub count: u32,
+    pub entries: Vec<ComponentType>,
+}
+
+impl ComponentTypeSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<ComponentTypeSection, Error> {
+        let entries = read_vec(reader, ComponentType::from_reader)?;
+
+        Ok(ComponentTypeSection {
+            count: entries.len() as u32,
+            entries,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum ComponentType {
+    Defined(DefinedType),
+    Func(ComponentFuncType),
+    Component(Vec<ComponentTypeDecl>),
+    Instance(Vec<ComponentTypeDecl>),
+    /// A resource represented by a core value, with an optional destructor.
+    Resource {
+        rep: ValueType,
+        dtor: Option<u32>,
+    },
+}
+
+impl ComponentType {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<ComponentType, Error> {
+        match reader.read_u8()? {
+            0x3f => {
+                let rep = ValueType::from_reader(reader)?;
+                let dtor = match reader.read_u8()? {
+                    0x00 => None,
+                    0x01 => Some(read_index(reader)?),
+                    flag => {
+                        return Err(Error::other(format!(
+                            "Invalid destructor flag 0x{:02x}",
+                            flag
+                        )))
+                    }
+                };
+                Ok(ComponentType::Resource { rep, dtor })
+            }
+            0x40 => Ok(ComponentType::Func(ComponentFuncType::from_reader(
+                reader, false,
+            )?)),
+            0x43 => Ok(ComponentType::Func(ComponentFuncType::from_reader(
+                reader, true,
+            )?)),
+            0x41 => Ok(ComponentType::Component(read_vec(reader, |reader| {
+                ComponentTypeDecl::from_reader(reader, true)
+            })?)),
+            0x42 => Ok(ComponentType::Instance(read_vec(reader, |reader| {
+                ComponentTypeDecl::from_reader(reader, false)
+            })?)),
+            code => Ok(ComponentType::Defined(DefinedType::from_code(
+                code, reader,
+            )?)),
+        }
+    }
+}
+
+/// A declaration within a component or instance type. Only component types
+/// may declare imports.
+#[derive(Debug, PartialEq, Serialize)]
+pub enum ComponentTypeDecl {
+    CoreType(CoreType),
+    Type(ComponentType),
+    Alias(Alias),
+    Import(ComponentImport),
+    Export { name: String, desc: ExternDesc },
+}
+
+impl ComponentTypeDecl {
+    fn from_reader<T: Read>(reader: &mut T, imports: bool) -> Result<ComponentTypeDecl, Error> {
+        match reader.read_u8()? {
+            0x00 => Ok(ComponentTypeDecl::CoreType(CoreType::from_reader(reader)?)),
+            0x01 => Ok(ComponentTypeDecl::Type(ComponentType::from_reader(reader)?)),
+            0x02 => Ok(ComponentTypeDecl::Alias(Alias::from_reader(reader)?)),
+            0x03 if imports => Ok(ComponentTypeDecl::Import(ComponentImport::from_reader(
+                reader,
+            )?)),
+            0x04 => Ok(ComponentTypeDecl::Export {
+                name: read_extern_name(reader)?,
+                desc: ExternDesc::from_reader(reader)?,
+            }),
+            decl => Err(Error::other(format!(
+                "Unknown type declaration 0x{:02x}",
+                decl
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ComponentFuncType {
+    pub is_async: bool,
+    pub params: Vec<LabeledType>,
+    pub result: Option<ComponentValType>,
+}
+
+impl ComponentFuncType {
+    fn from_reader<T: Read>(reader: &mut T, is_async: bool) -> Result<ComponentFuncType, Error> {
+        let params = read_vec(reader, LabeledType::from_reader)?;
+        let result = match reader.read_u8()? {
+            0x00 => Some(ComponentValType::from_reader(reader)?),
+            0x01 => match reader.read_u8()? {
+                0x00 => None,
+                _ => return Err(Error::other("Functions may have at most one result")),
+            },
+            flag => {
+                return Err(Error::other(format!(
+                    "Invalid function result flag 0x{:02x}",
+                    flag
+                )))
+            }
+        };
+
+        Ok(ComponentFuncType {
+            is_async,
+            params,
+            result,
+        })
+    }
+}
+
+/// A named parameter or record field.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct LabeledType {
+    pub name: String,
+    pub ty: ComponentValType,
+}
+
+impl LabeledType {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<LabeledType, Error> {
+        let name = read_name(reader)?;
+        let ty = ComponentValType::from_reader(reader)?;
+        Ok(LabeledType { name, ty })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum PrimitiveValType {
+    Bool,
+    S8,
+    U8,
+    S16,
+    U16,
+    S32,
+    U32,
+    S64,
+    U64,
+    F32,
+    F64,
+    Char,
+    String,
+    ErrorContext,
+}
+
+impl PrimitiveValType {
+    fn from_code(code: u8) -> Option<PrimitiveValType> {
+        match code {
+            0x7f => Some(PrimitiveValType::Bool),
+            0x7e => Some(PrimitiveValType::S8),
+            0x7d => Some(PrimitiveValType::U8),
+            0x7c => Some(PrimitiveValType::S16),
+            0x7b => Some(PrimitiveValType::U16),
+            0x7a => Some(PrimitiveValType::S32),
+            0x79 => Some(PrimitiveValType::U32),
+            0x78 => Some(PrimitiveValType::S64),
+            0x77 => Some(PrimitiveValType::U64),
+            0x76 => Some(PrimitiveValType::F32),
+            0x75 => Some(PrimitiveValType::F64),
+            0x74 => Some(PrimitiveValType::Char),
+            0x73 => Some(PrimitiveValType::String),
+            0x64 => Some(PrimitiveValType::ErrorContext),
+            _ => None,
+        }
+    }
+}
+
+/// A value type is either primitive or refers to a defined type by index.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum ComponentValType {
+    Primitive(PrimitiveValType),
+    Type(u32),
+}
+
+impl ComponentValType {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<ComponentValType, Error> {
+        // Primitive codes are single byte negative s33 values, type indices
+        // are never negative.
+        let (num, _) = reader.leb128_signed()?;
+
+        if num >= 0 {
+            return Ok(ComponentValType::Type(num as u32));
+        }
+
+        PrimitiveValType::from_code((num & 0x7f) as u8)
+            .map(ComponentValType::Primitive)
+            .ok_or_else(|| Error::other(format!("Unknown value type {}", num)))
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct VariantCase {
+    pub name: String,
+    pub ty: Option<ComponentValType>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum DefinedType {
+    Primitive(PrimitiveValType),
+    Record(Vec<LabeledType>),
+    Variant(Vec<VariantCase>),
+    List(ComponentValType),
+    FixedList(ComponentValType, u32),
+    Map(ComponentValType, ComponentValType),
+    Tuple(Vec<ComponentValType>),
+    Flags(Vec<String>),
+    Enum(Vec<String>),
+    Option(ComponentValType),
+    Result {
+        ok: Option<ComponentValType>,
+        err: Option<ComponentValType>,
+    },
+    Own(u32),
+    Borrow(u32),
+    Stream(Option<ComponentValType>),
+    Future(Option<ComponentValType>),
+}
+
+impl DefinedType {
+    fn from_code<T: Read>(code: u8, reader: &mut T) -> Result<DefinedType, Error> {
+        if let Some(primitive) = PrimitiveValType::from_code(code) {
+            return Ok(DefinedType::Primitive(primitive));
+        }
+
+        match code {
+            0x72 => Ok(DefinedType::Record(read_vec(
+                reader,
+                LabeledType::from_reader,
+            )?)),
+            0x71 => Ok(DefinedType::Variant(read_vec(reader, |reader| {
+                let name = read_name(reader)?;
+                let ty = read_optional(reader)?;
+                // Cases used to be able to refine an earlier case.
+                if reader.read_u8()? != 0x00 {
+                    return Err(Error::other("Variant case refinements are not supported"));
+                }
+                Ok(VariantCase { name, ty })
+            })?)),
+            0x70 => Ok(DefinedType::List(ComponentValType::from_reader(reader)?)),
+            0x67 => {
+                let ty = ComponentValType::from_reader(reader)?;
+                Ok(DefinedType::FixedList(ty, read_index(reader)?))
+            }
+            0x63 => {
+                let key = ComponentValType::from_reader(reader)?;
+                Ok(DefinedType::Map(
+                    key,
+                    ComponentValType::from_reader(reader)?,
+                ))
+            }
+            0x6f => Ok(DefinedType::Tuple(read_vec(
+                reader,
+                ComponentValType::from_reader,
+            )?)),
+            0x6e => Ok(DefinedType::Flags(read_vec(reader, read_name)?)),
+            0x6d => Ok(DefinedType::Enum(read_vec(reader, read_name)?)),
+            0x6b => Ok(DefinedType::Option(ComponentValType::from_reader(reader)?)),
+            0x6a => {
+                let ok = read_optional(reader)?;
+                let err = read_optional(reader)?;
+                Ok(DefinedType::Result { ok, err })
+            }
+            0x69 => Ok(DefinedType::Own(read_index(reader)?)),
+            0x68 => Ok(DefinedType::Borrow(read_index(reader)?)),
+            0x66 => Ok(DefinedType::Stream(read_optional(reader)?)),
+            0x65 => Ok(DefinedType::Future(read_optional(reader)?)),
+            _ => Err(Error::other(format!(
+                "Unknown component type 0x{:02x}",
+                code
+            ))),
+        }
+    }
+}
+
+fn read_optional<T: Read>(reader: &mut T) -> Result<Option<ComponentValType>, Error> {
+    match reader.read_u8()? {
+        0x00 => Ok(None),
+        0x01 => Ok(Some(ComponentValType::from_reader(reader)?)),
+        flag => Err(Error::other(format!(
+            "Invalid optional type flag 0x{:02x}",
+            flag
+        ))),
+    }
+}
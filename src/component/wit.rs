@@ -0,0 +1,712 @@
+//! WIT rendering for the `component-type:*` custom sections that
+//! wit-bindgen embeds in core modules. The section holds a component whose
+//! type exports the world the module was built against.
+
+use super::{
+    Alias, Component, ComponentFuncType, ComponentSectionBody, ComponentType, ComponentTypeDecl,
+    ComponentValType, DefinedType, ExternDesc, PrimitiveValType, Sort, TypeBound,
+};
+use std::collections::HashMap;
+use std::io::{Error, Read};
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ComponentTypeMetadata {
+    pub component: Component,
+}
+
+impl ComponentTypeMetadata {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<ComponentTypeMetadata, Error> {
+        let component = Component::from_reader(reader)?;
+        Ok(ComponentTypeMetadata { component })
+    }
+
+    /// Renders the world and the interfaces it uses as WIT text.
+    pub fn to_wit(&self) -> Result<String, Error> {
+        render_wit(&self.component)
+    }
+}
+
+/// Renders the world exported by a component's outermost component type.
+pub fn render_wit(component: &Component) -> Result<String, Error> {
+    let mut outer = None;
+    for section in &component.sections {
+        if let ComponentSectionBody::Type(ref section) = section.body {
+            for ty in &section.entries {
+                if let ComponentType::Component(ref decls) = *ty {
+                    outer = Some(decls);
+                }
+            }
+        }
+    }
+    let outer = outer.ok_or_else(|| Error::other("No component type found"))?;
+
+    let types: Vec<&ComponentType> = outer
+        .iter()
+        .filter_map(|decl| match *decl {
+            ComponentTypeDecl::Type(ref ty) => Some(ty),
+            _ => None,
+        })
+        .collect();
+
+    let (name, world) = outer
+        .iter()
+        .filter_map(|decl| match *decl {
+            ComponentTypeDecl::Export {
+                ref name,
+                desc: ExternDesc::Component(index),
+            } => match types.get(index as usize) {
+                Some(ComponentType::Component(decls)) => Some((name, decls)),
+                _ => None,
+            },
+            _ => None,
+        })
+        .next()
+        .ok_or_else(|| Error::other("Component type does not export a world"))?;
+
+    let (package, world_name) =
+        split_name(name).ok_or_else(|| Error::other(format!("Invalid world name {}", name)))?;
+
+    let mut renderer = Renderer {
+        scopes: Vec::new(),
+        interfaces: Vec::new(),
+    };
+    let (body, _) = renderer.decls(world, None)?;
+
+    let mut out = format!("package {};\n", package);
+    let mut foreign: Vec<(String, Vec<String>)> = Vec::new();
+
+    for (name, body) in renderer.interfaces {
+        let (iface_package, short) = split_name(&name).unwrap();
+        let mut lines = vec![format!("interface {} {{", ident(&short))];
+        lines.extend(indent(body));
+        lines.push("}".to_string());
+
+        if iface_package == package {
+            out.push('\n');
+            push_lines(&mut out, &lines);
+        } else {
+            match foreign.iter().position(|(p, _)| *p == iface_package) {
+                Some(position) => foreign[position].1.extend(lines),
+                None => foreign.push((iface_package, lines)),
+            }
+        }
+    }
+
+    for (package, lines) in foreign {
+        out.push('\n');
+        let mut block = vec![format!("package {} {{", package)];
+        block.extend(indent(lines));
+        block.push("}".to_string());
+        push_lines(&mut out, &block);
+    }
+
+    out.push('\n');
+    let mut lines = vec![format!("world {} {{", ident(&world_name))];
+    lines.extend(indent(body));
+    lines.push("}".to_string());
+    push_lines(&mut out, &lines);
+
+    Ok(out)
+}
+
+#[derive(Clone, Copy)]
+enum Def<'a> {
+    Defined(&'a DefinedType),
+    Func(&'a ComponentFuncType),
+    Instance(&'a [ComponentTypeDecl]),
+    Resource,
+    Unknown,
+}
+
+/// A type in one of the index spaces, with the name and interface it was
+/// exported under.
+#[derive(Clone)]
+struct TypeInfo<'a> {
+    name: Option<String>,
+    interface: Option<String>,
+    def: Def<'a>,
+}
+
+#[derive(Default)]
+struct Scope<'a> {
+    types: Vec<TypeInfo<'a>>,
+    instances: Vec<HashMap<String, TypeInfo<'a>>>,
+}
+
+enum Item {
+    Line(String),
+    Resource(String),
+}
+
+struct Renderer<'a> {
+    scopes: Vec<Scope<'a>>,
+    /// Interfaces referred to by their full name, in order of appearance.
+    interfaces: Vec<(String, Vec<String>)>,
+}
+
+impl<'a> Renderer<'a> {
+    /// Renders the declarations of a world, or of an interface when `owner`
+    /// is its name, and returns the types it exports by name.
+    fn decls(
+        &mut self,
+        decls: &'a [ComponentTypeDecl],
+        owner: Option<&str>,
+    ) -> Result<(Vec<String>, HashMap<String, TypeInfo<'a>>), Error> {
+        self.scopes.push(Scope::default());
+
+        let mut items = Vec::new();
+        let mut methods: HashMap<String, Vec<String>> = HashMap::new();
+        let mut exports = HashMap::new();
+
+        for decl in decls {
+            let (prefix, name, desc) = match *decl {
+                ComponentTypeDecl::CoreType(_) => continue,
+                ComponentTypeDecl::Type(ref ty) => {
+                    let def = match *ty {
+                        ComponentType::Defined(ref defined) => Def::Defined(defined),
+                        ComponentType::Func(ref func) => Def::Func(func),
+                        ComponentType::Instance(ref decls) => Def::Instance(decls),
+                        ComponentType::Resource { .. } => Def::Resource,
+                        ComponentType::Component(_) => Def::Unknown,
+                    };
+                    self.push_type(TypeInfo {
+                        name: None,
+                        interface: None,
+                        def,
+                    });
+                    continue;
+                }
+                ComponentTypeDecl::Alias(ref alias) => {
+                    self.alias(alias);
+                    continue;
+                }
+                ComponentTypeDecl::Import(ref import) => ("import ", &import.name, &import.desc),
+                ComponentTypeDecl::Export { ref name, ref desc } => {
+                    (if owner.is_some() { "" } else { "export " }, name, desc)
+                }
+            };
+
+            match *desc {
+                ExternDesc::Type(TypeBound::Eq(index)) => {
+                    let target = self.type_info(index);
+                    match target.interface {
+                        Some(ref interface) if Some(interface.as_str()) != owner => {
+                            let used = target.name.clone().unwrap_or_else(|| name.clone());
+                            let used = if used == *name {
+                                ident(name)
+                            } else {
+                                format!("{} as {}", ident(&used), ident(name))
+                            };
+                            items.push(Item::Line(format!("use {}.{{{}}};", interface, used)));
+                        }
+                        _ => match target.name {
+                            Some(ref target) => items.push(Item::Line(format!(
+                                "type {} = {};",
+                                ident(name),
+                                ident(target)
+                            ))),
+                            None => {
+                                for line in self.typedef(name, target.def) {
+                                    items.push(Item::Line(line));
+                                }
+                            }
+                        },
+                    }
+
+                    let info = TypeInfo {
+                        name: Some(name.clone()),
+                        interface: owner.map(|owner| owner.to_string()),
+                        def: target.def,
+                    };
+                    exports.insert(name.clone(), info.clone());
+                    self.push_type(info);
+                }
+                ExternDesc::Type(TypeBound::SubResource) => {
+                    items.push(Item::Resource(name.clone()));
+
+                    let info = TypeInfo {
+                        name: Some(name.clone()),
+                        interface: owner.map(|owner| owner.to_string()),
+                        def: Def::Resource,
+                    };
+                    exports.insert(name.clone(), info.clone());
+                    self.push_type(info);
+                }
+                ExternDesc::Func(index) => {
+                    let func = match self.type_info(index).def {
+                        Def::Func(func) => func,
+                        _ => return Err(Error::other(format!("Type {} is not a function", index))),
+                    };
+
+                    match self.resource_method(name, func) {
+                        Some((resource, line)) => methods.entry(resource).or_default().push(line),
+                        None => items.push(Item::Line(format!(
+                            "{}{}: {};",
+                            prefix,
+                            ident(name),
+                            self.func(func, false)
+                        ))),
+                    }
+                }
+                ExternDesc::Instance(index) => {
+                    let instance = match self.type_info(index).def {
+                        Def::Instance(decls) => decls,
+                        _ => {
+                            return Err(Error::other(format!("Type {} is not an instance", index)))
+                        }
+                    };
+                    let (body, instance_exports) = self.decls(instance, Some(name))?;
+
+                    if split_name(name).is_some() {
+                        if !self.interfaces.iter().any(|(known, _)| known == name) {
+                            self.interfaces.push((name.clone(), body));
+                        }
+                        items.push(Item::Line(format!("{}{};", prefix, name)));
+                    } else {
+                        items.push(Item::Line(format!(
+                            "{}{}: interface {{",
+                            prefix,
+                            ident(name)
+                        )));
+                        items.extend(indent(body).into_iter().map(Item::Line));
+                        items.push(Item::Line("}".to_string()));
+                    }
+
+                    self.scope().instances.push(instance_exports);
+                }
+                _ => {}
+            }
+        }
+
+        self.scopes.pop();
+
+        let mut lines = Vec::new();
+        for item in items {
+            match item {
+                Item::Line(line) => lines.push(line),
+                Item::Resource(name) => match methods.remove(&name) {
+                    Some(body) => {
+                        lines.push(format!("resource {} {{", ident(&name)));
+                        lines.extend(indent(body));
+                        lines.push("}".to_string());
+                    }
+                    None => lines.push(format!("resource {};", ident(&name))),
+                },
+            }
+        }
+
+        Ok((lines, exports))
+    }
+
+    fn alias(&mut self, alias: &Alias) {
+        let info = match *alias {
+            Alias::Outer {
+                sort: Sort::Type,
+                count,
+                index,
+            } => {
+                let depth = self.scopes.len().checked_sub(count as usize + 1);
+                depth
+                    .and_then(|depth| self.scopes[depth].types.get(index as usize))
+                    .cloned()
+            }
+            Alias::InstanceExport {
+                sort: Sort::Type,
+                instance,
+                ref name,
+            } => self
+                .scope()
+                .instances
+                .get(instance as usize)
+                .and_then(|exports| exports.get(name))
+                .cloned(),
+            _ => return,
+        };
+
+        self.push_type(info.unwrap_or(TypeInfo {
+            name: None,
+            interface: None,
+            def: Def::Unknown,
+        }));
+    }
+
+    fn scope(&mut self) -> &mut Scope<'a> {
+        self.scopes.last_mut().unwrap()
+    }
+
+    fn push_type(&mut self, info: TypeInfo<'a>) {
+        self.scope().types.push(info);
+    }
+
+    fn type_info(&self, index: u32) -> TypeInfo<'a> {
+        self.scopes
+            .last()
+            .and_then(|scope| scope.types.get(index as usize))
+            .cloned()
+            .unwrap_or(TypeInfo {
+                name: None,
+                interface: None,
+                def: Def::Unknown,
+            })
+    }
+
+    fn typedef(&self, name: &str, def: Def) -> Vec<String> {
+        let name = ident(name);
+        let block = |keyword: &str, cases: Vec<String>| {
+            let mut lines = vec![format!("{} {} {{", keyword, name)];
+            lines.extend(cases.into_iter().map(|case| format!("  {},", case)));
+            lines.push("}".to_string());
+            lines
+        };
+
+        match def {
+            Def::Defined(DefinedType::Record(fields)) => block(
+                "record",
+                fields
+                    .iter()
+                    .map(|field| format!("{}: {}", ident(&field.name), self.val_type(field.ty)))
+                    .collect(),
+            ),
+            Def::Defined(DefinedType::Variant(cases)) => block(
+                "variant",
+                cases
+                    .iter()
+                    .map(|case| match case.ty {
+                        Some(ty) => format!("{}({})", ident(&case.name), self.val_type(ty)),
+                        None => ident(&case.name),
+                    })
+                    .collect(),
+            ),
+            Def::Defined(DefinedType::Enum(names)) => {
+                block("enum", names.iter().map(|name| ident(name)).collect())
+            }
+            Def::Defined(DefinedType::Flags(names)) => {
+                block("flags", names.iter().map(|name| ident(name)).collect())
+            }
+            Def::Resource => vec![format!("resource {};", name)],
+            def => vec![format!("type {} = {};", name, self.inline(def))],
+        }
+    }
+
+    fn val_type(&self, ty: ComponentValType) -> String {
+        match ty {
+            ComponentValType::Primitive(primitive) => primitive_name(primitive).to_string(),
+            ComponentValType::Type(index) => self.type_name(index),
+        }
+    }
+
+    fn type_name(&self, index: u32) -> String {
+        let info = self.type_info(index);
+        match info.name {
+            Some(ref name) => ident(name),
+            None => self.inline(info.def),
+        }
+    }
+
+    /// Renders an anonymous type.
+    fn inline(&self, def: Def) -> String {
+        let defined = match def {
+            Def::Defined(defined) => defined,
+            Def::Func(func) => return self.func(func, false),
+            _ => return "unknown".to_string(),
+        };
+        let optional = |keyword: &str, ty: Option<ComponentValType>| match ty {
+            Some(ty) => format!("{}<{}>", keyword, self.val_type(ty)),
+            None => keyword.to_string(),
+        };
+
+        match *defined {
+            DefinedType::Primitive(primitive) => primitive_name(primitive).to_string(),
+            DefinedType::List(ty) => format!("list<{}>", self.val_type(ty)),
+            DefinedType::FixedList(ty, len) => format!("list<{}, {}>", self.val_type(ty), len),
+            DefinedType::Map(key, value) => {
+                format!("map<{}, {}>", self.val_type(key), self.val_type(value))
+            }
+            DefinedType::Tuple(ref types) => format!(
+                "tuple<{}>",
+                types
+                    .iter()
+                    .map(|&ty| self.val_type(ty))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            DefinedType::Option(ty) => format!("option<{}>", self.val_type(ty)),
+            DefinedType::Result { ok, err } => match (ok, err) {
+                (None, None) => "result".to_string(),
+                (Some(ok), None) => format!("result<{}>", self.val_type(ok)),
+                (None, Some(err)) => format!("result<_, {}>", self.val_type(err)),
+                (Some(ok), Some(err)) => {
+                    format!("result<{}, {}>", self.val_type(ok), self.val_type(err))
+                }
+            },
+            DefinedType::Own(index) => self.type_name(index),
+            DefinedType::Borrow(index) => format!("borrow<{}>", self.type_name(index)),
+            DefinedType::Stream(ty) => optional("stream", ty),
+            DefinedType::Future(ty) => optional("future", ty),
+            DefinedType::Record(_)
+            | DefinedType::Variant(_)
+            | DefinedType::Enum(_)
+            | DefinedType::Flags(_) => "unknown".to_string(),
+        }
+    }
+
+    /// Renders a function signature, dropping the `self` parameter of
+    /// methods.
+    fn func(&self, func: &ComponentFuncType, method: bool) -> String {
+        format!(
+            "{}func({}){}",
+            if func.is_async { "async " } else { "" },
+            self.params(func, method),
+            self.result(func)
+        )
+    }
+
+    fn params(&self, func: &ComponentFuncType, method: bool) -> String {
+        func.params
+            .iter()
+            .skip(if method { 1 } else { 0 })
+            .map(|param| format!("{}: {}", ident(&param.name), self.val_type(param.ty)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn result(&self, func: &ComponentFuncType) -> String {
+        match func.result {
+            Some(result) => format!(" -> {}", self.val_type(result)),
+            None => String::new(),
+        }
+    }
+
+    /// Splits `[constructor]r`, `[method]r.name` and `[static]r.name` into
+    /// the resource they belong to and the line declaring them in its body.
+    fn resource_method(&self, name: &str, func: &ComponentFuncType) -> Option<(String, String)> {
+        if let Some(resource) = name.strip_prefix("[constructor]") {
+            // The implied `own<r>` result is left out.
+            let result = match func.result {
+                Some(result) if self.val_type(result) != ident(resource) => self.result(func),
+                _ => String::new(),
+            };
+            let line = format!("constructor({}){};", self.params(func, false), result);
+            return Some((resource.to_string(), line));
+        }
+
+        let (method, rest) = match name.strip_prefix("[method]") {
+            Some(rest) => (true, rest),
+            None => (false, name.strip_prefix("[static]")?),
+        };
+        let dot = rest.find('.')?;
+
+        let line = format!(
+            "{}: {}{};",
+            ident(&rest[dot + 1..]),
+            if method { "" } else { "static " },
+            self.func(func, method)
+        );
+        Some((rest[..dot].to_string(), line))
+    }
+}
+
+/// Splits a name such as `wasi:cli/stdout@0.2.0` into its package
+/// `wasi:cli@0.2.0` and the interface or world name `stdout`.
+fn split_name(name: &str) -> Option<(String, String)> {
+    let slash = name.find('/')?;
+    if !name[..slash].contains(':') {
+        return None;
+    }
+
+    // Only the interface or world name carries a version.
+    let (path, version) = match name[slash..].find('@') {
+        Some(at) => name.split_at(slash + at),
+        None => (name, ""),
+    };
+
+    Some((
+        format!("{}{}", &path[..slash], version),
+        path[slash + 1..].to_string(),
+    ))
+}
+
+fn primitive_name(primitive: PrimitiveValType) -> &'static str {
+    match primitive {
+        PrimitiveValType::Bool => "bool",
+        PrimitiveValType::S8 => "s8",
+        PrimitiveValType::U8 => "u8",
+        PrimitiveValType::S16 => "s16",
+        PrimitiveValType::U16 => "u16",
+        PrimitiveValType::S32 => "s32",
+        PrimitiveValType::U32 => "u32",
+        PrimitiveValType::S64 => "s64",
+        PrimitiveValType::U64 => "u64",
+        PrimitiveValType::F32 => "f32",
+        PrimitiveValType::F64 => "f64",
+        PrimitiveValType::Char => "char",
+        PrimitiveValType::String => "string",
+        PrimitiveValType::ErrorContext => "error-context",
+    }
+}
+
+static KEYWORDS: &[&str] = &[
+    "as",
+    "async",
+    "bool",
+    "borrow",
+    "char",
+    "constructor",
+    "enum",
+    "error-context",
+    "export",
+    "f32",
+    "f64",
+    "flags",
+    "from",
+    "func",
+    "future",
+    "import",
+    "include",
+    "interface",
+    "list",
+    "option",
+    "own",
+    "package",
+    "record",
+    "resource",
+    "result",
+    "s16",
+    "s32",
+    "s64",
+    "s8",
+    "static",
+    "stream",
+    "string",
+    "tuple",
+    "type",
+    "u16",
+    "u32",
+    "u64",
+    "u8",
+    "use",
+    "variant",
+    "with",
+    "world",
+];
+
+/// Escapes names that clash with WIT keywords.
+fn ident(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("%{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn indent(lines: Vec<String>) -> Vec<String> {
+    lines
+        .into_iter()
+        .map(|line| format!("  {}", line))
+        .collect()
+}
+
+fn push_lines(out: &mut String, lines: &[String]) {
+    for line in lines {
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{ident, split_name, ComponentTypeMetadata};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_split_name() {
+        assert_eq!(
+            split_name("wasi:cli/stdout@0.2.0"),
+            Some(("wasi:cli@0.2.0".to_string(), "stdout".to_string()))
+        );
+        assert_eq!(
+            split_name("my:pkg/app"),
+            Some(("my:pkg".to_string(), "app".to_string()))
+        );
+        assert_eq!(split_name("log"), None);
+        assert_eq!(
+            split_name("a:b@1/c"),
+            Some(("a:b@1".to_string(), "c".to_string()))
+        );
+        assert_eq!(ident("type"), "%type");
+        assert_eq!(ident("point"), "point");
+    }
+
+    #[test]
+    fn test_to_wit() {
+        // The world `my:geo/app`, importing `wasi:io/streams@0.2.0` and an
+        // interface `my:geo/types` with a record, a variant, an enum, a
+        // resource with a method and a constructor, and a `use` of the
+        // stream resource. It exports `run`.
+        let bytes = [
+            0x00, 0x61, 0x73, 0x6d, 0x0d, 0x00, 0x01, 0x00, 0x07, 0xae, 0x02, 0x01, 0x41, 0x02,
+            0x01, 0x41, 0x07, 0x01, 0x42, 0x01, 0x04, 0x00, 0x0c, 0x69, 0x6e, 0x70, 0x75, 0x74,
+            0x2d, 0x73, 0x74, 0x72, 0x65, 0x61, 0x6d, 0x03, 0x01, 0x03, 0x00, 0x15, 0x77, 0x61,
+            0x73, 0x69, 0x3a, 0x69, 0x6f, 0x2f, 0x73, 0x74, 0x72, 0x65, 0x61, 0x6d, 0x73, 0x40,
+            0x30, 0x2e, 0x32, 0x2e, 0x30, 0x05, 0x00, 0x02, 0x03, 0x00, 0x00, 0x0c, 0x69, 0x6e,
+            0x70, 0x75, 0x74, 0x2d, 0x73, 0x74, 0x72, 0x65, 0x61, 0x6d, 0x01, 0x42, 0x0f, 0x01,
+            0x72, 0x02, 0x01, 0x78, 0x79, 0x01, 0x79, 0x79, 0x04, 0x00, 0x05, 0x70, 0x6f, 0x69,
+            0x6e, 0x74, 0x03, 0x00, 0x00, 0x01, 0x71, 0x02, 0x04, 0x6e, 0x6f, 0x6e, 0x65, 0x00,
+            0x00, 0x06, 0x63, 0x69, 0x72, 0x63, 0x6c, 0x65, 0x01, 0x79, 0x00, 0x04, 0x00, 0x05,
+            0x73, 0x68, 0x61, 0x70, 0x65, 0x03, 0x00, 0x02, 0x01, 0x6d, 0x02, 0x03, 0x72, 0x65,
+            0x64, 0x05, 0x67, 0x72, 0x65, 0x65, 0x6e, 0x04, 0x00, 0x05, 0x63, 0x6f, 0x6c, 0x6f,
+            0x72, 0x03, 0x00, 0x04, 0x02, 0x03, 0x02, 0x01, 0x01, 0x04, 0x00, 0x0c, 0x69, 0x6e,
+            0x70, 0x75, 0x74, 0x2d, 0x73, 0x74, 0x72, 0x65, 0x61, 0x6d, 0x03, 0x00, 0x06, 0x04,
+            0x00, 0x06, 0x63, 0x61, 0x6e, 0x76, 0x61, 0x73, 0x03, 0x01, 0x01, 0x68, 0x08, 0x01,
+            0x40, 0x02, 0x04, 0x73, 0x65, 0x6c, 0x66, 0x09, 0x01, 0x70, 0x01, 0x00, 0x03, 0x04,
+            0x00, 0x13, 0x5b, 0x6d, 0x65, 0x74, 0x68, 0x6f, 0x64, 0x5d, 0x63, 0x61, 0x6e, 0x76,
+            0x61, 0x73, 0x2e, 0x64, 0x72, 0x61, 0x77, 0x01, 0x0a, 0x01, 0x69, 0x08, 0x01, 0x40,
+            0x00, 0x00, 0x0b, 0x04, 0x00, 0x13, 0x5b, 0x63, 0x6f, 0x6e, 0x73, 0x74, 0x72, 0x75,
+            0x63, 0x74, 0x6f, 0x72, 0x5d, 0x63, 0x61, 0x6e, 0x76, 0x61, 0x73, 0x01, 0x0c, 0x03,
+            0x00, 0x0c, 0x6d, 0x79, 0x3a, 0x67, 0x65, 0x6f, 0x2f, 0x74, 0x79, 0x70, 0x65, 0x73,
+            0x05, 0x02, 0x01, 0x40, 0x01, 0x01, 0x61, 0x79, 0x00, 0x73, 0x04, 0x00, 0x03, 0x72,
+            0x75, 0x6e, 0x01, 0x03, 0x04, 0x00, 0x0a, 0x6d, 0x79, 0x3a, 0x67, 0x65, 0x6f, 0x2f,
+            0x61, 0x70, 0x70, 0x04, 0x00,
+        ];
+        let metadata = ComponentTypeMetadata::from_reader(&mut Cursor::new(&bytes[..])).unwrap();
+
+        assert_eq!(
+            metadata.to_wit().unwrap(),
+            "package my:geo;
+
+interface types {
+  record point {
+    x: u32,
+    y: u32,
+  }
+  variant shape {
+    none,
+    circle(u32),
+  }
+  enum color {
+    red,
+    green,
+  }
+  use wasi:io/streams@0.2.0.{input-stream};
+  resource canvas {
+    draw: func(p: point) -> shape;
+    constructor();
+  }
+}
+
+package wasi:io@0.2.0 {
+  interface streams {
+    resource input-stream;
+  }
+}
+
+world app {
+  import wasi:io/streams@0.2.0;
+  import my:geo/types;
+  export run: func(a: u32) -> string;
+}
+"
+        );
+    }
+}
@@ -0,0 +1,559 @@
+//! Reports the parts of a module that can never be used. Functions are live
+//! when reachable in the call graph from the exports, the start function or
+//! an element segment; everything else is live when live code, an export or
+//! an instantiation-time expression refers to it.
+
+use call_graph::CallGraph;
+use features::Features;
+use std::collections::BTreeSet;
+use std::io::Error;
+use types::data_section::DataMode;
+use types::element_section::{ElementItems, ElementMode};
+use types::{
+    BlockType, CompositeType, ExportKind, ExternalKind, HeapType, Operator, RefType, StorageType,
+    SubType, ValueType, WasmModule, WasmSectionBody,
+};
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct UnusedItem {
+    pub index: u32,
+    pub name: Option<String>,
+    /// Bytes the item takes in the code or data section. Only unused
+    /// functions and data segments have a size; types, globals and imports
+    /// always report zero.
+    pub size: u32,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct DeadCodeReport {
+    /// Defined functions that are never reached.
+    pub functions: Vec<UnusedItem>,
+    pub types: Vec<UnusedItem>,
+    /// Defined globals that are never read, written or exported.
+    pub globals: Vec<UnusedItem>,
+    /// Imported functions and globals that are never used, indexed by their
+    /// position in the import section. Other imports are assumed used.
+    pub imports: Vec<UnusedItem>,
+    /// Passive data segments that no live code refers to.
+    pub data: Vec<UnusedItem>,
+}
+
+impl DeadCodeReport {
+    pub fn from_module(module: &WasmModule) -> Result<DeadCodeReport, Error> {
+        DeadCodeReport::from_module_with_features(module, &Features::default())
+    }
+
+    pub fn from_module_with_features(
+        module: &WasmModule,
+        features: &Features,
+    ) -> Result<DeadCodeReport, Error> {
+        let graph = CallGraph::from_module_with_features(module, features)?;
+        let mut usage = Usage::default();
+
+        for section in &module.sections {
+            match section.body {
+                WasmSectionBody::Element(ref elements) => {
+                    for segment in &elements.entries {
+                        usage.ref_type(segment.element_type);
+                        if let ElementMode::Active { ref offset, .. } = segment.mode {
+                            usage.expression(offset.operators()?);
+                        }
+                        if let ElementItems::Expressions(ref expressions) = segment.items {
+                            for expression in expressions {
+                                usage.expression(expression.operators()?);
+                            }
+                        }
+                    }
+                }
+                WasmSectionBody::Data(ref data) => {
+                    for (index, segment) in data.entries.iter().enumerate() {
+                        if let DataMode::Active { ref offset, .. } = segment.mode {
+                            usage.data.insert(index as u32);
+                            usage.expression(offset.operators()?);
+                        }
+                    }
+                }
+                WasmSectionBody::Export(ref exports) => {
+                    usage
+                        .globals
+                        .extend(exports.of_kind(ExportKind::Global).map(|entry| entry.index));
+                }
+                WasmSectionBody::Import(ref imports) => {
+                    usage.types.extend(imports.entries.iter().filter_map(
+                        |entry| match entry.kind {
+                            ExternalKind::Tag(ref tag) => Some(tag.type_index),
+                            _ => None,
+                        },
+                    ));
+                }
+                WasmSectionBody::Tag(ref tags) => {
+                    usage
+                        .types
+                        .extend(tags.entries.iter().map(|tag| tag.type_index));
+                }
+                _ => {}
+            }
+        }
+
+        for table in module.tables() {
+            usage.ref_type(table.ty.element_type);
+            if let Some(init) = table.init {
+                usage.expression(init.operators()?);
+            }
+        }
+
+        // Live code can read globals whose initializers reference more
+        // functions, so alternate between the two until nothing new is live.
+        let functions: Vec<_> = module.functions().collect();
+        let globals: Vec<_> = module.globals().collect();
+        let mut roots = entry_points(module, &graph)?;
+        let mut live = BTreeSet::new();
+        let mut live_globals = BTreeSet::new();
+        loop {
+            let reached = graph.reachable_from(&roots);
+            for &index in reached.difference(&live) {
+                let function = &functions[index as usize];
+                usage.types.insert(function.type_index);
+                if let Some(body) = function.body {
+                    for local in &body.locals {
+                        usage.value_type(local.t);
+                    }
+                    for operator in body.operators_with_features(features)? {
+                        usage.operator(&operator);
+                    }
+                }
+            }
+            live = reached;
+
+            // Initializers only read earlier globals, so walking backwards
+            // sees every use before deciding whether a global is live.
+            let mut referenced = Vec::new();
+            for global in globals.iter().rev() {
+                if usage.globals.contains(&global.index) && live_globals.insert(global.index) {
+                    usage.value_type(global.ty.content_type);
+                    if let Some(init) = global.init {
+                        let operators = init.operators()?;
+                        referenced.extend(operators.iter().filter_map(
+                            |operator| match *operator {
+                                Operator::RefFunc(function) => Some(function),
+                                _ => None,
+                            },
+                        ));
+                        usage.expression(operators);
+                    }
+                }
+            }
+
+            if referenced.is_empty() {
+                break;
+            }
+            roots.extend(referenced);
+        }
+
+        let types = used_types(module, usage.types);
+        let names = module.names();
+        let mut report = DeadCodeReport::default();
+
+        for function in &functions {
+            if let Some(body) = function.body {
+                if !live.contains(&function.index) {
+                    report.functions.push(UnusedItem {
+                        index: function.index,
+                        name: function.debug_name.map(|name| name.to_string()),
+                        size: body.locals_size + body.body_size,
+                    });
+                }
+            }
+        }
+
+        let type_count = module
+            .sections
+            .iter()
+            .filter_map(|section| match section.body {
+                WasmSectionBody::Types(ref types) => Some(types.types().count()),
+                _ => None,
+            })
+            .sum::<usize>() as u32;
+        for index in (0..type_count).filter(|index| !types.contains(index)) {
+            report.types.push(UnusedItem {
+                index,
+                name: names
+                    .and_then(|names| names.type_name(index))
+                    .map(|name| name.to_string()),
+                size: 0,
+            });
+        }
+
+        for global in &globals {
+            if !global.is_imported() && !usage.globals.contains(&global.index) {
+                report.globals.push(UnusedItem {
+                    index: global.index,
+                    name: global.debug_name.map(|name| name.to_string()),
+                    size: 0,
+                });
+            }
+        }
+
+        let mut function_index = 0;
+        let mut global_index = 0;
+        for (index, import) in module.imports().enumerate() {
+            let unused = match import.kind {
+                ExternalKind::Function(_) => {
+                    function_index += 1;
+                    !live.contains(&(function_index - 1))
+                }
+                ExternalKind::Global(_) => {
+                    global_index += 1;
+                    !usage.globals.contains(&(global_index - 1))
+                }
+                _ => false,
+            };
+            if unused {
+                report.imports.push(UnusedItem {
+                    index: index as u32,
+                    name: Some(format!("{}.{}", import.module_name, import.field_name)),
+                    size: 0,
+                });
+            }
+        }
+
+        for section in &module.sections {
+            if let WasmSectionBody::Data(ref data) = section.body {
+                for (index, segment) in data.entries.iter().enumerate() {
+                    let index = index as u32;
+                    if !usage.data.contains(&index) {
+                        report.data.push(UnusedItem {
+                            index,
+                            name: names
+                                .and_then(|names| names.data_name(index))
+                                .map(|name| name.to_string()),
+                            size: segment.data.len() as u32,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Bytes of code and data that could be removed. Types, globals and
+    /// imports are not counted.
+    pub fn total_size(&self) -> u64 {
+        self.functions
+            .iter()
+            .chain(&self.types)
+            .chain(&self.globals)
+            .chain(&self.imports)
+            .chain(&self.data)
+            .map(|item| u64::from(item.size))
+            .sum()
+    }
+}
+
+#[derive(Default)]
+struct Usage {
+    types: BTreeSet<u32>,
+    globals: BTreeSet<u32>,
+    data: BTreeSet<u32>,
+}
+
+impl Usage {
+    fn expression(&mut self, operators: Vec<Operator>) {
+        for operator in &operators {
+            self.operator(operator);
+        }
+    }
+
+    fn operator(&mut self, operator: &Operator) {
+        match *operator {
+            Operator::Block(block_type)
+            | Operator::Loop(block_type)
+            | Operator::If(block_type)
+            | Operator::Try(block_type)
+            | Operator::TryTable { block_type, .. } => match block_type {
+                BlockType::FuncType(index) => {
+                    self.types.insert(index);
+                }
+                BlockType::Value(ty) => self.value_type(ty),
+                BlockType::Empty => {}
+            },
+            Operator::TypedSelect(ref types) => {
+                for &ty in types {
+                    self.value_type(ty);
+                }
+            }
+            Operator::GlobalGet(index) | Operator::GlobalSet(index) => {
+                self.globals.insert(index);
+            }
+            Operator::MemoryInit { data_index, .. } | Operator::DataDrop(data_index) => {
+                self.data.insert(data_index);
+            }
+            Operator::ArrayNewData {
+                type_index,
+                data_index,
+            }
+            | Operator::ArrayInitData {
+                type_index,
+                data_index,
+            } => {
+                self.types.insert(type_index);
+                self.data.insert(data_index);
+            }
+            Operator::CallIndirect { type_index, .. }
+            | Operator::ReturnCallIndirect { type_index, .. }
+            | Operator::CallRef(type_index)
+            | Operator::ReturnCallRef(type_index)
+            | Operator::StructNew(type_index)
+            | Operator::StructNewDefault(type_index)
+            | Operator::StructGet { type_index, .. }
+            | Operator::StructGetS { type_index, .. }
+            | Operator::StructGetU { type_index, .. }
+            | Operator::StructSet { type_index, .. }
+            | Operator::ArrayNew(type_index)
+            | Operator::ArrayNewDefault(type_index)
+            | Operator::ArrayNewFixed { type_index, .. }
+            | Operator::ArrayNewElem { type_index, .. }
+            | Operator::ArrayGet(type_index)
+            | Operator::ArrayGetS(type_index)
+            | Operator::ArrayGetU(type_index)
+            | Operator::ArraySet(type_index)
+            | Operator::ArrayFill(type_index)
+            | Operator::ArrayInitElem { type_index, .. } => {
+                self.types.insert(type_index);
+            }
+            Operator::ArrayCopy { dst, src } => {
+                self.types.insert(dst);
+                self.types.insert(src);
+            }
+            Operator::RefNull(heap_type) => self.heap_type(heap_type),
+            Operator::RefTest(ref_type) | Operator::RefCast(ref_type) => self.ref_type(ref_type),
+            Operator::BrOnCast { from, to, .. } | Operator::BrOnCastFail { from, to, .. } => {
+                self.ref_type(from);
+                self.ref_type(to);
+            }
+            _ => {}
+        }
+    }
+
+    fn value_type(&mut self, ty: ValueType) {
+        if let ValueType::Ref(ref_type) = ty {
+            self.ref_type(ref_type);
+        }
+    }
+
+    fn ref_type(&mut self, ref_type: RefType) {
+        self.heap_type(ref_type.heap_type);
+    }
+
+    fn heap_type(&mut self, heap_type: HeapType) {
+        if let HeapType::Concrete(index) = heap_type {
+            self.types.insert(index);
+        }
+    }
+}
+
+/// The functions that can be called from outside the module: the exports
+/// and start function, and every function placed in an element segment or
+/// referenced by a table initializer. Functions global initializers
+/// reference are only reachable through the global, see
+/// `global_references`.
+pub(crate) fn entry_points(module: &WasmModule, graph: &CallGraph) -> Result<Vec<u32>, Error> {
+    let mut roots = graph.roots.clone();
+    let mut expressions = Vec::new();
+
+    for section in &module.sections {
+        match section.body {
+            WasmSectionBody::Element(ref elements) => {
+                for segment in &elements.entries {
+                    match segment.items {
+                        ElementItems::Functions(ref functions) => roots.extend(functions),
+                        ElementItems::Expressions(ref items) => expressions.extend(items),
+                    }
+                }
+            }
+            WasmSectionBody::Table(ref tables) => {
+                expressions.extend(
+                    tables
+                        .entries
+                        .iter()
+                        .filter_map(|table| table.init.as_ref()),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    for expression in expressions {
+        for operator in expression.operators()? {
+            if let Operator::RefFunc(function) = operator {
+                roots.push(function);
+            }
+        }
+    }
+
+    Ok(roots)
+}
+
+/// The functions any global initializer references.
+pub(crate) fn global_references(module: &WasmModule) -> Result<Vec<u32>, Error> {
+    let mut functions = Vec::new();
+
+    for section in &module.sections {
+        if let WasmSectionBody::Global(ref globals) = section.body {
+            for global in &globals.globals {
+                for operator in global.init.operators()? {
+                    if let Operator::RefFunc(function) = operator {
+                        functions.push(function);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(functions)
+}
+
+/// Extends `used` with every type the used types refer to. A recursion
+/// group can only be kept or removed as a whole.
+fn used_types(module: &WasmModule, used: BTreeSet<u32>) -> BTreeSet<u32> {
+    let mut types: Vec<&SubType> = Vec::new();
+    let mut groups: Vec<(u32, u32)> = Vec::new();
+    for section in &module.sections {
+        if let WasmSectionBody::Types(ref section) = section.body {
+            for group in &section.entries {
+                let start = types.len() as u32;
+                types.extend(group.types.iter());
+                groups.extend(group.types.iter().map(|_| (start, types.len() as u32)));
+            }
+        }
+    }
+
+    let mut result = BTreeSet::new();
+    let mut pending: Vec<u32> = used.into_iter().collect();
+
+    while let Some(index) = pending.pop() {
+        let (start, end) = match groups.get(index as usize) {
+            Some(&group) => group,
+            None => continue,
+        };
+        if !result.insert(index) {
+            continue;
+        }
+        pending.extend(start..end);
+
+        let sub_type = types[index as usize];
+        let mut usage = Usage::default();
+        usage.types.extend(&sub_type.supertypes);
+        match sub_type.composite {
+            CompositeType::Func(ref func_type) => {
+                for &ty in func_type.param_types.iter().chain(&func_type.return_types) {
+                    usage.value_type(ty);
+                }
+            }
+            CompositeType::Struct(ref struct_type) => {
+                for field in &struct_type.fields {
+                    if let StorageType::Value(ty) = field.storage_type {
+                        usage.value_type(ty);
+                    }
+                }
+            }
+            CompositeType::Array(ref array_type) => {
+                if let StorageType::Value(ty) = array_type.field.storage_type {
+                    usage.value_type(ty);
+                }
+            }
+        }
+        pending.extend(usage.types);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::*;
+
+    #[test]
+    fn test_dead_code_report() {
+        // (type (func (param i64))) (type (func)) (import "env" "f" (func))
+        // (memory 1) (global (mut i32) (i32.const 0)) (global i32 (i32.const 1))
+        // (data "abc")
+        // (func (export "main") global.get 0 drop) (func (local i32))
+        let module = module(&[
+            (TYPE, &[0x02, 0x60, 0x01, 0x7e, 0x00, 0x60, 0x00, 0x00]),
+            (
+                IMPORT,
+                &[0x01, 0x03, 0x65, 0x6e, 0x76, 0x01, 0x66, 0x00, 0x01],
+            ),
+            (FUNCTION, &[0x02, 0x01, 0x01]),
+            (MEMORY, &[0x01, 0x00, 0x01]),
+            (
+                GLOBAL,
+                &[
+                    0x02, 0x7f, 0x01, 0x41, 0x00, 0x0b, 0x7f, 0x00, 0x41, 0x01, 0x0b,
+                ],
+            ),
+            (EXPORT, &[0x01, 0x04, 0x6d, 0x61, 0x69, 0x6e, 0x00, 0x01]),
+            (
+                CODE,
+                &code(&[&[0x00, 0x23, 0x00, 0x1a, 0x0b], &[0x01, 0x01, 0x7f, 0x0b]]),
+            ),
+            (DATA, &[0x01, 0x01, 0x03, 0x61, 0x62, 0x63]),
+        ]);
+        let report = DeadCodeReport::from_module(&module).unwrap();
+        let indices =
+            |items: &[UnusedItem]| items.iter().map(|item| item.index).collect::<Vec<_>>();
+
+        assert_eq!(indices(&report.functions), vec![2]);
+        assert_eq!(report.functions[0].size, 4);
+        assert_eq!(indices(&report.types), vec![0]);
+        assert_eq!(indices(&report.globals), vec![1]);
+        assert_eq!(report.imports[0].name, Some("env.f".to_string()));
+        assert_eq!(report.data[0].size, 3);
+        assert_eq!(report.total_size(), 7);
+    }
+    #[test]
+    fn test_global_references() {
+        // (type (func)) (type (func (param i32))) (import "env" "f" (func (type 1)))
+        // (global funcref (ref.func 0)) (global funcref (ref.func 1))
+        // (global funcref (ref.func 3))
+        // (func) (func (export "main") global.get 1 drop) (func)
+        let module = module(&[
+            (TYPE, &[0x02, 0x60, 0x00, 0x00, 0x60, 0x01, 0x7f, 0x00]),
+            (
+                IMPORT,
+                &[0x01, 0x03, 0x65, 0x6e, 0x76, 0x01, 0x66, 0x00, 0x01],
+            ),
+            (FUNCTION, &[0x03, 0x00, 0x00, 0x00]),
+            (
+                GLOBAL,
+                &[
+                    0x03, 0x70, 0x00, 0xd2, 0x00, 0x0b, 0x70, 0x00, 0xd2, 0x01, 0x0b, 0x70, 0x00,
+                    0xd2, 0x03, 0x0b,
+                ],
+            ),
+            (EXPORT, &[0x01, 0x04, 0x6d, 0x61, 0x69, 0x6e, 0x00, 0x02]),
+            (
+                CODE,
+                &code(&[
+                    &[0x00, 0x0b],
+                    &[0x00, 0x23, 0x01, 0x1a, 0x0b],
+                    &[0x00, 0x0b],
+                ]),
+            ),
+        ]);
+        let report = DeadCodeReport::from_module(&module).unwrap();
+        let indices =
+            |items: &[UnusedItem]| items.iter().map(|item| item.index).collect::<Vec<_>>();
+
+        // Only the global main reads keeps its function alive.
+        assert_eq!(indices(&report.functions), vec![3]);
+        assert_eq!(indices(&report.globals), vec![0, 2]);
+        // The import and its type are only referenced by an unused global.
+        assert_eq!(indices(&report.imports), vec![0]);
+        assert_eq!(indices(&report.types), vec![1]);
+    }
+}
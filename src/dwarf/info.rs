@@ -0,0 +1,685 @@
+use byteorder::{LittleEndian, ReadBytesExt};
+use leb128::ReadLeb128Ext;
+use std::collections::HashMap;
+use std::io::{Cursor, Error};
+
+use super::*;
+
+const DW_UT_COMPILE: u8 = 0x01;
+const DW_UT_PARTIAL: u8 = 0x03;
+
+const DW_TAG_SUBPROGRAM: u64 = 0x2e;
+
+const DW_AT_NAME: u64 = 0x03;
+const DW_AT_STMT_LIST: u64 = 0x10;
+const DW_AT_LOW_PC: u64 = 0x11;
+const DW_AT_HIGH_PC: u64 = 0x12;
+const DW_AT_COMP_DIR: u64 = 0x1b;
+const DW_AT_ABSTRACT_ORIGIN: u64 = 0x31;
+const DW_AT_SPECIFICATION: u64 = 0x47;
+const DW_AT_RANGES: u64 = 0x55;
+const DW_AT_LINKAGE_NAME: u64 = 0x6e;
+const DW_AT_STR_OFFSETS_BASE: u64 = 0x72;
+const DW_AT_ADDR_BASE: u64 = 0x73;
+const DW_AT_RNGLISTS_BASE: u64 = 0x74;
+const DW_AT_MIPS_LINKAGE_NAME: u64 = 0x2007;
+
+const DW_FORM_ADDR: u64 = 0x01;
+const DW_FORM_BLOCK2: u64 = 0x03;
+const DW_FORM_BLOCK4: u64 = 0x04;
+const DW_FORM_DATA2: u64 = 0x05;
+const DW_FORM_DATA4: u64 = 0x06;
+const DW_FORM_DATA8: u64 = 0x07;
+const DW_FORM_STRING: u64 = 0x08;
+const DW_FORM_BLOCK: u64 = 0x09;
+const DW_FORM_BLOCK1: u64 = 0x0a;
+const DW_FORM_DATA1: u64 = 0x0b;
+const DW_FORM_FLAG: u64 = 0x0c;
+const DW_FORM_SDATA: u64 = 0x0d;
+const DW_FORM_STRP: u64 = 0x0e;
+const DW_FORM_UDATA: u64 = 0x0f;
+const DW_FORM_REF_ADDR: u64 = 0x10;
+const DW_FORM_REF1: u64 = 0x11;
+const DW_FORM_REF2: u64 = 0x12;
+const DW_FORM_REF4: u64 = 0x13;
+const DW_FORM_REF8: u64 = 0x14;
+const DW_FORM_REF_UDATA: u64 = 0x15;
+const DW_FORM_INDIRECT: u64 = 0x16;
+const DW_FORM_SEC_OFFSET: u64 = 0x17;
+const DW_FORM_EXPRLOC: u64 = 0x18;
+const DW_FORM_FLAG_PRESENT: u64 = 0x19;
+const DW_FORM_STRX: u64 = 0x1a;
+const DW_FORM_ADDRX: u64 = 0x1b;
+const DW_FORM_REF_SUP4: u64 = 0x1c;
+const DW_FORM_STRP_SUP: u64 = 0x1d;
+const DW_FORM_DATA16: u64 = 0x1e;
+const DW_FORM_LINE_STRP: u64 = 0x1f;
+const DW_FORM_REF_SIG8: u64 = 0x20;
+const DW_FORM_IMPLICIT_CONST: u64 = 0x21;
+const DW_FORM_LOCLISTX: u64 = 0x22;
+const DW_FORM_RNGLISTX: u64 = 0x23;
+const DW_FORM_REF_SUP8: u64 = 0x24;
+const DW_FORM_STRX1: u64 = 0x25;
+const DW_FORM_STRX2: u64 = 0x26;
+const DW_FORM_STRX3: u64 = 0x27;
+const DW_FORM_STRX4: u64 = 0x28;
+const DW_FORM_ADDRX1: u64 = 0x29;
+const DW_FORM_ADDRX2: u64 = 0x2a;
+const DW_FORM_ADDRX3: u64 = 0x2b;
+const DW_FORM_ADDRX4: u64 = 0x2c;
+const DW_FORM_GNU_ADDR_INDEX: u64 = 0x1f01;
+const DW_FORM_GNU_STR_INDEX: u64 = 0x1f02;
+
+const DW_RLE_END_OF_LIST: u8 = 0x00;
+const DW_RLE_BASE_ADDRESSX: u8 = 0x01;
+const DW_RLE_STARTX_ENDX: u8 = 0x02;
+const DW_RLE_STARTX_LENGTH: u8 = 0x03;
+const DW_RLE_OFFSET_PAIR: u8 = 0x04;
+const DW_RLE_BASE_ADDRESS: u8 = 0x05;
+const DW_RLE_START_END: u8 = 0x06;
+const DW_RLE_START_LENGTH: u8 = 0x07;
+
+/// A half-open range of code-section-relative addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AddressRange {
+    pub begin: u64,
+    pub end: u64,
+}
+
+/// A function described by a `DW_TAG_subprogram` entry in `.debug_info`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DwarfFunction {
+    pub name: Option<String>,
+    pub linkage_name: Option<String>,
+    pub ranges: Vec<AddressRange>,
+}
+
+impl DwarfFunction {
+    pub fn low_pc(&self) -> u64 {
+        self.ranges
+            .iter()
+            .map(|range| range.begin)
+            .min()
+            .unwrap_or(0)
+    }
+
+    pub fn contains(&self, address: u64) -> bool {
+        self.ranges
+            .iter()
+            .any(|range| range.begin <= address && address < range.end)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum AttrValue {
+    Address(u64),
+    AddressIndex(u64),
+    Unsigned(u64),
+    Signed(i64),
+    String(String),
+    StringOffset(u64),
+    LineStringOffset(u64),
+    StringIndex(u64),
+    SecOffset(u64),
+    UnitRef(u64),
+    DebugInfoRef(u64),
+    RangeListIndex(u64),
+    Other,
+}
+
+struct AttrSpec {
+    name: u64,
+    form: u64,
+    implicit_const: i64,
+}
+
+struct Abbrev {
+    tag: u64,
+    has_children: bool,
+    attrs: Vec<AttrSpec>,
+}
+
+fn parse_abbrevs(data: &[u8], offset: u64) -> Result<HashMap<u64, Abbrev>, Error> {
+    let mut reader = Cursor::new(data);
+    reader.set_position(offset);
+
+    let mut abbrevs = HashMap::new();
+
+    loop {
+        let (code, _) = reader.leb128_unsigned_64()?;
+        if code == 0 {
+            break;
+        }
+
+        let (tag, _) = reader.leb128_unsigned_64()?;
+        let has_children = reader.read_u8()? != 0;
+
+        let mut attrs = Vec::new();
+
+        loop {
+            let (name, _) = reader.leb128_unsigned_64()?;
+            let (form, _) = reader.leb128_unsigned_64()?;
+            if name == 0 && form == 0 {
+                break;
+            }
+
+            let implicit_const = if form == DW_FORM_IMPLICIT_CONST {
+                reader.leb128_signed_64()?.0
+            } else {
+                0
+            };
+
+            attrs.push(AttrSpec {
+                name,
+                form,
+                implicit_const,
+            });
+        }
+
+        abbrevs.insert(
+            code,
+            Abbrev {
+                tag,
+                has_children,
+                attrs,
+            },
+        );
+    }
+
+    Ok(abbrevs)
+}
+
+pub(super) fn read_attr_value(
+    reader: &mut Cursor<&[u8]>,
+    form: u64,
+    encoding: Encoding,
+    implicit_const: i64,
+) -> Result<AttrValue, Error> {
+    let value = match form {
+        DW_FORM_ADDR => AttrValue::Address(read_address(reader, encoding.address_size)?),
+        DW_FORM_BLOCK1 => {
+            let len = reader.read_u8()?;
+            skip(reader, u64::from(len));
+            AttrValue::Other
+        }
+        DW_FORM_BLOCK2 => {
+            let len = reader.read_u16::<LittleEndian>()?;
+            skip(reader, u64::from(len));
+            AttrValue::Other
+        }
+        DW_FORM_BLOCK4 => {
+            let len = reader.read_u32::<LittleEndian>()?;
+            skip(reader, u64::from(len));
+            AttrValue::Other
+        }
+        DW_FORM_BLOCK | DW_FORM_EXPRLOC => {
+            let (len, _) = reader.leb128_unsigned_64()?;
+            skip(reader, len);
+            AttrValue::Other
+        }
+        DW_FORM_DATA1 => AttrValue::Unsigned(u64::from(reader.read_u8()?)),
+        DW_FORM_DATA2 => AttrValue::Unsigned(u64::from(reader.read_u16::<LittleEndian>()?)),
+        DW_FORM_DATA4 => AttrValue::Unsigned(u64::from(reader.read_u32::<LittleEndian>()?)),
+        DW_FORM_DATA8 => AttrValue::Unsigned(reader.read_u64::<LittleEndian>()?),
+        DW_FORM_DATA16 => {
+            skip(reader, 16);
+            AttrValue::Other
+        }
+        DW_FORM_UDATA => AttrValue::Unsigned(reader.leb128_unsigned_64()?.0),
+        DW_FORM_SDATA => AttrValue::Signed(reader.leb128_signed_64()?.0),
+        DW_FORM_IMPLICIT_CONST => AttrValue::Signed(implicit_const),
+        DW_FORM_FLAG => AttrValue::Unsigned(u64::from(reader.read_u8()?)),
+        DW_FORM_FLAG_PRESENT => AttrValue::Unsigned(1),
+        DW_FORM_STRING => AttrValue::String(read_cstr(reader)?),
+        DW_FORM_STRP => AttrValue::StringOffset(read_offset(reader, encoding.dwarf64)?),
+        DW_FORM_LINE_STRP => AttrValue::LineStringOffset(read_offset(reader, encoding.dwarf64)?),
+        DW_FORM_STRP_SUP => {
+            read_offset(reader, encoding.dwarf64)?;
+            AttrValue::Other
+        }
+        DW_FORM_STRX | DW_FORM_GNU_STR_INDEX => {
+            AttrValue::StringIndex(reader.leb128_unsigned_64()?.0)
+        }
+        DW_FORM_STRX1 => AttrValue::StringIndex(u64::from(reader.read_u8()?)),
+        DW_FORM_STRX2 => AttrValue::StringIndex(u64::from(reader.read_u16::<LittleEndian>()?)),
+        DW_FORM_STRX3 => AttrValue::StringIndex(reader.read_u24::<LittleEndian>()?.into()),
+        DW_FORM_STRX4 => AttrValue::StringIndex(u64::from(reader.read_u32::<LittleEndian>()?)),
+        DW_FORM_ADDRX | DW_FORM_GNU_ADDR_INDEX => {
+            AttrValue::AddressIndex(reader.leb128_unsigned_64()?.0)
+        }
+        DW_FORM_ADDRX1 => AttrValue::AddressIndex(u64::from(reader.read_u8()?)),
+        DW_FORM_ADDRX2 => AttrValue::AddressIndex(u64::from(reader.read_u16::<LittleEndian>()?)),
+        DW_FORM_ADDRX3 => AttrValue::AddressIndex(reader.read_u24::<LittleEndian>()?.into()),
+        DW_FORM_ADDRX4 => AttrValue::AddressIndex(u64::from(reader.read_u32::<LittleEndian>()?)),
+        DW_FORM_SEC_OFFSET => AttrValue::SecOffset(read_offset(reader, encoding.dwarf64)?),
+        DW_FORM_REF1 => AttrValue::UnitRef(u64::from(reader.read_u8()?)),
+        DW_FORM_REF2 => AttrValue::UnitRef(u64::from(reader.read_u16::<LittleEndian>()?)),
+        DW_FORM_REF4 => AttrValue::UnitRef(u64::from(reader.read_u32::<LittleEndian>()?)),
+        DW_FORM_REF8 => AttrValue::UnitRef(reader.read_u64::<LittleEndian>()?),
+        DW_FORM_REF_UDATA => AttrValue::UnitRef(reader.leb128_unsigned_64()?.0),
+        DW_FORM_REF_ADDR => {
+            if encoding.version <= 2 {
+                AttrValue::DebugInfoRef(read_address(reader, encoding.address_size)?)
+            } else {
+                AttrValue::DebugInfoRef(read_offset(reader, encoding.dwarf64)?)
+            }
+        }
+        DW_FORM_REF_SUP4 => {
+            skip(reader, 4);
+            AttrValue::Other
+        }
+        DW_FORM_REF_SIG8 | DW_FORM_REF_SUP8 => {
+            skip(reader, 8);
+            AttrValue::Other
+        }
+        DW_FORM_LOCLISTX => {
+            reader.leb128_unsigned_64()?;
+            AttrValue::Other
+        }
+        DW_FORM_RNGLISTX => AttrValue::RangeListIndex(reader.leb128_unsigned_64()?.0),
+        DW_FORM_INDIRECT => {
+            let (form, _) = reader.leb128_unsigned_64()?;
+            read_attr_value(reader, form, encoding, implicit_const)?
+        }
+        _ => return Err(Error::other(format!("Unknown DWARF form 0x{:x}", form))),
+    };
+
+    Ok(value)
+}
+
+/// Offsets of unit contributions to the indexed sections, taken from the
+/// unit's root entry.
+struct Unit<'a> {
+    sections: &'a DebugSections,
+    offset: u64,
+    encoding: Encoding,
+    addr_base: u64,
+    str_offsets_base: u64,
+    rnglists_base: u64,
+    base_address: u64,
+}
+
+/// The offset of entry `index` in a table of `size`-byte entries at `base`.
+fn table_entry(base: u64, index: u64, size: u64) -> Result<u64, Error> {
+    index
+        .checked_mul(size)
+        .and_then(|offset| base.checked_add(offset))
+        .ok_or_else(|| Error::other(format!("Index {} overflows its table", index)))
+}
+
+impl<'a> Unit<'a> {
+    fn string(&self, value: &AttrValue) -> Option<String> {
+        match *value {
+            AttrValue::String(ref string) => Some(string.clone()),
+            AttrValue::StringOffset(offset) => cstr_at(&self.sections.str, offset),
+            AttrValue::LineStringOffset(offset) => cstr_at(&self.sections.line_str, offset),
+            AttrValue::StringIndex(index) => {
+                let offset = self.indexed_string_offset(index).ok()?;
+                cstr_at(&self.sections.str, offset)
+            }
+            _ => None,
+        }
+    }
+
+    fn address(&self, value: &AttrValue) -> Option<u64> {
+        match *value {
+            AttrValue::Address(address) => Some(address),
+            AttrValue::AddressIndex(index) => self.indexed_address(index).ok(),
+            _ => None,
+        }
+    }
+
+    fn indexed_string_offset(&self, index: u64) -> Result<u64, Error> {
+        let size = if self.encoding.dwarf64 { 8 } else { 4 };
+        let mut reader = Cursor::new(&self.sections.str_offsets[..]);
+        reader.set_position(table_entry(self.str_offsets_base, index, size)?);
+        read_offset(&mut reader, self.encoding.dwarf64)
+    }
+
+    fn indexed_address(&self, index: u64) -> Result<u64, Error> {
+        let size = u64::from(self.encoding.address_size);
+        let mut reader = Cursor::new(&self.sections.addr[..]);
+        reader.set_position(table_entry(self.addr_base, index, size)?);
+        read_address(&mut reader, self.encoding.address_size)
+    }
+
+    fn reference(&self, value: &AttrValue) -> Option<u64> {
+        match *value {
+            AttrValue::UnitRef(offset) => Some(self.offset.wrapping_add(offset)),
+            AttrValue::DebugInfoRef(offset) => Some(offset),
+            _ => None,
+        }
+    }
+
+    fn ranges(&self, value: &AttrValue) -> Result<Vec<AddressRange>, Error> {
+        if self.encoding.version < 5 {
+            return match *value {
+                AttrValue::SecOffset(offset) | AttrValue::Unsigned(offset) => {
+                    self.debug_ranges(offset)
+                }
+                _ => Ok(Vec::new()),
+            };
+        }
+
+        let offset = match *value {
+            AttrValue::SecOffset(offset) => offset,
+            AttrValue::RangeListIndex(index) => {
+                let size = if self.encoding.dwarf64 { 8 } else { 4 };
+                let mut reader = Cursor::new(&self.sections.rnglists[..]);
+                reader.set_position(self.rnglists_base.wrapping_add(index.wrapping_mul(size)));
+                let offset = read_offset(&mut reader, self.encoding.dwarf64)?;
+                self.rnglists_base.wrapping_add(offset)
+            }
+            _ => return Ok(Vec::new()),
+        };
+
+        self.debug_rnglists(offset)
+    }
+
+    fn debug_ranges(&self, offset: u64) -> Result<Vec<AddressRange>, Error> {
+        let size = self.encoding.address_size;
+        let max = if size == 4 { 0xffff_ffff } else { u64::MAX };
+        let mut reader = Cursor::new(&self.sections.ranges[..]);
+        reader.set_position(offset);
+
+        let mut base = self.base_address;
+        let mut ranges = Vec::new();
+
+        loop {
+            let begin = read_address(&mut reader, size)?;
+            let end = read_address(&mut reader, size)?;
+
+            if begin == 0 && end == 0 {
+                break;
+            } else if begin == max {
+                base = end;
+            } else {
+                ranges.push(AddressRange {
+                    begin: base.wrapping_add(begin),
+                    end: base.wrapping_add(end),
+                });
+            }
+        }
+
+        Ok(ranges)
+    }
+
+    fn debug_rnglists(&self, offset: u64) -> Result<Vec<AddressRange>, Error> {
+        let size = self.encoding.address_size;
+        let mut reader = Cursor::new(&self.sections.rnglists[..]);
+        reader.set_position(offset);
+
+        let mut base = self.base_address;
+        let mut ranges = Vec::new();
+
+        loop {
+            let (begin, end) = match reader.read_u8()? {
+                DW_RLE_END_OF_LIST => break,
+                DW_RLE_BASE_ADDRESSX => {
+                    base = self.indexed_address(reader.leb128_unsigned_64()?.0)?;
+                    continue;
+                }
+                DW_RLE_BASE_ADDRESS => {
+                    base = read_address(&mut reader, size)?;
+                    continue;
+                }
+                DW_RLE_STARTX_ENDX => {
+                    let begin = self.indexed_address(reader.leb128_unsigned_64()?.0)?;
+                    let end = self.indexed_address(reader.leb128_unsigned_64()?.0)?;
+                    (begin, end)
+                }
+                DW_RLE_STARTX_LENGTH => {
+                    let begin = self.indexed_address(reader.leb128_unsigned_64()?.0)?;
+                    let (length, _) = reader.leb128_unsigned_64()?;
+                    (begin, begin.wrapping_add(length))
+                }
+                DW_RLE_OFFSET_PAIR => {
+                    let (begin, _) = reader.leb128_unsigned_64()?;
+                    let (end, _) = reader.leb128_unsigned_64()?;
+                    (base.wrapping_add(begin), base.wrapping_add(end))
+                }
+                DW_RLE_START_END => {
+                    let begin = read_address(&mut reader, size)?;
+                    let end = read_address(&mut reader, size)?;
+                    (begin, end)
+                }
+                DW_RLE_START_LENGTH => {
+                    let begin = read_address(&mut reader, size)?;
+                    let (length, _) = reader.leb128_unsigned_64()?;
+                    (begin, begin.wrapping_add(length))
+                }
+                kind => {
+                    return Err(Error::other(format!(
+                        "Unknown range list entry 0x{:x}",
+                        kind
+                    )))
+                }
+            };
+
+            ranges.push(AddressRange { begin, end });
+        }
+
+        Ok(ranges)
+    }
+}
+
+struct Die {
+    offset: u64,
+    tag: u64,
+    has_children: bool,
+    attrs: Vec<(u64, AttrValue)>,
+}
+
+impl Die {
+    fn attr(&self, name: u64) -> Option<&AttrValue> {
+        self.attrs
+            .iter()
+            .find(|attr| attr.0 == name)
+            .map(|attr| &attr.1)
+    }
+}
+
+/// Reads the next entry, or `None` for the null entry that ends a list of
+/// siblings.
+fn read_die(
+    reader: &mut Cursor<&[u8]>,
+    abbrevs: &HashMap<u64, Abbrev>,
+    encoding: Encoding,
+) -> Result<Option<Die>, Error> {
+    let offset = reader.position();
+    let (code, _) = reader.leb128_unsigned_64()?;
+
+    if code == 0 {
+        return Ok(None);
+    }
+
+    let abbrev = abbrevs
+        .get(&code)
+        .ok_or_else(|| Error::other(format!("Unknown abbreviation code {}", code)))?;
+
+    let mut attrs = Vec::with_capacity(abbrev.attrs.len());
+
+    for spec in &abbrev.attrs {
+        let value = read_attr_value(reader, spec.form, encoding, spec.implicit_const)?;
+        attrs.push((spec.name, value));
+    }
+
+    Ok(Some(Die {
+        offset,
+        tag: abbrev.tag,
+        has_children: abbrev.has_children,
+        attrs,
+    }))
+}
+
+pub(super) fn parse_units(sections: &DebugSections, dwarf: &mut Dwarf) -> Result<(), Error> {
+    let mut reader = Cursor::new(&sections.info[..]);
+
+    while reader.position() < sections.info.len() as u64 {
+        let unit_offset = reader.position();
+        let (length, dwarf64) = read_initial_length(&mut reader)?;
+        let end = end_of(&reader, length)?;
+        let version = reader.read_u16::<LittleEndian>()?;
+
+        let (unit_type, address_size, abbrev_offset) = if version >= 5 {
+            let unit_type = reader.read_u8()?;
+            let address_size = reader.read_u8()?;
+            let abbrev_offset = read_offset(&mut reader, dwarf64)?;
+            (unit_type, address_size, abbrev_offset)
+        } else {
+            let abbrev_offset = read_offset(&mut reader, dwarf64)?;
+            let address_size = reader.read_u8()?;
+            (DW_UT_COMPILE, address_size, abbrev_offset)
+        };
+
+        if unit_type == DW_UT_COMPILE || unit_type == DW_UT_PARTIAL {
+            let encoding = Encoding {
+                version,
+                dwarf64,
+                address_size,
+            };
+            let abbrevs = parse_abbrevs(&sections.abbrev, abbrev_offset)?;
+            parse_unit(
+                sections,
+                &mut reader,
+                unit_offset,
+                end,
+                encoding,
+                &abbrevs,
+                dwarf,
+            )?;
+        }
+
+        reader.set_position(end);
+    }
+
+    Ok(())
+}
+
+fn parse_unit(
+    sections: &DebugSections,
+    reader: &mut Cursor<&[u8]>,
+    offset: u64,
+    end: u64,
+    encoding: Encoding,
+    abbrevs: &HashMap<u64, Abbrev>,
+    dwarf: &mut Dwarf,
+) -> Result<(), Error> {
+    let root = match read_die(reader, abbrevs, encoding)? {
+        Some(root) => root,
+        None => return Ok(()),
+    };
+
+    let offsets_size = if encoding.dwarf64 { 16 } else { 8 };
+    let base = |name| match root.attr(name) {
+        Some(&AttrValue::SecOffset(base)) | Some(&AttrValue::Unsigned(base)) => base,
+        _ => offsets_size,
+    };
+
+    let mut unit = Unit {
+        sections,
+        offset,
+        encoding,
+        addr_base: base(DW_AT_ADDR_BASE),
+        str_offsets_base: base(DW_AT_STR_OFFSETS_BASE),
+        rnglists_base: base(DW_AT_RNGLISTS_BASE),
+        base_address: 0,
+    };
+    unit.base_address = root
+        .attr(DW_AT_LOW_PC)
+        .and_then(|value| unit.address(value))
+        .unwrap_or(0);
+
+    if let Some(stmt_list) = root.attr(DW_AT_STMT_LIST) {
+        let comp_dir = root
+            .attr(DW_AT_COMP_DIR)
+            .and_then(|value| unit.string(value));
+
+        if let AttrValue::SecOffset(line_offset) | AttrValue::Unsigned(line_offset) = *stmt_list {
+            line::parse_program(sections, line_offset, comp_dir.as_deref(), dwarf)?;
+        }
+    }
+
+    let mut names = HashMap::new();
+    let mut subprograms = Vec::new();
+    let mut depth = if root.has_children { 1 } else { 0 };
+
+    while depth > 0 && reader.position() < end {
+        let die = match read_die(reader, abbrevs, encoding)? {
+            Some(die) => die,
+            None => {
+                depth -= 1;
+                continue;
+            }
+        };
+
+        if die.has_children {
+            depth += 1;
+        }
+
+        let name = die.attr(DW_AT_NAME).and_then(|value| unit.string(value));
+        let linkage_name = die
+            .attr(DW_AT_LINKAGE_NAME)
+            .or_else(|| die.attr(DW_AT_MIPS_LINKAGE_NAME))
+            .and_then(|value| unit.string(value));
+        let origin = die
+            .attr(DW_AT_SPECIFICATION)
+            .or_else(|| die.attr(DW_AT_ABSTRACT_ORIGIN))
+            .and_then(|value| unit.reference(value));
+
+        if die.tag == DW_TAG_SUBPROGRAM {
+            let mut ranges = match die.attr(DW_AT_RANGES) {
+                Some(value) => unit.ranges(value)?,
+                None => Vec::new(),
+            };
+
+            let low_pc = die.attr(DW_AT_LOW_PC).and_then(|value| unit.address(value));
+
+            if let Some(low_pc) = low_pc {
+                let high_pc = match die.attr(DW_AT_HIGH_PC) {
+                    Some(&AttrValue::Unsigned(size)) => Some(low_pc.wrapping_add(size)),
+                    Some(&AttrValue::Signed(size)) => Some(low_pc.wrapping_add(size as u64)),
+                    Some(value) => unit.address(value),
+                    None => None,
+                };
+
+                ranges.push(AddressRange {
+                    begin: low_pc,
+                    end: high_pc.unwrap_or(low_pc.wrapping_add(1)),
+                });
+            }
+
+            ranges.retain(|range| !is_tombstone(range.begin, encoding.address_size));
+
+            if !ranges.is_empty() {
+                subprograms.push((origin, name.clone(), linkage_name.clone(), ranges));
+            }
+        }
+
+        names.insert(die.offset, (name, linkage_name, origin));
+    }
+
+    for (mut origin, mut name, mut linkage_name, ranges) in subprograms {
+        // Out-of-line definitions and inlined copies keep their names on the
+        // declaration they refer to.
+        let mut hops = 0;
+        while let (Some(reference), true) = (origin, name.is_none() && hops < 8) {
+            let entry = match names.get(&reference) {
+                Some(entry) => entry,
+                None => break,
+            };
+            name = entry.0.clone();
+            linkage_name = linkage_name.or_else(|| entry.1.clone());
+            origin = entry.2;
+            hops += 1;
+        }
+
+        dwarf.functions.push(DwarfFunction {
+            name,
+            linkage_name,
+            ranges,
+        });
+    }
+
+    Ok(())
+}
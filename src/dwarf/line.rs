@@ -0,0 +1,317 @@
+use byteorder::{LittleEndian, ReadBytesExt};
+use leb128::ReadLeb128Ext;
+use std::io::{Cursor, Error, Read};
+
+use super::info::{read_attr_value, AttrValue};
+use super::*;
+
+const DW_LNS_COPY: u8 = 0x01;
+const DW_LNS_ADVANCE_PC: u8 = 0x02;
+const DW_LNS_ADVANCE_LINE: u8 = 0x03;
+const DW_LNS_SET_FILE: u8 = 0x04;
+const DW_LNS_SET_COLUMN: u8 = 0x05;
+const DW_LNS_CONST_ADD_PC: u8 = 0x08;
+const DW_LNS_FIXED_ADVANCE_PC: u8 = 0x09;
+
+const DW_LNE_END_SEQUENCE: u8 = 0x01;
+const DW_LNE_SET_ADDRESS: u8 = 0x02;
+
+const DW_LNCT_PATH: u64 = 0x01;
+const DW_LNCT_DIRECTORY_INDEX: u64 = 0x02;
+
+#[derive(Debug, Serialize)]
+pub(super) struct LineRow {
+    pub address: u64,
+    pub file: Option<usize>,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// A run of rows covering contiguous addresses, ended by `DW_LNE_end_sequence`.
+#[derive(Debug, Serialize)]
+pub(super) struct LineSequence {
+    pub start: u64,
+    pub end: u64,
+    pub rows: Vec<LineRow>,
+}
+
+struct Registers {
+    address: u64,
+    file: u64,
+    line: i64,
+    column: u64,
+}
+
+impl Registers {
+    fn new() -> Registers {
+        Registers {
+            address: 0,
+            file: 1,
+            line: 1,
+            column: 0,
+        }
+    }
+}
+
+/// Parses the line number program at `offset` in `.debug_line`, adding its
+/// files and sequences to `dwarf`. Returns the offset of the next program.
+pub(super) fn parse_program(
+    sections: &DebugSections,
+    offset: u64,
+    comp_dir: Option<&str>,
+    dwarf: &mut Dwarf,
+) -> Result<u64, Error> {
+    let mut reader = Cursor::new(&sections.line[..]);
+    reader.set_position(offset);
+
+    let (length, dwarf64) = read_initial_length(&mut reader)?;
+    let end = end_of(&reader, length)?;
+    let version = reader.read_u16::<LittleEndian>()?;
+
+    let mut address_size = 4;
+    if version >= 5 {
+        address_size = reader.read_u8()?;
+        let _segment_selector_size = reader.read_u8()?;
+    }
+
+    let header_length = read_offset(&mut reader, dwarf64)?;
+    let program_start = end_of(&reader, header_length)?;
+
+    let min_inst_length = u64::from(reader.read_u8()?);
+    if version >= 4 {
+        let _max_ops_per_inst = reader.read_u8()?;
+    }
+    let _default_is_stmt = reader.read_u8()?;
+    let line_base = i64::from(reader.read_i8()?);
+    let line_range = u64::from(reader.read_u8()?);
+    let opcode_base = reader.read_u8()?;
+
+    if line_range == 0 {
+        return Err(Error::other("Line program has a line_range of 0"));
+    }
+
+    let mut standard_opcode_lengths = vec![0; opcode_base.saturating_sub(1) as usize];
+    reader.read_exact(&mut standard_opcode_lengths)?;
+
+    let encoding = Encoding {
+        version,
+        dwarf64,
+        address_size,
+    };
+
+    // Version 5 numbers files from 0, earlier versions from 1.
+    let (files, first_file) = if version >= 5 {
+        (read_files_v5(&mut reader, sections, encoding)?, 0)
+    } else {
+        (read_files(&mut reader, comp_dir)?, 1)
+    };
+
+    let file_base = dwarf.files.len();
+    let file_count = files.len() as u64;
+    dwarf.files.extend(files);
+
+    let file_index = |file: u64| {
+        if file >= first_file && file - first_file < file_count {
+            Some(file_base + (file - first_file) as usize)
+        } else {
+            None
+        }
+    };
+
+    reader.set_position(program_start);
+
+    let mut registers = Registers::new();
+    let mut rows = Vec::new();
+
+    while reader.position() < end {
+        let opcode = reader.read_u8()?;
+        let mut emit = false;
+
+        if opcode >= opcode_base {
+            let adjusted = u64::from(opcode - opcode_base);
+            registers.address = registers
+                .address
+                .wrapping_add((adjusted / line_range) * min_inst_length);
+            registers.line = registers
+                .line
+                .wrapping_add(line_base + (adjusted % line_range) as i64);
+            emit = true;
+        } else if opcode == 0 {
+            let (len, _) = reader.leb128_unsigned_64()?;
+            let instruction_end = end_of(&reader, len)?;
+
+            match reader.read_u8()? {
+                DW_LNE_END_SEQUENCE => {
+                    let start = rows.first().map(|row: &LineRow| row.address);
+
+                    if let Some(start) = start {
+                        if !is_tombstone(start, address_size) {
+                            dwarf.sequences.push(LineSequence {
+                                start,
+                                end: registers.address,
+                                rows,
+                            });
+                        }
+                    }
+
+                    rows = Vec::new();
+                    registers = Registers::new();
+                }
+                DW_LNE_SET_ADDRESS => {
+                    let size = len.saturating_sub(1) as u8;
+                    registers.address = read_address(&mut reader, size)?;
+                }
+                _ => {}
+            }
+
+            reader.set_position(instruction_end);
+        } else {
+            match opcode {
+                DW_LNS_COPY => emit = true,
+                DW_LNS_ADVANCE_PC => {
+                    let (advance, _) = reader.leb128_unsigned_64()?;
+                    registers.address = registers
+                        .address
+                        .wrapping_add(advance.wrapping_mul(min_inst_length));
+                }
+                DW_LNS_ADVANCE_LINE => {
+                    let (advance, _) = reader.leb128_signed_64()?;
+                    registers.line = registers.line.wrapping_add(advance);
+                }
+                DW_LNS_SET_FILE => registers.file = reader.leb128_unsigned_64()?.0,
+                DW_LNS_SET_COLUMN => registers.column = reader.leb128_unsigned_64()?.0,
+                DW_LNS_CONST_ADD_PC => {
+                    let adjusted = u64::from(255 - opcode_base);
+                    registers.address = registers
+                        .address
+                        .wrapping_add((adjusted / line_range) * min_inst_length);
+                }
+                DW_LNS_FIXED_ADVANCE_PC => {
+                    let advance = reader.read_u16::<LittleEndian>()?;
+                    registers.address = registers.address.wrapping_add(u64::from(advance));
+                }
+                _ => {
+                    // Flag-setting and unknown opcodes only carry operands
+                    // that are irrelevant to address lookup.
+                    for _ in 0..standard_opcode_lengths[opcode as usize - 1] {
+                        reader.leb128_unsigned_64()?;
+                    }
+                }
+            }
+        }
+
+        if emit {
+            rows.push(LineRow {
+                address: registers.address,
+                file: file_index(registers.file),
+                line: registers.line as u32,
+                column: registers.column as u32,
+            });
+        }
+    }
+
+    Ok(end)
+}
+
+fn read_files(reader: &mut Cursor<&[u8]>, comp_dir: Option<&str>) -> Result<Vec<String>, Error> {
+    let mut dirs = vec![comp_dir.unwrap_or("").to_string()];
+
+    loop {
+        let dir = read_cstr(reader)?;
+        if dir.is_empty() {
+            break;
+        }
+        dirs.push(join_path(comp_dir, &dir));
+    }
+
+    let mut files = Vec::new();
+
+    loop {
+        let name = read_cstr(reader)?;
+        if name.is_empty() {
+            break;
+        }
+
+        let (dir, _) = reader.leb128_unsigned_64()?;
+        let _mtime = reader.leb128_unsigned_64()?;
+        let _length = reader.leb128_unsigned_64()?;
+
+        let dir = dirs.get(dir as usize).map(|dir| dir.as_str());
+        files.push(join_path(dir, &name));
+    }
+
+    Ok(files)
+}
+
+fn read_files_v5(
+    reader: &mut Cursor<&[u8]>,
+    sections: &DebugSections,
+    encoding: Encoding,
+) -> Result<Vec<String>, Error> {
+    let dir_entries = read_entries(reader, sections, encoding)?;
+
+    let comp_dir = dir_entries.first().map(|entry| entry.0.clone());
+    let dirs: Vec<String> = dir_entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| match index {
+            0 => entry.0.clone(),
+            _ => join_path(comp_dir.as_deref(), &entry.0),
+        })
+        .collect();
+
+    let files = read_entries(reader, sections, encoding)?
+        .into_iter()
+        .map(|(path, dir)| {
+            let dir = dirs.get(dir as usize).map(|dir| dir.as_str());
+            join_path(dir, &path)
+        })
+        .collect();
+
+    Ok(files)
+}
+
+/// Reads a version 5 directory or file name table as (path, directory index)
+/// pairs.
+fn read_entries(
+    reader: &mut Cursor<&[u8]>,
+    sections: &DebugSections,
+    encoding: Encoding,
+) -> Result<Vec<(String, u64)>, Error> {
+    let format_count = reader.read_u8()?;
+    let mut formats = Vec::new();
+
+    for _ in 0..format_count {
+        let (content_type, _) = reader.leb128_unsigned_64()?;
+        let (form, _) = reader.leb128_unsigned_64()?;
+        formats.push((content_type, form));
+    }
+
+    let (count, _) = reader.leb128_unsigned_64()?;
+    let mut entries = Vec::new();
+
+    for _ in 0..count {
+        let mut path = String::new();
+        let mut dir = 0;
+
+        for &(content_type, form) in &formats {
+            let value = read_attr_value(reader, form, encoding, 0)?;
+
+            match (content_type, value) {
+                (DW_LNCT_PATH, AttrValue::String(string)) => path = string,
+                (DW_LNCT_PATH, AttrValue::StringOffset(offset)) => {
+                    path = cstr_at(&sections.str, offset).unwrap_or_default()
+                }
+                (DW_LNCT_PATH, AttrValue::LineStringOffset(offset)) => {
+                    path = cstr_at(&sections.line_str, offset).unwrap_or_default()
+                }
+                (DW_LNCT_DIRECTORY_INDEX, AttrValue::Unsigned(index)) => dir = index,
+                _ => {}
+            }
+        }
+
+        entries.push((path, dir));
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,349 @@
+//! DWARF debug information carried in the `.debug_*` custom sections of
+//! modules built with `-g`. Addresses are offsets relative to the start of
+//! the code section payload, which is how wasm toolchains encode them.
+
+mod info;
+mod line;
+
+pub use self::info::{AddressRange, DwarfFunction};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::{Cursor, Error};
+use types::{WasmModule, WasmSectionBody};
+
+#[derive(Default)]
+struct DebugSections {
+    info: Vec<u8>,
+    abbrev: Vec<u8>,
+    line: Vec<u8>,
+    line_str: Vec<u8>,
+    str: Vec<u8>,
+    str_offsets: Vec<u8>,
+    addr: Vec<u8>,
+    ranges: Vec<u8>,
+    rnglists: Vec<u8>,
+}
+
+impl DebugSections {
+    fn from_module(module: &WasmModule) -> DebugSections {
+        let mut sections = DebugSections::default();
+
+        for section in &module.sections {
+            let custom = match section.body {
+                WasmSectionBody::Custom(ref custom) => custom,
+                _ => continue,
+            };
+
+            let target = match section.name.as_deref() {
+                Some(".debug_info") => &mut sections.info,
+                Some(".debug_abbrev") => &mut sections.abbrev,
+                Some(".debug_line") => &mut sections.line,
+                Some(".debug_line_str") => &mut sections.line_str,
+                Some(".debug_str") => &mut sections.str,
+                Some(".debug_str_offsets") => &mut sections.str_offsets,
+                Some(".debug_addr") => &mut sections.addr,
+                Some(".debug_ranges") => &mut sections.ranges,
+                Some(".debug_rnglists") => &mut sections.rnglists,
+                _ => continue,
+            };
+
+            *target = custom.data.clone();
+        }
+
+        sections
+    }
+}
+
+/// Properties of a unit that determine how its attribute values are encoded.
+#[derive(Debug, Clone, Copy)]
+struct Encoding {
+    version: u16,
+    dwarf64: bool,
+    address_size: u8,
+}
+
+/// A source position resolved from the line table.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Location {
+    pub file: Option<String>,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Dwarf {
+    files: Vec<String>,
+    sequences: Vec<line::LineSequence>,
+    functions: Vec<DwarfFunction>,
+}
+
+impl Dwarf {
+    pub fn from_module(module: &WasmModule) -> Result<Dwarf, Error> {
+        let sections = DebugSections::from_module(module);
+
+        let mut dwarf = Dwarf {
+            files: Vec::new(),
+            sequences: Vec::new(),
+            functions: Vec::new(),
+        };
+
+        if sections.info.is_empty() {
+            let mut offset = 0;
+            while offset < sections.line.len() as u64 {
+                offset = line::parse_program(&sections, offset, None, &mut dwarf)?;
+            }
+        } else {
+            info::parse_units(&sections, &mut dwarf)?;
+        }
+
+        dwarf.sequences.sort_by_key(|sequence| sequence.start);
+        dwarf.functions.sort_by_key(|function| function.low_pc());
+
+        Ok(dwarf)
+    }
+
+    pub fn functions(&self) -> &[DwarfFunction] {
+        &self.functions
+    }
+
+    pub fn find_function(&self, address: u64) -> Option<&DwarfFunction> {
+        self.functions
+            .iter()
+            .find(|function| function.contains(address))
+    }
+
+    pub fn find_location(&self, address: u64) -> Option<Location> {
+        let sequence = self
+            .sequences
+            .iter()
+            .find(|sequence| sequence.start <= address && address < sequence.end)?;
+
+        let index = sequence.rows.partition_point(|row| row.address <= address);
+        let row = &sequence.rows[index.checked_sub(1)?];
+
+        Some(Location {
+            file: row.file.map(|file| self.files[file].clone()),
+            line: row.line,
+            column: row.column,
+        })
+    }
+}
+
+fn read_initial_length(reader: &mut Cursor<&[u8]>) -> Result<(u64, bool), Error> {
+    let length = reader.read_u32::<LittleEndian>()?;
+
+    if length == 0xffff_ffff {
+        Ok((reader.read_u64::<LittleEndian>()?, true))
+    } else {
+        Ok((u64::from(length), false))
+    }
+}
+
+fn read_offset(reader: &mut Cursor<&[u8]>, dwarf64: bool) -> Result<u64, Error> {
+    if dwarf64 {
+        reader.read_u64::<LittleEndian>()
+    } else {
+        Ok(u64::from(reader.read_u32::<LittleEndian>()?))
+    }
+}
+
+fn read_address(reader: &mut Cursor<&[u8]>, size: u8) -> Result<u64, Error> {
+    match size {
+        1 => Ok(u64::from(reader.read_u8()?)),
+        2 => Ok(u64::from(reader.read_u16::<LittleEndian>()?)),
+        4 => Ok(u64::from(reader.read_u32::<LittleEndian>()?)),
+        8 => reader.read_u64::<LittleEndian>(),
+        _ => Err(Error::other(format!("Unsupported address size {}", size))),
+    }
+}
+
+fn read_cstr(reader: &mut Cursor<&[u8]>) -> Result<String, Error> {
+    let mut bytes = Vec::new();
+
+    loop {
+        match reader.read_u8()? {
+            0 => break,
+            byte => bytes.push(byte),
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn cstr_at(data: &[u8], offset: u64) -> Option<String> {
+    let bytes = data.get(offset as usize..)?;
+    let end = bytes.iter().position(|&byte| byte == 0)?;
+    Some(String::from_utf8_lossy(&bytes[..end]).into_owned())
+}
+
+/// Returns the position `len` bytes past the reader's, or an error when that
+/// runs past the end of the section.
+fn end_of(reader: &Cursor<&[u8]>, len: u64) -> Result<u64, Error> {
+    reader
+        .position()
+        .checked_add(len)
+        .filter(|&end| end <= reader.get_ref().len() as u64)
+        .ok_or_else(|| Error::other("Length runs past the end of the section"))
+}
+
+fn skip(reader: &mut Cursor<&[u8]>, len: u64) {
+    let position = reader.position();
+    reader.set_position(position.saturating_add(len));
+}
+
+/// Linkers resolve addresses of discarded code to a tombstone value rather
+/// than removing their debug entries.
+fn is_tombstone(address: u64, address_size: u8) -> bool {
+    match address_size {
+        4 => address >= 0xffff_fffe,
+        _ => address >= u64::MAX - 1,
+    }
+}
+
+fn join_path(dir: Option<&str>, path: &str) -> String {
+    match dir {
+        Some(dir) if !dir.is_empty() && !path.starts_with('/') => {
+            format!("{}/{}", dir.trim_end_matches('/'), path)
+        }
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::*;
+
+    fn dwarf(sections: &[(&str, &[u8])]) -> Result<Dwarf, Error> {
+        let payloads: Vec<_> = sections
+            .iter()
+            .map(|&(name, data)| custom(name, data))
+            .collect();
+        let sections: Vec<_> = payloads
+            .iter()
+            .map(|payload| (CUSTOM, &payload[..]))
+            .collect();
+        Dwarf::from_module(&module(&sections))
+    }
+
+    /// A version 4 line program with one directory and one file in it.
+    fn line_program(program: &[u8]) -> Vec<u8> {
+        let mut header = vec![0x01, 0x01, 0x01, 0xfb, 0x0e, 0x0d];
+        header.extend(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1]);
+        header.extend(b"src\0\0a.c\0\x01\x00\x00\0");
+
+        let mut unit = vec![0x04, 0x00];
+        unit.extend(&(header.len() as u32).to_le_bytes());
+        unit.extend(header);
+        unit.extend(program);
+
+        let mut bytes = (unit.len() as u32).to_le_bytes().to_vec();
+        bytes.extend(unit);
+        bytes
+    }
+
+    #[test]
+    fn test_line_program() {
+        let line = line_program(&[
+            0x00, 0x05, 0x02, 0x10, 0x00, 0x00, 0x00, // set_address 0x10
+            0x05, 0x03, // set_column 3
+            0x01, // copy
+            0x2f, // special: address += 2, line += 1
+            0x03, 0x04, // advance_line 4
+            0x02, 0x04, // advance_pc 4
+            0x01, // copy
+            0x02, 0x02, // advance_pc 2
+            0x00, 0x01, 0x01, // end_sequence
+        ]);
+        let dwarf = dwarf(&[(".debug_line", &line)]).unwrap();
+        let location = |line, column| Location {
+            file: Some("src/a.c".to_string()),
+            line,
+            column,
+        };
+
+        assert_eq!(dwarf.find_location(0x0f), None);
+        assert_eq!(dwarf.find_location(0x10), Some(location(1, 3)));
+        assert_eq!(dwarf.find_location(0x15), Some(location(2, 3)));
+        assert_eq!(dwarf.find_location(0x17), Some(location(6, 3)));
+        assert_eq!(dwarf.find_location(0x18), None);
+    }
+
+    #[test]
+    fn test_subprograms() {
+        let abbrev = [
+            0x01, 0x11, 0x01, 0x03, 0x08, 0x00, 0x00, // compile_unit: name
+            0x02, 0x2e, 0x00, 0x03, 0x08, 0x11, 0x01, 0x12, 0x06, 0x00,
+            0x00, // subprogram: name, low_pc, high_pc as a size
+            0x00,
+        ];
+        let mut unit = vec![0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04];
+        unit.extend(b"\x01a.c\0");
+        unit.extend(b"\x02main\0\x10\x00\x00\x00\x20\x00\x00\x00");
+        // A function the linker discarded, with a tombstone address.
+        unit.extend(b"\x02gone\0\xff\xff\xff\xff\x04\x00\x00\x00");
+        unit.push(0x00);
+        let mut info = (unit.len() as u32).to_le_bytes().to_vec();
+        info.extend(unit);
+
+        let dwarf = dwarf(&[(".debug_abbrev", &abbrev), (".debug_info", &info)]).unwrap();
+
+        assert_eq!(
+            dwarf.functions(),
+            &[DwarfFunction {
+                name: Some("main".to_string()),
+                linkage_name: None,
+                ranges: vec![AddressRange {
+                    begin: 0x10,
+                    end: 0x30
+                }],
+            }]
+        );
+        assert_eq!(dwarf.find_function(0x2f), Some(&dwarf.functions()[0]));
+        assert_eq!(dwarf.find_function(0x30), None);
+    }
+
+    #[test]
+    fn test_malformed_lengths() {
+        // A DWARF64 unit length of u64::MAX.
+        let length = [0xff; 12];
+        assert!(dwarf(&[(".debug_line", &length)]).is_err());
+        assert!(dwarf(&[(".debug_abbrev", &[0x00]), (".debug_info", &length)]).is_err());
+
+        // An extended opcode longer than the section.
+        let line = line_program(&[0x00, 0xff, 0xff, 0xff, 0xff, 0x0f, 0x01]);
+        assert!(dwarf(&[(".debug_line", &line)]).is_err());
+
+        // Address and line advances that overflow wrap instead.
+        let mut program = vec![0x02];
+        program.extend(uleb(u64::MAX));
+        program.extend(&[
+            0x03, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00,
+        ]);
+        program.extend(&[0x01, 0x02, 0x01, 0x00, 0x01, 0x01]);
+        assert!(dwarf(&[(".debug_line", &line_program(&program))]).is_ok());
+    }
+
+    #[test]
+    fn test_string_index_overflow() {
+        let abbrev = [
+            0x01, 0x11, 0x01, 0x00, 0x00, // compile_unit
+            0x02, 0x2e, 0x00, 0x03, 0x1a, 0x11, 0x01, 0x12, 0x06, 0x00,
+            0x00, // subprogram: name as a strx, low_pc, high_pc as a size
+            0x00,
+        ];
+        // A version 5 unit whose subprogram names string index u64::MAX.
+        let mut unit = vec![0x05, 0x00, 0x01, 0x04, 0x00, 0x00, 0x00, 0x00];
+        unit.extend(&[0x01, 0x02]);
+        unit.extend(uleb(u64::MAX));
+        unit.extend(&[0x10, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        let mut info = (unit.len() as u32).to_le_bytes().to_vec();
+        info.extend(unit);
+
+        let dwarf = dwarf(&[(".debug_abbrev", &abbrev), (".debug_info", &info)]).unwrap();
+
+        assert_eq!(dwarf.functions().len(), 1);
+        assert_eq!(dwarf.functions()[0].name, None);
+    }
+}
@@ -0,0 +1,10 @@
+//! Opt-in support for proposals that are decoded only when asked for.
+
+/// Proposals the operator decoder accepts beyond the default instruction
+/// set. Everything is disabled by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Features {
+    /// Relaxed SIMD instructions (`0xfd 0x100` to `0xfd 0x113`), whose
+    /// results may differ between hosts.
+    pub relaxed_simd: bool,
+}
@@ -1,4 +1,4 @@
-use std::io::{Error, ErrorKind, Read};
+use std::io::{Error, Read};
 
 #[derive(PartialEq)]
 enum Sign {
@@ -6,8 +6,11 @@ enum Sign {
     Unsigned,
 }
 
-fn leb128<T: Read + ?Sized>(reader: &mut T, signage: Sign) -> Result<(i64, usize), Error> {
-    let n = 32;
+fn leb128<T: Read + ?Sized>(
+    reader: &mut T,
+    signage: Sign,
+    n: usize,
+) -> Result<(i64, usize), Error> {
     let mut result: i64 = 0;
     let mut shift: usize = 0;
     let mut bytes_read = 0;
@@ -15,7 +18,7 @@ fn leb128<T: Read + ?Sized>(reader: &mut T, signage: Sign) -> Result<(i64, usize
 
     loop {
         let mut buf = [0];
-        reader.read(&mut buf)?;
+        reader.read_exact(&mut buf)?;
         let byte = buf[0];
         let low_order_7 = (0b0111_1111 & byte) as i64;
         let hob = (byte >> 7) & 1;
@@ -26,7 +29,7 @@ fn leb128<T: Read + ?Sized>(reader: &mut T, signage: Sign) -> Result<(i64, usize
         bytes_read += 1;
 
         if hob == 0 {
-            if signage == Sign::Signed {
+            if signage == Sign::Signed && shift < 64 {
                 let sign_bit_set = (0b0100_0000 & byte) > 0;
 
                 if sign_bit_set {
@@ -38,8 +41,7 @@ fn leb128<T: Read + ?Sized>(reader: &mut T, signage: Sign) -> Result<(i64, usize
         }
 
         if bytes_read == ceil_bytes {
-            return Err(Error::new(
-                ErrorKind::Other,
+            return Err(Error::other(
                 "No leb128 encoded number found in byte stream",
             ));
         }
@@ -48,10 +50,17 @@ fn leb128<T: Read + ?Sized>(reader: &mut T, signage: Sign) -> Result<(i64, usize
 
 pub trait ReadLeb128Ext: Read {
     fn leb128_signed(&mut self) -> Result<(i64, usize), Error> {
-        leb128(self, Sign::Signed)
+        leb128(self, Sign::Signed, 32)
     }
     fn leb128_unsigned(&mut self) -> Result<(i64, usize), Error> {
-        leb128(self, Sign::Unsigned)
+        leb128(self, Sign::Unsigned, 32)
+    }
+    fn leb128_signed_64(&mut self) -> Result<(i64, usize), Error> {
+        leb128(self, Sign::Signed, 64)
+    }
+    fn leb128_unsigned_64(&mut self) -> Result<(u64, usize), Error> {
+        let (result, bytes_read) = leb128(self, Sign::Unsigned, 64)?;
+        Ok((result as u64, bytes_read))
     }
 }
 
@@ -86,4 +95,14 @@ mod tests {
         assert_eq!(number, -624485);
         assert_eq!(bytes_read, 3);
     }
+
+    #[test]
+    fn test_signed_leb128_64() {
+        let bytes = [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7F];
+        let mut cursor = Cursor::new(bytes);
+        let (number, bytes_read) = cursor.leb128_signed_64().unwrap();
+
+        assert_eq!(number, i64::MIN);
+        assert_eq!(bytes_read, 10);
+    }
 }
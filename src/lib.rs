@@ -4,16 +4,28 @@ extern crate serde_json;
 #[macro_use]
 extern crate serde_derive;
 
+pub mod call_graph;
+pub mod component;
+pub mod dead_code;
+pub mod dwarf;
+pub mod features;
 mod leb128;
-mod types;
+pub mod size_profile;
+pub mod source_map;
+#[cfg(test)]
+mod testing;
+pub mod types;
+pub mod validate;
 
 use byteorder::{LittleEndian, ReadBytesExt};
+use component::wit::ComponentTypeMetadata;
 use leb128::ReadLeb128Ext;
-use std::io::{Error, ErrorKind, Read, Seek, SeekFrom};
+use std::io::{Cursor, Error, Read, Seek};
 use types::*;
 
 static WASM_MAGIC_NUMBER: u32 = 0x6d736100;
 static WASM_VERSION_KNOWN: u32 = 0x01;
+static COMPONENT_VERSION_LAYER: u32 = 0x0001000d;
 
 fn parse_section<T: Read>(reader: &mut T) -> Result<Option<WasmSection>, Error> {
     let code = match reader.read_u8() {
@@ -26,8 +38,7 @@ fn parse_section<T: Read>(reader: &mut T) -> Result<Option<WasmSection>, Error>
 
     if code == 0 {
         let (name_len, name_len_bytes) = reader.leb128_unsigned()?;
-        let mut n = vec![0; name_len as usize];
-        reader.read(&mut n)?;
+        let n = read_bytes(reader, name_len as u64)?;
         let nam = String::from_utf8_lossy(&n).into_owned();
         name = Some(nam);
 
@@ -36,6 +47,7 @@ fn parse_section<T: Read>(reader: &mut T) -> Result<Option<WasmSection>, Error>
     }
 
     let body = match code {
+        0 => parse_custom_section(reader, name.as_deref().unwrap_or(""), payload_len as usize)?,
         1 => WasmSectionBody::Types(Box::new(TypeSection::from_reader(reader)?)),
         2 => WasmSectionBody::Import(Box::new(ImportSection::from_reader(reader)?)),
         3 => WasmSectionBody::Function(Box::new(FunctionSection::from_reader(reader)?)),
@@ -47,6 +59,8 @@ fn parse_section<T: Read>(reader: &mut T) -> Result<Option<WasmSection>, Error>
         9 => WasmSectionBody::Element(Box::new(ElementSection::from_reader(reader)?)),
         10 => WasmSectionBody::Code(Box::new(CodeSection::from_reader(reader)?)),
         11 => WasmSectionBody::Data(Box::new(DataSection::from_reader(reader)?)),
+        12 => WasmSectionBody::DataCount(Box::new(DataCountSection::from_reader(reader)?)),
+        13 => WasmSectionBody::Tag(Box::new(TagSection::from_reader(reader)?)),
         _ => WasmSectionBody::Custom(Box::new(CustomSection::from_reader(
             reader,
             payload_len as usize,
@@ -60,43 +74,100 @@ fn parse_section<T: Read>(reader: &mut T) -> Result<Option<WasmSection>, Error>
     }))
 }
 
+fn parse_custom_section<T: Read>(
+    reader: &mut T,
+    name: &str,
+    len: usize,
+) -> Result<WasmSectionBody, Error> {
+    let custom = CustomSection::from_reader(reader, len)?;
+
+    let decoded = match name {
+        "linking" => LinkingSection::from_reader(&mut Cursor::new(&custom.data))
+            .map(|section| WasmSectionBody::Linking(Box::new(section))),
+        "dylink.0" => DylinkSection::from_reader(&mut Cursor::new(&custom.data))
+            .map(|section| WasmSectionBody::Dylink(Box::new(section))),
+        "dylink" => DylinkSection::from_reader_legacy(&mut Cursor::new(&custom.data))
+            .map(|section| WasmSectionBody::Dylink(Box::new(section))),
+        "sourceMappingURL" => SourceMappingUrlSection::from_reader(&mut Cursor::new(&custom.data))
+            .map(|section| WasmSectionBody::SourceMappingUrl(Box::new(section))),
+        "external_debug_info" => {
+            ExternalDebugInfoSection::from_reader(&mut Cursor::new(&custom.data))
+                .map(|section| WasmSectionBody::ExternalDebugInfo(Box::new(section)))
+        }
+        "name" => NameSection::from_reader(&mut Cursor::new(&custom.data))
+            .map(|section| WasmSectionBody::Name(Box::new(section))),
+        "metadata.code.branch_hint" => {
+            BranchHintSection::from_reader(&mut Cursor::new(&custom.data))
+                .map(|section| WasmSectionBody::BranchHint(Box::new(section)))
+        }
+        _ if name.starts_with("component-type") => {
+            ComponentTypeMetadata::from_reader(&mut Cursor::new(&custom.data))
+                .map(|section| WasmSectionBody::ComponentType(Box::new(section)))
+        }
+        _ if name.starts_with("reloc.") => {
+            RelocSection::from_reader(&mut Cursor::new(&custom.data))
+                .map(|section| WasmSectionBody::Reloc(Box::new(section)))
+        }
+        _ => return Ok(WasmSectionBody::Custom(Box::new(custom))),
+    };
+
+    // A malformed custom section does not invalidate the module, so fall
+    // back to keeping its raw bytes.
+    Ok(decoded.unwrap_or_else(|_| WasmSectionBody::Custom(Box::new(custom))))
+}
+
 pub fn parse<T: Read + Seek>(mut rdr: T) -> Result<WasmModule, Error> {
+    let version = parse_header(&mut rdr)?;
+    let sections = parse_sections(&mut rdr)?;
+
+    Ok(WasmModule { version, sections })
+}
+
+fn parse_header<T: Read>(rdr: &mut T) -> Result<u32, Error> {
     let magic = rdr.read_u32::<LittleEndian>()?;
 
     if magic != WASM_MAGIC_NUMBER {
-        return Err(Error::new(
-            ErrorKind::Other,
-            format!(
-                "Magic number 0x{:x} is not the expected value 0x{:x}",
-                magic, WASM_MAGIC_NUMBER
-            ),
-        ));
+        return Err(Error::other(format!(
+            "Magic number 0x{:x} is not the expected value 0x{:x}",
+            magic, WASM_MAGIC_NUMBER
+        )));
     }
 
     let version = rdr.read_u32::<LittleEndian>()?;
 
-    if version != WASM_VERSION_KNOWN {
-        return Err(Error::new(
-            ErrorKind::Other,
-            format!("Unknown WASM version {}", version),
+    if version == COMPONENT_VERSION_LAYER {
+        return Err(Error::other(
+            "Binary is a component, use parse_component instead",
         ));
     }
 
-    let mut module = WasmModule {
-        version,
-        sections: Vec::new(),
-    };
+    if version != WASM_VERSION_KNOWN {
+        return Err(Error::other(format!("Unknown WASM version {}", version)));
+    }
+
+    Ok(version)
+}
+
+/// Reads sections until the end of the input. Errors report the position
+/// they occurred at.
+fn parse_sections<T: Read + Seek>(rdr: &mut T) -> Result<Vec<WasmSection>, Error> {
+    let mut sections = Vec::new();
 
     loop {
-        let section = parse_section(&mut rdr).unwrap_or_else(|err| {
-            let position = rdr.seek(SeekFrom::Current(0)).unwrap();
-            panic!(format!("PARSE ERROR AT 0x{:012X}: {}", position, err));
-        });
-        match section {
-            Some(section) => module.sections.push(section),
-            None => break,
+        match parse_section(rdr) {
+            Ok(Some(section)) => sections.push(section),
+            Ok(None) => return Ok(sections),
+            Err(err) => {
+                let position = rdr.stream_position()?;
+                return Err(Error::other(format!(
+                    "PARSE ERROR AT 0x{:012X}: {}",
+                    position, err
+                )));
+            }
         }
     }
+}
 
-    return Ok(module);
+pub fn parse_component<T: Read + Seek>(mut rdr: T) -> Result<component::Component, Error> {
+    component::Component::from_reader(&mut rdr)
 }
@@ -0,0 +1,414 @@
+//! Attributes the bytes of a module to the items that take them up, in the
+//! manner of twiggy. An item's shallow size is what it occupies itself; its
+//! retained size adds everything it dominates in the graph of calls and
+//! references, which is what removing it would save.
+
+use call_graph::CallGraph;
+use dead_code::{entry_points, global_references};
+use features::Features;
+use serde_json;
+use std::io::Error;
+use types::data_section::DataMode;
+use types::{Operator, WasmModule, WasmSectionBody};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum ItemKind {
+    Function,
+    Data,
+    Custom,
+}
+
+/// Identifies an item by its function index, data segment index or, for
+/// custom sections, position among the module's custom sections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ItemId {
+    pub kind: ItemKind,
+    pub index: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SizeItem {
+    pub id: ItemId,
+    pub name: String,
+    pub shallow_size: u32,
+    pub retained_size: u64,
+    /// The item's immediate dominator, `None` when only the module's entry
+    /// points dominate it or the item is unreachable.
+    pub dominator: Option<ItemId>,
+    pub reachable: bool,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SizeProfile {
+    /// Bytes of section contents in the module, the basis for percentages.
+    pub total_size: u64,
+    /// Items by descending retained size.
+    pub items: Vec<SizeItem>,
+}
+
+impl SizeProfile {
+    pub fn from_module(module: &WasmModule) -> Result<SizeProfile, Error> {
+        SizeProfile::from_module_with_features(module, &Features::default())
+    }
+
+    pub fn from_module_with_features(
+        module: &WasmModule,
+        features: &Features,
+    ) -> Result<SizeProfile, Error> {
+        let graph = CallGraph::from_module_with_features(module, features)?;
+        let names = module.names();
+
+        // Node 0 stands for the module's entry points, functions follow in
+        // index order and then data segments and custom sections.
+        let mut ids = vec![None];
+        let mut labels = vec![String::new()];
+        let mut shallow = vec![0];
+        let mut successors = vec![Vec::new()];
+
+        let functions: Vec<_> = module.functions().collect();
+        for function in &functions {
+            ids.push(Some(ItemId {
+                kind: ItemKind::Function,
+                index: function.index,
+            }));
+            labels.push(
+                function
+                    .debug_name
+                    .or_else(|| function.export_names.first().cloned())
+                    .map(|name| name.to_string())
+                    .unwrap_or_else(|| format!("func[{}]", function.index)),
+            );
+            shallow.push(
+                function
+                    .body
+                    .map_or(0, |body| body.locals_size + body.body_size),
+            );
+            successors.push(
+                graph
+                    .callees(function.index)
+                    .iter()
+                    .map(|&callee| callee as usize + 1)
+                    .collect(),
+            );
+        }
+        // Entry points come from unchecked indices such as the start function.
+        // Functions globals reference are kept alive with them.
+        for root in entry_points(module, &graph)?
+            .into_iter()
+            .chain(global_references(module)?)
+        {
+            if (root as usize) < functions.len() {
+                successors[0].push(root as usize + 1);
+            }
+        }
+
+        // Custom sections before the data section take the nodes after the
+        // functions, so each segment's node is recorded.
+        let mut data_nodes = Vec::new();
+        let mut total_size = 0;
+        let mut custom_index = 0;
+        for section in &module.sections {
+            let name = section.name.as_deref().unwrap_or("");
+            total_size += u64::from(section.payload_len) + name.len() as u64;
+
+            match section.body {
+                WasmSectionBody::Data(ref data) => {
+                    for (index, segment) in data.entries.iter().enumerate() {
+                        let index = index as u32;
+                        if let DataMode::Active { .. } = segment.mode {
+                            successors[0].push(ids.len());
+                        }
+                        data_nodes.push(ids.len());
+                        ids.push(Some(ItemId {
+                            kind: ItemKind::Data,
+                            index,
+                        }));
+                        labels.push(
+                            names
+                                .and_then(|names| names.data_name(index))
+                                .map(|name| name.to_string())
+                                .unwrap_or_else(|| format!("data[{}]", index)),
+                        );
+                        shallow.push(segment.data.len() as u32);
+                        successors.push(Vec::new());
+                    }
+                }
+                _ if section.name.is_some() => {
+                    successors[0].push(ids.len());
+                    ids.push(Some(ItemId {
+                        kind: ItemKind::Custom,
+                        index: custom_index,
+                    }));
+                    labels.push(format!("custom section '{}'", name));
+                    shallow.push(section.payload_len + name.len() as u32);
+                    successors.push(Vec::new());
+                    custom_index += 1;
+                }
+                _ => {}
+            }
+        }
+
+        for function in &functions {
+            if let Some(body) = function.body {
+                for operator in body.operators_with_features(features)? {
+                    match operator {
+                        Operator::MemoryInit { data_index, .. }
+                        | Operator::DataDrop(data_index)
+                        | Operator::ArrayNewData { data_index, .. }
+                        | Operator::ArrayInitData { data_index, .. } => {
+                            if let Some(&node) = data_nodes.get(data_index as usize) {
+                                successors[function.index as usize + 1].push(node);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let (order, dominators) = dominators(&successors);
+
+        let mut retained: Vec<u64> = shallow.iter().map(|&size| u64::from(size)).collect();
+        for &node in &order {
+            if let Some(dominator) = dominators[node] {
+                if node != 0 {
+                    retained[dominator] += retained[node];
+                }
+            }
+        }
+
+        let mut items: Vec<SizeItem> = ids
+            .iter()
+            .enumerate()
+            .filter_map(|(node, id)| id.map(|id| (node, id)))
+            .filter(|&(node, id)| {
+                id.kind != ItemKind::Function || functions[node - 1].body.is_some()
+            })
+            .map(|(node, id)| SizeItem {
+                id,
+                name: labels[node].clone(),
+                shallow_size: shallow[node],
+                retained_size: retained[node],
+                dominator: dominators[node].and_then(|dominator| ids[dominator]),
+                reachable: dominators[node].is_some(),
+            })
+            .collect();
+
+        items.sort_by(|a, b| {
+            b.retained_size
+                .cmp(&a.retained_size)
+                .then(b.shallow_size.cmp(&a.shallow_size))
+                .then(a.id.cmp(&b.id))
+        });
+
+        Ok(SizeProfile { total_size, items })
+    }
+
+    /// Renders the items as a table with one row per item.
+    pub fn to_table(&self) -> String {
+        let percent = |size: u64| {
+            if self.total_size == 0 {
+                0.0
+            } else {
+                size as f64 * 100.0 / self.total_size as f64
+            }
+        };
+
+        let mut table = format!(
+            "{:>14} | {:>9} | {:>14} | {:>10} | Item\n",
+            "Shallow Bytes", "Shallow %", "Retained Bytes", "Retained %"
+        );
+        table.push_str(&format!(
+            "{}-+-{}-+-{}-+-{}-+-{}\n",
+            "-".repeat(14),
+            "-".repeat(9),
+            "-".repeat(14),
+            "-".repeat(10),
+            "-".repeat(4)
+        ));
+
+        for item in &self.items {
+            table.push_str(&format!(
+                "{:>14} | {:>8.2}% | {:>14} | {:>9.2}% | {}\n",
+                item.shallow_size,
+                percent(u64::from(item.shallow_size)),
+                item.retained_size,
+                percent(item.retained_size),
+                item.name
+            ));
+        }
+
+        table
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(self).map_err(Error::from)
+    }
+}
+
+/// Computes immediate dominators of the nodes reachable from node 0 with the
+/// iterative algorithm of Cooper, Harvey and Kennedy. Returns the reachable
+/// nodes in postorder, so every node precedes its dominator, and each node's
+/// immediate dominator. Node 0 dominates itself; unreachable nodes have none.
+fn dominators(successors: &[Vec<usize>]) -> (Vec<usize>, Vec<Option<usize>>) {
+    let count = successors.len();
+    let mut order = Vec::with_capacity(count);
+    let mut visited = vec![false; count];
+    let mut stack = vec![(0, 0)];
+    visited[0] = true;
+
+    while let Some(&mut (node, ref mut next)) = stack.last_mut() {
+        if let Some(&successor) = successors[node].get(*next) {
+            *next += 1;
+            if !visited[successor] {
+                visited[successor] = true;
+                stack.push((successor, 0));
+            }
+        } else {
+            order.push(node);
+            stack.pop();
+        }
+    }
+
+    let mut position = vec![0; count];
+    for (index, &node) in order.iter().enumerate() {
+        position[node] = index;
+    }
+
+    let mut predecessors = vec![Vec::new(); count];
+    for &node in &order {
+        for &successor in &successors[node] {
+            predecessors[successor].push(node);
+        }
+    }
+
+    let mut dominators = vec![None; count];
+    dominators[0] = Some(0);
+    let mut changed = true;
+
+    while changed {
+        changed = false;
+        for &node in order.iter().rev().skip(1) {
+            let mut dominator: Option<usize> = None;
+            for &predecessor in &predecessors[node] {
+                if dominators[predecessor].is_none() {
+                    continue;
+                }
+                dominator = Some(match dominator {
+                    None => predecessor,
+                    Some(mut other) => {
+                        let mut finger = predecessor;
+                        while finger != other {
+                            while position[finger] < position[other] {
+                                finger = dominators[finger].unwrap();
+                            }
+                            while position[other] < position[finger] {
+                                other = dominators[other].unwrap();
+                            }
+                        }
+                        finger
+                    }
+                });
+            }
+            if dominator != dominators[node] {
+                dominators[node] = dominator;
+                changed = true;
+            }
+        }
+    }
+
+    (order, dominators)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::*;
+
+    #[test]
+    fn test_retained_sizes() {
+        // (func (export "m") call 1 call 2) (func call 2) (func nop) (func)
+        let module = module(&[
+            (TYPE, &[0x01, 0x60, 0x00, 0x00]),
+            (FUNCTION, &[0x04, 0x00, 0x00, 0x00, 0x00]),
+            (EXPORT, &[0x01, 0x01, 0x6d, 0x00, 0x00]),
+            (
+                CODE,
+                &code(&[
+                    &[0x00, 0x10, 0x01, 0x10, 0x02, 0x0b],
+                    &[0x00, 0x10, 0x02, 0x0b],
+                    &[0x00, 0x01, 0x0b],
+                    &[0x00, 0x0b],
+                ]),
+            ),
+        ]);
+        let profile = SizeProfile::from_module(&module).unwrap();
+        let function = |index| ItemId {
+            kind: ItemKind::Function,
+            index,
+        };
+        let sizes: Vec<_> = profile
+            .items
+            .iter()
+            .map(|item| (item.id.index, item.shallow_size, item.retained_size))
+            .collect();
+
+        assert_eq!(sizes, vec![(0, 6, 13), (1, 4, 4), (2, 3, 3), (3, 2, 2)]);
+        assert_eq!(profile.items[2].dominator, Some(function(0)));
+        assert_eq!(profile.items[1].dominator, Some(function(0)));
+        assert!(!profile.items[3].reachable);
+        assert!(profile.to_table().contains("38.24% | m\n"));
+    }
+
+    #[test]
+    fn test_data_after_custom_section() {
+        // (func (export "m") i32.const 0 i32.const 0 i32.const 4 memory.init 0 data.drop 5)
+        // with a custom section ahead of a passive segment.
+        let body = [
+            0x00, 0x41, 0x00, 0x41, 0x00, 0x41, 0x04, 0xfc, 0x08, 0x00, 0x00, 0xfc, 0x09, 0x05,
+            0x0b,
+        ];
+        let module = module(&[
+            (TYPE, &[0x01, 0x60, 0x00, 0x00]),
+            (FUNCTION, &[0x01, 0x00]),
+            (MEMORY, &[0x01, 0x00, 0x01]),
+            (EXPORT, &[0x01, 0x01, 0x6d, 0x00, 0x00]),
+            (DATA_COUNT, &[0x01]),
+            (CODE, &code(&[&body])),
+            (CUSTOM, &custom("x", &[0x00])),
+            (DATA, &[0x01, 0x01, 0x04, 0x61, 0x62, 0x63, 0x64]),
+        ]);
+        let profile = SizeProfile::from_module(&module).unwrap();
+        let data = profile
+            .items
+            .iter()
+            .find(|item| item.id.kind == ItemKind::Data)
+            .unwrap();
+
+        assert!(data.reachable);
+        assert_eq!(
+            data.dominator,
+            Some(ItemId {
+                kind: ItemKind::Function,
+                index: 0
+            })
+        );
+        assert_eq!(profile.items[0].retained_size, 15 + 4);
+    }
+
+    #[test]
+    fn test_start_out_of_range() {
+        // (start 5) with only one function
+        let module = module(&[
+            (TYPE, &[0x01, 0x60, 0x00, 0x00]),
+            (FUNCTION, &[0x01, 0x00]),
+            (START, &[0x05]),
+            (CODE, &code(&[&[0x00, 0x0b]])),
+        ]);
+        let profile = SizeProfile::from_module(&module).unwrap();
+
+        assert_eq!(profile.items.len(), 1);
+        assert!(!profile.items[0].reachable);
+    }
+}
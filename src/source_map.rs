@@ -0,0 +1,251 @@
+//! Source map (revision 3) loading for modules whose toolchain emits a
+//! `sourceMappingURL` section instead of DWARF. In wasm source maps every
+//! mapping sits on generated line 0 and the generated column is the byte
+//! offset of the instruction within the module.
+
+use serde_json;
+use std::fs::File;
+use std::io::{BufReader, Error, Read};
+use std::path::Path;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSourceMap {
+    version: u32,
+    #[serde(default)]
+    source_root: Option<String>,
+    sources: Vec<Option<String>>,
+    #[serde(default)]
+    names: Vec<String>,
+    mappings: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Mapping {
+    pub generated_line: u32,
+    pub generated_column: u32,
+    pub source: Option<u32>,
+    pub original_line: u32,
+    pub original_column: u32,
+    pub name: Option<u32>,
+}
+
+/// An original source position. Lines are 1-based and columns 0-based, as
+/// reported by source map consumers.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct OriginalPosition {
+    pub source: String,
+    pub line: u32,
+    pub column: u32,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceMap {
+    pub sources: Vec<String>,
+    pub names: Vec<String>,
+    pub mappings: Vec<Mapping>,
+}
+
+impl SourceMap {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<SourceMap, Error> {
+        let file = File::open(path)?;
+        SourceMap::from_reader(BufReader::new(file))
+    }
+
+    pub fn from_reader<T: Read>(reader: T) -> Result<SourceMap, Error> {
+        let raw: RawSourceMap = serde_json::from_reader(reader)?;
+
+        if raw.version != 3 {
+            return Err(Error::other(format!(
+                "Unsupported source map version {}",
+                raw.version
+            )));
+        }
+
+        let source_root = raw.source_root.unwrap_or_default();
+        let sources = raw
+            .sources
+            .into_iter()
+            .map(|source| {
+                let source = source.unwrap_or_default();
+                if source_root.is_empty() {
+                    source
+                } else {
+                    format!("{}/{}", source_root.trim_end_matches('/'), source)
+                }
+            })
+            .collect();
+
+        let mut mappings = decode_mappings(&raw.mappings)?;
+        mappings.sort_by_key(|mapping| (mapping.generated_line, mapping.generated_column));
+
+        Ok(SourceMap {
+            sources,
+            names: raw.names,
+            mappings,
+        })
+    }
+
+    /// Resolves a byte offset within the module to the original position of
+    /// the closest preceding mapping.
+    pub fn lookup(&self, offset: u32) -> Option<OriginalPosition> {
+        let end = self.mappings.partition_point(|mapping| {
+            (mapping.generated_line, mapping.generated_column) <= (0, offset)
+        });
+        let mapping = &self.mappings[end.checked_sub(1)?];
+
+        if mapping.generated_line != 0 {
+            return None;
+        }
+
+        let source = self.sources.get(mapping.source? as usize)?;
+
+        Some(OriginalPosition {
+            source: source.clone(),
+            line: mapping.original_line + 1,
+            column: mapping.original_column,
+            name: mapping
+                .name
+                .and_then(|name| self.names.get(name as usize))
+                .cloned(),
+        })
+    }
+}
+
+fn decode_mappings(mappings: &str) -> Result<Vec<Mapping>, Error> {
+    let mut decoded = Vec::new();
+
+    // Every field apart from the generated column is relative to the
+    // previous segment across the whole map.
+    let mut source = 0;
+    let mut original_line = 0;
+    let mut original_column = 0;
+    let mut name = 0;
+
+    for (generated_line, line) in mappings.split(';').enumerate() {
+        let mut generated_column = 0;
+
+        for segment in line.split(',').filter(|segment| !segment.is_empty()) {
+            let fields = decode_vlq(segment)?;
+
+            advance(&mut generated_column, fields[0])?;
+
+            let mut mapping = Mapping {
+                generated_line: generated_line as u32,
+                generated_column: generated_column as u32,
+                source: None,
+                original_line: 0,
+                original_column: 0,
+                name: None,
+            };
+
+            if fields.len() >= 4 {
+                advance(&mut source, fields[1])?;
+                advance(&mut original_line, fields[2])?;
+                advance(&mut original_column, fields[3])?;
+
+                mapping.source = Some(source as u32);
+                mapping.original_line = original_line as u32;
+                mapping.original_column = original_column as u32;
+            }
+
+            if fields.len() >= 5 {
+                advance(&mut name, fields[4])?;
+                mapping.name = Some(name as u32);
+            }
+
+            decoded.push(mapping);
+        }
+    }
+
+    Ok(decoded)
+}
+
+/// Adds a relative field to its running total.
+fn advance(total: &mut i64, delta: i64) -> Result<(), Error> {
+    *total = total
+        .checked_add(delta)
+        .ok_or_else(|| Error::other("Source map mapping field overflows"))?;
+    Ok(())
+}
+
+fn decode_vlq(segment: &str) -> Result<Vec<i64>, Error> {
+    let mut values = Vec::new();
+    let mut value: i64 = 0;
+    let mut shift = 0;
+
+    for byte in segment.bytes() {
+        let digit = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(Error::other("Invalid base64 digit in source map mappings")),
+        };
+
+        value |= i64::from(digit & 0b1_1111) << shift;
+        shift += 5;
+
+        if digit & 0b10_0000 == 0 {
+            let magnitude = value >> 1;
+            values.push(if value & 1 == 1 {
+                -magnitude
+            } else {
+                magnitude
+            });
+            value = 0;
+            shift = 0;
+        } else if shift > 60 {
+            return Err(Error::other("Source map VLQ value is too large"));
+        }
+    }
+
+    if shift != 0 || values.is_empty() {
+        return Err(Error::other("Truncated source map segment"));
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{decode_mappings, decode_vlq, SourceMap};
+
+    #[test]
+    fn test_decode_vlq() {
+        assert_eq!(decode_vlq("AAgBC").unwrap(), vec![0, 0, 16, 1]);
+        assert_eq!(decode_vlq("D").unwrap(), vec![-1]);
+        assert!(decode_vlq("g").is_err());
+    }
+
+    #[test]
+    fn test_decode_mappings_overflow() {
+        // Generated column deltas of 2^61 overflow on the fourth segment.
+        let segment = "ggggggggggggE";
+
+        assert!(decode_mappings(&[segment; 3].join(",")).is_ok());
+        assert!(decode_mappings(&[segment; 4].join(",")).is_err());
+    }
+
+    #[test]
+    fn test_lookup() {
+        let json = r#"{
+            "version": 3,
+            "sources": ["main.c"],
+            "names": ["main"],
+            "mappings": "oBAAA,IACEA,EAAE"
+        }"#;
+        let map = SourceMap::from_reader(json.as_bytes()).unwrap();
+
+        let position = map.lookup(25).unwrap();
+        assert_eq!(position.source, "main.c");
+        assert_eq!(position.line, 2);
+        assert_eq!(position.column, 2);
+        assert_eq!(position.name, Some("main".to_string()));
+
+        assert!(map.lookup(19).is_none());
+    }
+}
@@ -0,0 +1,72 @@
+//! Helpers for assembling small modules in tests. Sections are given as
+//! `(id, payload)` pairs and the helpers fill in the header and every size
+//! prefix.
+
+use parse;
+use std::io::Cursor;
+use types::WasmModule;
+
+pub const CUSTOM: u8 = 0;
+pub const TYPE: u8 = 1;
+pub const IMPORT: u8 = 2;
+pub const FUNCTION: u8 = 3;
+pub const TABLE: u8 = 4;
+pub const MEMORY: u8 = 5;
+pub const GLOBAL: u8 = 6;
+pub const EXPORT: u8 = 7;
+pub const START: u8 = 8;
+pub const ELEMENT: u8 = 9;
+pub const CODE: u8 = 10;
+pub const DATA: u8 = 11;
+pub const DATA_COUNT: u8 = 12;
+pub const TAG: u8 = 13;
+
+pub fn module_bytes(sections: &[(u8, &[u8])]) -> Vec<u8> {
+    let mut bytes = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+
+    for &(id, payload) in sections {
+        bytes.push(id);
+        bytes.extend(uleb(payload.len() as u64));
+        bytes.extend(payload);
+    }
+
+    bytes
+}
+
+pub fn module(sections: &[(u8, &[u8])]) -> WasmModule {
+    parse(Cursor::new(module_bytes(sections))).unwrap()
+}
+
+/// A code section payload. Each body starts with its local declarations.
+pub fn code(bodies: &[&[u8]]) -> Vec<u8> {
+    let mut payload = uleb(bodies.len() as u64);
+
+    for body in bodies {
+        payload.extend(uleb(body.len() as u64));
+        payload.extend(*body);
+    }
+
+    payload
+}
+
+/// A custom section payload: the section name followed by `data`.
+pub fn custom(name: &str, data: &[u8]) -> Vec<u8> {
+    let mut payload = uleb(name.len() as u64);
+    payload.extend(name.as_bytes());
+    payload.extend(data);
+    payload
+}
+
+pub fn uleb(mut value: u64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
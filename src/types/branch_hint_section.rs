@@ -0,0 +1,192 @@
+use byteorder::ReadBytesExt;
+use std::io::{Error, Read};
+
+use super::*;
+
+/// The `metadata.code.branch_hint` custom section, recording for `br_if`
+/// and `if` instructions whether the branch is likely to be taken.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BranchHintSection {
+    pub count: u32,
+    pub entries: Vec<FunctionBranchHints>,
+}
+
+impl BranchHintSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<BranchHintSection, Error> {
+        let entries = read_vec(reader, FunctionBranchHints::from_reader)?;
+
+        Ok(BranchHintSection {
+            count: entries.len() as u32,
+            entries,
+        })
+    }
+
+    /// Checks every hint against the decoded body of its function and
+    /// returns those that do not point at a `br_if` or `if`.
+    pub fn check(
+        &self,
+        module: &WasmModule,
+        features: &Features,
+    ) -> Result<Vec<BranchHintIssue>, Error> {
+        let mut issues = Vec::new();
+        let bodies: Vec<_> = module.functions().map(|function| function.body).collect();
+
+        for entry in &self.entries {
+            let issue = |hint: &BranchHint, problem| BranchHintIssue {
+                func_index: entry.func_index,
+                offset: hint.offset,
+                problem,
+            };
+
+            let body = match bodies.get(entry.func_index as usize) {
+                Some(&Some(body)) => body,
+                _ => {
+                    for hint in &entry.hints {
+                        issues.push(issue(hint, BranchHintProblem::UnknownFunction));
+                    }
+                    continue;
+                }
+            };
+            let operators = body.operators_with_offsets(features)?;
+
+            for hint in &entry.hints {
+                match operators.binary_search_by_key(&hint.offset, |&(offset, _)| offset) {
+                    Ok(position) => match operators[position].1 {
+                        Operator::BrIf(_) | Operator::If(_) => {}
+                        ref operator => issues
+                            .push(issue(hint, BranchHintProblem::NotABranch(operator.clone()))),
+                    },
+                    Err(_) => issues.push(issue(hint, BranchHintProblem::NotAnInstruction)),
+                }
+            }
+        }
+
+        Ok(issues)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FunctionBranchHints {
+    pub func_index: u32,
+    pub hints: Vec<BranchHint>,
+}
+
+impl FunctionBranchHints {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<FunctionBranchHints, Error> {
+        let func_index = read_index(reader)?;
+        let hints = read_vec(reader, BranchHint::from_reader)?;
+        Ok(FunctionBranchHints { func_index, hints })
+    }
+}
+
+/// A hint for the instruction at `offset` bytes from the start of the
+/// function body, where its local declarations begin.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BranchHint {
+    pub offset: u32,
+    pub likely: bool,
+}
+
+impl BranchHint {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<BranchHint, Error> {
+        let offset = read_index(reader)?;
+
+        let size = read_index(reader)?;
+        if size != 1 {
+            return Err(Error::other(format!("Invalid branch hint size {}", size)));
+        }
+
+        let likely = match reader.read_u8()? {
+            0 => false,
+            1 => true,
+            value => return Err(Error::other(format!("Invalid branch hint value {}", value))),
+        };
+
+        Ok(BranchHint { offset, likely })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BranchHintIssue {
+    pub func_index: u32,
+    pub offset: u32,
+    pub problem: BranchHintProblem,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum BranchHintProblem {
+    /// The function is imported or does not exist.
+    UnknownFunction,
+    /// The offset falls inside an instruction or outside the body.
+    NotAnInstruction,
+    NotABranch(Operator),
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::*;
+
+    #[test]
+    fn test_branch_hints() {
+        // (func (param i32) (local i64)
+        //   (block local.get 0 br_if 0 local.get 0 if nop end))
+        // after an imported function, hinted at two offsets.
+        let hinted_module = |first: u8, second: u8| {
+            module(&[
+                (TYPE, &[0x02, 0x60, 0x00, 0x00, 0x60, 0x01, 0x7f, 0x00]),
+                (
+                    IMPORT,
+                    &[0x01, 0x03, 0x65, 0x6e, 0x76, 0x01, 0x66, 0x00, 0x00],
+                ),
+                (FUNCTION, &[0x01, 0x01]),
+                (
+                    CUSTOM,
+                    &custom(
+                        "metadata.code.branch_hint",
+                        &[0x01, 0x01, 0x02, first, 0x01, 0x01, second, 0x01, 0x00],
+                    ),
+                ),
+                (
+                    CODE,
+                    &code(&[&[
+                        0x01, 0x01, 0x7e, 0x02, 0x40, 0x20, 0x00, 0x0d, 0x00, 0x20, 0x00, 0x04,
+                        0x40, 0x01, 0x0b, 0x0b, 0x0b,
+                    ]]),
+                ),
+            ])
+        };
+        let check = |module: &WasmModule| {
+            let section = module
+                .sections
+                .iter()
+                .filter_map(|section| match section.body {
+                    WasmSectionBody::BranchHint(ref section) => Some(section),
+                    _ => None,
+                })
+                .next()
+                .unwrap();
+            section.check(module, &Features::default()).unwrap()
+        };
+
+        let hinted = hinted_module(0x07, 0x0b);
+        assert_eq!(check(&hinted), vec![]);
+
+        assert_eq!(
+            check(&hinted_module(0x08, 0x09)),
+            vec![
+                BranchHintIssue {
+                    func_index: 1,
+                    offset: 8,
+                    problem: BranchHintProblem::NotAnInstruction,
+                },
+                BranchHintIssue {
+                    func_index: 1,
+                    offset: 9,
+                    problem: BranchHintProblem::NotABranch(Operator::LocalGet(0)),
+                },
+            ]
+        );
+    }
+}
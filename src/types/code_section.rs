@@ -1,5 +1,5 @@
 use leb128::ReadLeb128Ext;
-use std::io::{Error, Read};
+use std::io::{Cursor, Error, Read};
 
 use super::*;
 
@@ -30,6 +30,8 @@ impl CodeSection {
 #[derive(Debug, PartialEq, Serialize)]
 pub struct FunctionBody {
     pub body_size: u32,
+    /// Bytes taken by the local declarations that precede `code`.
+    pub locals_size: u32,
     pub local_count: u32,
     pub locals: Vec<LocalEntry>,
     pub code: Vec<u8>,
@@ -38,11 +40,12 @@ pub struct FunctionBody {
 impl FunctionBody {
     pub fn from_reader<T: Read>(reader: &mut T) -> Result<FunctionBody, Error> {
         let (mut body_size, _) = reader.leb128_unsigned()?;
+        let total_size = body_size;
         let (local_count, local_count_bytes) = reader.leb128_unsigned()?;
 
         body_size -= local_count_bytes as i64;
 
-        let mut locals = Vec::with_capacity(local_count as usize);
+        let mut locals = Vec::new();
 
         for _ in 0..local_count {
             let (local, bytes_read) = LocalEntry::from_reader(reader)?;
@@ -50,16 +53,55 @@ impl FunctionBody {
             body_size -= bytes_read as i64;
         }
 
-        let mut code = vec![0; body_size as usize];
-        reader.read_exact(&mut code)?;
+        if body_size < 0 {
+            return Err(Error::other("Function body locals overrun its size"));
+        }
+
+        let code = read_bytes(reader, body_size as u64)?;
 
         Ok(FunctionBody {
             body_size: body_size as u32,
+            locals_size: (total_size - body_size) as u32,
             local_count: local_count as u32,
             locals,
             code,
         })
     }
+
+    pub fn operators(&self) -> Result<Vec<Operator>, Error> {
+        self.operators_with_features(&Features::default())
+    }
+
+    pub fn operators_with_features(&self, features: &Features) -> Result<Vec<Operator>, Error> {
+        let mut reader = Cursor::new(&self.code);
+        let mut operators = Vec::new();
+
+        while (reader.position() as usize) < self.code.len() {
+            operators.push(Operator::from_reader_with_features(&mut reader, features)?);
+        }
+
+        Ok(operators)
+    }
+
+    /// Decodes the operators along with their offsets from the start of
+    /// the body, counting the local declarations.
+    pub fn operators_with_offsets(
+        &self,
+        features: &Features,
+    ) -> Result<Vec<(u32, Operator)>, Error> {
+        let mut reader = Cursor::new(&self.code);
+        let mut operators = Vec::new();
+
+        while (reader.position() as usize) < self.code.len() {
+            let offset = self.locals_size + reader.position() as u32;
+            operators.push((
+                offset,
+                Operator::from_reader_with_features(&mut reader, features)?,
+            ));
+        }
+
+        Ok(operators)
+    }
 }
 
 #[derive(Debug, PartialEq, Serialize)]
@@ -72,8 +114,12 @@ impl LocalEntry {
     pub fn from_reader<T: Read>(reader: &mut T) -> Result<(LocalEntry, usize), Error> {
         let mut read = 0;
         let (count, read1) = reader.leb128_unsigned()?;
-        let (num, read2) = reader.leb128_signed()?;
-        let t = ValueType::from_i64(num)?;
+        let mut recorder = Recorder {
+            inner: reader,
+            bytes: Vec::new(),
+        };
+        let t = ValueType::from_reader(&mut recorder)?;
+        let read2 = recorder.bytes.len();
 
         read += read1;
         read += read2;
@@ -1,5 +1,7 @@
 use std::io::{Error, Read};
 
+use super::read_bytes;
+
 #[derive(Debug, PartialEq, Serialize)]
 pub struct CustomSection {
     pub len: usize,
@@ -8,8 +10,7 @@ pub struct CustomSection {
 
 impl CustomSection {
     pub fn from_reader<T: Read>(reader: &mut T, len: usize) -> Result<CustomSection, Error> {
-        let mut data = vec![0; len];
-        reader.read_exact(&mut data)?;
+        let data = read_bytes(reader, len as u64)?;
         Ok(CustomSection { len, data })
     }
 }
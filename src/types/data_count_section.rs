@@ -0,0 +1,16 @@
+use leb128::ReadLeb128Ext;
+use std::io::{Error, Read};
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DataCountSection {
+    pub count: u32,
+}
+
+impl DataCountSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<DataCountSection, Error> {
+        let (count, _) = reader.leb128_unsigned()?;
+        Ok(DataCountSection {
+            count: count as u32,
+        })
+    }
+}
@@ -27,30 +27,131 @@ impl DataSection {
     }
 }
 
+#[derive(Debug, PartialEq, Serialize)]
+pub enum DataMode {
+    Active {
+        memory_index: u32,
+        offset: Expression,
+    },
+    Passive,
+}
+
+/// A data segment. `flags` is 0 for an active segment in memory 0, 1 for a
+/// passive segment and 2 for an active segment with an explicit memory index.
 #[derive(Debug, PartialEq, Serialize)]
 pub struct DataSegment {
-    pub index: u32,
-    pub offset: Expression,
+    pub flags: u32,
+    pub mode: DataMode,
     pub size: u32,
     pub data: Vec<u8>,
 }
 
 impl DataSegment {
     pub fn from_reader<T: Read>(reader: &mut T) -> Result<DataSegment, Error> {
-        let (index, _) = reader.leb128_unsigned()?;
+        let (flags, _) = reader.leb128_unsigned()?;
 
-        let offset = Expression::from_reader(reader)?;
+        let mode = match flags {
+            0 => DataMode::Active {
+                memory_index: 0,
+                offset: Expression::from_reader(reader)?,
+            },
+            1 => DataMode::Passive,
+            2 => {
+                let memory_index = read_index(reader)?;
+                let offset = Expression::from_reader(reader)?;
+                DataMode::Active {
+                    memory_index,
+                    offset,
+                }
+            }
+            _ => {
+                return Err(Error::other(format!(
+                    "Unknown data segment flags {}",
+                    flags
+                )))
+            }
+        };
 
         let (size, _) = reader.leb128_unsigned()?;
 
-        let mut data = vec![0; size as usize];
-        reader.read_exact(&mut data)?;
+        let data = read_bytes(reader, size as u64)?;
 
         Ok(DataSegment {
-            index: index as u32,
-            offset,
+            flags: flags as u32,
+            mode,
             size: size as u32,
             data,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::*;
+    use validate::validate;
+
+    fn bulk_memory_module(sections: &[(u8, &[u8])]) -> WasmModule {
+        let mut all: Vec<(u8, &[u8])> = vec![
+            (TYPE, &[0x01, 0x60, 0x00, 0x00]),
+            (FUNCTION, &[0x01, 0x00]),
+            (MEMORY, &[0x01, 0x00, 0x01]),
+        ];
+        all.extend(sections);
+        module(&all)
+    }
+
+    #[test]
+    fn test_data_segment_modes() {
+        // i32.const 0 i32.const 0 i32.const 1 memory.init 1 0 data.drop 1
+        let body = code(&[&[
+            0x00, 0x41, 0x00, 0x41, 0x00, 0x41, 0x01, 0xfc, 0x08, 0x01, 0x00, 0xfc, 0x09, 0x01,
+            0x0b,
+        ]]);
+        #[rustfmt::skip]
+        let data = [
+            0x03,
+            0x00, 0x41, 0x00, 0x0b, 0x02, 0x68, 0x69, // active in memory 0
+            0x01, 0x03, 0x61, 0x62, 0x63, // passive
+            0x02, 0x00, 0x41, 0x08, 0x0b, 0x01, 0x78, // active with a memory index
+        ];
+        let module = bulk_memory_module(&[(DATA_COUNT, &[0x03]), (CODE, &body), (DATA, &data)]);
+
+        match module.sections[3].body {
+            WasmSectionBody::DataCount(ref section) => assert_eq!(section.count, 3),
+            ref body => panic!("Expected a data count section, got {:?}", body),
+        }
+
+        let segments = match module.sections[5].body {
+            WasmSectionBody::Data(ref section) => &section.entries,
+            ref body => panic!("Expected a data section, got {:?}", body),
+        };
+        let modes: Vec<_> = segments
+            .iter()
+            .map(|segment| match segment.mode {
+                DataMode::Active {
+                    memory_index,
+                    ref offset,
+                } => Some((memory_index, offset.bytes().to_vec())),
+                DataMode::Passive => None,
+            })
+            .collect();
+
+        assert_eq!(
+            modes,
+            vec![
+                Some((0, vec![0x41, 0x00, 0x0b])),
+                None,
+                Some((0, vec![0x41, 0x08, 0x0b])),
+            ]
+        );
+        assert_eq!(segments[1].data, b"abc");
+        assert_eq!(segments[2].flags, 2);
+        assert!(validate(&module).is_ok());
+
+        // memory.init and data.drop need the data count section.
+        let module = bulk_memory_module(&[(CODE, &body), (DATA, &data)]);
+        assert!(validate(&module).is_err());
+    }
+}
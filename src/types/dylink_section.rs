@@ -0,0 +1,195 @@
+use byteorder::ReadBytesExt;
+use leb128::ReadLeb128Ext;
+use std::io::{Cursor, Error, Read};
+
+use super::linking_section::{WASM_SYM_BINDING_WEAK, WASM_SYM_TLS};
+use super::*;
+
+/// The `dylink.0` custom section of a dynamic-linking side module. The
+/// legacy `dylink` section decodes into the same structure, without export,
+/// import or runtime path information.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DylinkSection {
+    pub mem_info: DylinkMemInfo,
+    pub needed: Vec<String>,
+    pub exports: Vec<DylinkExport>,
+    pub imports: Vec<DylinkImport>,
+    pub runtime_paths: Vec<String>,
+}
+
+impl DylinkSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<DylinkSection, Error> {
+        let mut section = DylinkSection {
+            mem_info: DylinkMemInfo::default(),
+            needed: Vec::new(),
+            exports: Vec::new(),
+            imports: Vec::new(),
+            runtime_paths: Vec::new(),
+        };
+
+        while let Ok(code) = reader.read_u8() {
+            let (payload_len, _) = reader.leb128_unsigned()?;
+            let payload = read_bytes(reader, payload_len as u64)?;
+
+            let mut payload = Cursor::new(payload);
+
+            match code {
+                1 => section.mem_info = DylinkMemInfo::from_reader(&mut payload)?,
+                2 => section.needed = read_vec(&mut payload, read_name)?,
+                3 => section.exports = read_vec(&mut payload, DylinkExport::from_reader)?,
+                4 => section.imports = read_vec(&mut payload, DylinkImport::from_reader)?,
+                5 => section.runtime_paths = read_vec(&mut payload, read_name)?,
+                _ => {}
+            }
+        }
+
+        Ok(section)
+    }
+
+    pub fn from_reader_legacy<T: Read>(reader: &mut T) -> Result<DylinkSection, Error> {
+        let mem_info = DylinkMemInfo::from_reader(reader)?;
+        let needed = read_vec(reader, read_name)?;
+
+        Ok(DylinkSection {
+            mem_info,
+            needed,
+            exports: Vec::new(),
+            imports: Vec::new(),
+            runtime_paths: Vec::new(),
+        })
+    }
+}
+
+/// Memory and table requirements of a side module. Alignments are log2.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct DylinkMemInfo {
+    pub memory_size: u32,
+    pub memory_alignment: u32,
+    pub table_size: u32,
+    pub table_alignment: u32,
+}
+
+impl DylinkMemInfo {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<DylinkMemInfo, Error> {
+        let (memory_size, _) = reader.leb128_unsigned()?;
+        let (memory_alignment, _) = reader.leb128_unsigned()?;
+        let (table_size, _) = reader.leb128_unsigned()?;
+        let (table_alignment, _) = reader.leb128_unsigned()?;
+
+        Ok(DylinkMemInfo {
+            memory_size: memory_size as u32,
+            memory_alignment: memory_alignment as u32,
+            table_size: table_size as u32,
+            table_alignment: table_alignment as u32,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DylinkExport {
+    pub name: String,
+    pub flags: u32,
+}
+
+impl DylinkExport {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<DylinkExport, Error> {
+        let name = read_name(reader)?;
+        let (flags, _) = reader.leb128_unsigned()?;
+
+        Ok(DylinkExport {
+            name,
+            flags: flags as u32,
+        })
+    }
+
+    pub fn is_tls(&self) -> bool {
+        self.flags & WASM_SYM_TLS != 0
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DylinkImport {
+    pub module_name: String,
+    pub field_name: String,
+    pub flags: u32,
+}
+
+impl DylinkImport {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<DylinkImport, Error> {
+        let module_name = read_name(reader)?;
+        let field_name = read_name(reader)?;
+        let (flags, _) = reader.leb128_unsigned()?;
+
+        Ok(DylinkImport {
+            module_name,
+            field_name,
+            flags: flags as u32,
+        })
+    }
+
+    pub fn is_weak(&self) -> bool {
+        self.flags & WASM_SYM_BINDING_WEAK != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::*;
+
+    fn dylink(module: &WasmModule) -> &DylinkSection {
+        match module.sections[0].body {
+            WasmSectionBody::Dylink(ref dylink) => dylink,
+            ref body => panic!("Expected a dylink section, got {:?}", body),
+        }
+    }
+
+    #[test]
+    fn test_dylink_0_section() {
+        let payload = [
+            0x01, 0x05, 0x80, 0x02, 0x03, 0x02, 0x00, // mem info
+            0x02, 0x08, 0x01, 0x06, 0x6c, 0x69, 0x62, 0x2e, 0x73, 0x6f, // needed
+            0x03, 0x05, 0x01, 0x01, 0x74, 0x80, 0x02, // exports
+            0x04, 0x06, 0x01, 0x01, 0x65, 0x01, 0x77, 0x01, // imports
+            0x05, 0x03, 0x01, 0x01, 0x2e, // runtime paths
+            0x09, 0x01, 0x00, // unknown subsection
+        ];
+        let module = module(&[(CUSTOM, &custom("dylink.0", &payload))]);
+        let section = dylink(&module);
+
+        assert_eq!(
+            section.mem_info,
+            DylinkMemInfo {
+                memory_size: 256,
+                memory_alignment: 3,
+                table_size: 2,
+                table_alignment: 0,
+            }
+        );
+        assert_eq!(section.needed, vec!["lib.so"]);
+        assert_eq!(section.exports[0].name, "t");
+        assert!(section.exports[0].is_tls());
+        assert_eq!(
+            (
+                &*section.imports[0].module_name,
+                &*section.imports[0].field_name
+            ),
+            ("e", "w")
+        );
+        assert!(section.imports[0].is_weak());
+        assert_eq!(section.runtime_paths, vec!["."]);
+    }
+
+    #[test]
+    fn test_legacy_dylink_section() {
+        let payload = [0x10, 0x02, 0x01, 0x00, 0x01, 0x04, 0x6c, 0x69, 0x62, 0x63];
+        let module = module(&[(CUSTOM, &custom("dylink", &payload))]);
+        let section = dylink(&module);
+
+        assert_eq!(section.mem_info.memory_size, 16);
+        assert_eq!(section.mem_info.table_size, 1);
+        assert_eq!(section.needed, vec!["libc"]);
+        assert!(section.exports.is_empty() && section.imports.is_empty());
+    }
+}
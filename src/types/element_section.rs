@@ -1,3 +1,4 @@
+use byteorder::ReadBytesExt;
 use leb128::ReadLeb128Ext;
 use std::io::{Error, Read};
 
@@ -27,32 +28,176 @@ impl ElementSection {
     }
 }
 
+#[derive(Debug, PartialEq, Serialize)]
+pub enum ElementMode {
+    Active {
+        table_index: u32,
+        offset: Expression,
+    },
+    Passive,
+    Declarative,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum ElementItems {
+    Functions(Vec<u32>),
+    Expressions(Vec<Expression>),
+}
+
+/// An element segment in any of the eight encodings selected by `flags`.
+/// Bit 0 marks a passive or declarative segment, bit 1 an explicit table
+/// index (or, for non-active segments, a declarative one) and bit 2 element
+/// expressions instead of function indices.
 #[derive(Debug, PartialEq, Serialize)]
 pub struct ElementSegment {
-    pub index: u32,
-    pub offset: Expression,
+    pub flags: u32,
+    pub mode: ElementMode,
+    pub element_type: RefType,
     pub num_elem: u32,
-    pub elems: Vec<u32>,
+    pub items: ElementItems,
 }
 
 impl ElementSegment {
     pub fn from_reader<T: Read>(reader: &mut T) -> Result<ElementSegment, Error> {
-        let (index, _) = reader.leb128_unsigned()?;
-        let offset = Expression::from_reader(reader)?;
-        let (num_elem, _) = reader.leb128_unsigned()?;
+        let (flags, _) = reader.leb128_unsigned()?;
 
-        let mut elems = Vec::with_capacity(num_elem as usize);
-
-        for _ in 0..num_elem {
-            let (index, _) = reader.leb128_unsigned()?;
-            elems.push(index as u32);
+        if flags > 7 {
+            return Err(Error::other(format!(
+                "Unknown element segment flags {}",
+                flags
+            )));
         }
 
+        let passive = flags & 0b001 != 0;
+        let explicit = flags & 0b010 != 0;
+        let expressions = flags & 0b100 != 0;
+
+        let mode = if passive {
+            if explicit {
+                ElementMode::Declarative
+            } else {
+                ElementMode::Passive
+            }
+        } else {
+            let table_index = if explicit { read_index(reader)? } else { 0 };
+            let offset = Expression::from_reader(reader)?;
+            ElementMode::Active {
+                table_index,
+                offset,
+            }
+        };
+
+        let element_type = if flags & 0b011 == 0 {
+            RefType::FUNCREF
+        } else if expressions {
+            RefType::from_reader(reader)?
+        } else {
+            match reader.read_u8()? {
+                0x00 => RefType::FUNCREF,
+                kind => return Err(Error::other(format!("Unknown element kind 0x{:02x}", kind))),
+            }
+        };
+
+        let (num_elem, _) = reader.leb128_unsigned()?;
+
+        let items = if expressions {
+            let mut exprs = Vec::new();
+            for _ in 0..num_elem {
+                exprs.push(Expression::from_reader(reader)?);
+            }
+            ElementItems::Expressions(exprs)
+        } else {
+            let mut indices = Vec::new();
+            for _ in 0..num_elem {
+                indices.push(read_index(reader)?);
+            }
+            ElementItems::Functions(indices)
+        };
+
         Ok(ElementSegment {
-            index: index as u32,
-            offset,
+            flags: flags as u32,
+            mode,
+            element_type,
             num_elem: num_elem as u32,
-            elems,
+            items,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::io::Cursor;
+
+    fn segment(bytes: &[u8]) -> ElementSegment {
+        ElementSegment::from_reader(&mut Cursor::new(bytes)).unwrap()
+    }
+
+    fn offset(segment: &ElementSegment) -> Option<(u32, &[u8])> {
+        match segment.mode {
+            ElementMode::Active {
+                table_index,
+                ref offset,
+            } => Some((table_index, offset.bytes())),
+            _ => None,
+        }
+    }
+
+    fn expressions(segment: &ElementSegment) -> Vec<&[u8]> {
+        match segment.items {
+            ElementItems::Expressions(ref exprs) => exprs.iter().map(|expr| expr.bytes()).collect(),
+            ElementItems::Functions(_) => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_element_segment_flags() {
+        let offset_expr: &[u8] = &[0x41, 0x00, 0x0b];
+        let ref_func: &[u8] = &[0xd2, 0x00, 0x0b];
+
+        // 0: active in table 0, function indices
+        let active = segment(&[0x00, 0x41, 0x00, 0x0b, 0x02, 0x00, 0x01]);
+        assert_eq!(offset(&active), Some((0, offset_expr)));
+        assert_eq!(active.items, ElementItems::Functions(vec![0, 1]));
+        assert_eq!(active.num_elem, 2);
+
+        // 1: passive, element kind
+        let passive = segment(&[0x01, 0x00, 0x01, 0x00]);
+        assert_eq!(passive.mode, ElementMode::Passive);
+        assert_eq!(passive.items, ElementItems::Functions(vec![0]));
+
+        // 2: active with a table index, element kind
+        let explicit = segment(&[0x02, 0x01, 0x41, 0x00, 0x0b, 0x00, 0x01, 0x00]);
+        assert_eq!(offset(&explicit), Some((1, offset_expr)));
+
+        // 3: declarative, element kind
+        let declarative = segment(&[0x03, 0x00, 0x01, 0x00]);
+        assert_eq!(declarative.mode, ElementMode::Declarative);
+        assert_eq!(declarative.element_type, RefType::FUNCREF);
+
+        // 4: active in table 0, expressions
+        let active = segment(&[0x04, 0x41, 0x00, 0x0b, 0x01, 0xd2, 0x00, 0x0b]);
+        assert_eq!(offset(&active), Some((0, offset_expr)));
+        assert_eq!(expressions(&active), vec![ref_func]);
+        assert_eq!(active.element_type, RefType::FUNCREF);
+
+        // 5: passive, reference type and expressions
+        let passive = segment(&[0x05, 0x70, 0x01, 0xd0, 0x70, 0x0b]);
+        assert_eq!(passive.mode, ElementMode::Passive);
+        assert_eq!(expressions(&passive), vec![&[0xd0, 0x70, 0x0b][..]]);
+
+        // 6: active with a table index, reference type and expressions
+        let explicit = segment(&[0x06, 0x01, 0x41, 0x00, 0x0b, 0x6f, 0x01, 0xd0, 0x6f, 0x0b]);
+        assert_eq!(offset(&explicit), Some((1, offset_expr)));
+        assert_eq!(expressions(&explicit), vec![&[0xd0, 0x6f, 0x0b][..]]);
+        assert_eq!(explicit.element_type, RefType::EXTERNREF);
+
+        // 7: declarative, reference type and expressions
+        let declarative = segment(&[0x07, 0x70, 0x01, 0xd2, 0x00, 0x0b]);
+        assert_eq!(declarative.mode, ElementMode::Declarative);
+        assert_eq!(expressions(&declarative), vec![ref_func]);
+
+        assert!(ElementSegment::from_reader(&mut Cursor::new(&[0x08, 0x00, 0x00])).is_err());
+    }
+}
@@ -2,6 +2,8 @@ use byteorder::ReadBytesExt;
 use leb128::ReadLeb128Ext;
 use std::io::{Error, Read};
 
+use super::read_bytes;
+
 #[derive(Debug, PartialEq, Serialize)]
 pub struct ExportSection {
     pub count: u32,
@@ -24,24 +26,32 @@ impl ExportSection {
             entries,
         })
     }
+
+    /// Finds an export by name. Export names are unique within a module.
+    pub fn get(&self, name: &str) -> Option<&ExportEntry> {
+        self.entries.iter().find(|entry| entry.field_name == name)
+    }
+
+    pub fn of_kind(&self, kind: ExportKind) -> impl Iterator<Item = &ExportEntry> {
+        self.entries.iter().filter(move |entry| entry.kind == kind)
+    }
 }
 
 #[derive(Debug, PartialEq, Serialize)]
 pub struct ExportEntry {
     pub field_name_len: u32,
     pub field_name: String,
-    pub kind: u8,
+    pub kind: ExportKind,
     pub index: u32,
 }
 
 impl ExportEntry {
     pub fn from_reader<T: Read>(reader: &mut T) -> Result<ExportEntry, Error> {
         let (field_name_len, _) = reader.leb128_unsigned()?;
-        let mut buff = vec![0; field_name_len as usize];
-        reader.read(&mut buff)?;
+        let buff = read_bytes(reader, field_name_len as u64)?;
         let field_name = String::from_utf8_lossy(&buff).into_owned();
 
-        let kind = reader.read_u8()?;
+        let kind = ExportKind::from_u8(reader.read_u8()?)?;
         let (index, _) = reader.leb128_unsigned()?;
 
         Ok(ExportEntry {
@@ -52,3 +62,58 @@ impl ExportEntry {
         })
     }
 }
+
+/// The index space an export refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum ExportKind {
+    Func,
+    Table,
+    Memory,
+    Global,
+    Tag,
+}
+
+impl ExportKind {
+    pub fn from_u8(num: u8) -> Result<ExportKind, Error> {
+        match num {
+            0 => Ok(ExportKind::Func),
+            1 => Ok(ExportKind::Table),
+            2 => Ok(ExportKind::Memory),
+            3 => Ok(ExportKind::Global),
+            4 => Ok(ExportKind::Tag),
+            _ => Err(Error::other(format!("Unknown export kind {}", num))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_export_lookup() {
+        // (export "f" (func 0)) (export "mem" (memory 0)) (export "g" (func 1))
+        let bytes = [
+            0x03, 0x01, 0x66, 0x00, 0x00, 0x03, 0x6d, 0x65, 0x6d, 0x02, 0x00, 0x01, 0x67, 0x00,
+            0x01,
+        ];
+        let section = ExportSection::from_reader(&mut Cursor::new(&bytes[..])).unwrap();
+
+        assert_eq!(
+            section.get("mem").map(|entry| entry.kind),
+            Some(ExportKind::Memory)
+        );
+        assert!(section.get("missing").is_none());
+
+        let funcs: Vec<u32> = section
+            .of_kind(ExportKind::Func)
+            .map(|entry| entry.index)
+            .collect();
+        assert_eq!(funcs, vec![0, 1]);
+
+        let unknown = [0x01, 0x01, 0x66, 0x05, 0x00];
+        assert!(ExportSection::from_reader(&mut Cursor::new(&unknown[..])).is_err());
+    }
+}
@@ -0,0 +1,36 @@
+use std::io::{Error, Read};
+
+use super::*;
+
+/// The `external_debug_info` custom section, pointing at a separate file
+/// that holds the module's DWARF sections.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ExternalDebugInfoSection {
+    pub url: String,
+}
+
+impl ExternalDebugInfoSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<ExternalDebugInfoSection, Error> {
+        let url = read_name(reader)?;
+        Ok(ExternalDebugInfoSection { url })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::*;
+
+    #[test]
+    fn test_external_debug_info_section() {
+        let module = module(&[(CUSTOM, &custom("external_debug_info", b"\x0adebug.wasm"))]);
+
+        match module.sections[0].body {
+            WasmSectionBody::ExternalDebugInfo(ref section) => {
+                assert_eq!(section.url, "debug.wasm")
+            }
+            ref body => panic!("Expected an external_debug_info section, got {:?}", body),
+        }
+    }
+}
@@ -29,8 +29,8 @@ impl GlobalSection {
 
 #[derive(Debug, PartialEq, Serialize)]
 pub struct GlobalEntry {
-    t: GlobalType,
-    init: Expression,
+    pub t: GlobalType,
+    pub init: Expression,
 }
 
 impl GlobalEntry {
@@ -50,8 +50,7 @@ pub struct GlobalType {
 
 impl GlobalType {
     pub fn from_reader<T: Read>(reader: &mut T) -> Result<GlobalType, Error> {
-        let (content_type_num, _) = reader.leb128_signed()?;
-        let content_type = ValueType::from_i64(content_type_num)?;
+        let content_type = ValueType::from_reader(reader)?;
         let mutability = reader.read_u8()?;
 
         Ok(GlobalType {
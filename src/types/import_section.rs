@@ -40,13 +40,11 @@ impl ImportEntry {
     pub fn from_reader<T: Read>(reader: &mut T) -> Result<ImportEntry, Error> {
         let (module_name_len, _) = reader.leb128_unsigned()?;
 
-        let mut buff = vec![0; module_name_len as usize];
-        reader.read(&mut buff)?;
+        let buff = read_bytes(reader, module_name_len as u64)?;
         let module_name = String::from_utf8_lossy(&buff).into_owned();
 
         let (field_name_len, _) = reader.leb128_unsigned()?;
-        let mut buff = vec![0; field_name_len as usize];
-        reader.read(&mut buff)?;
+        let buff = read_bytes(reader, field_name_len as u64)?;
         let field_name = String::from_utf8_lossy(&buff).into_owned();
 
         let kind = ExternalKind::from_reader(reader)?;
@@ -0,0 +1,389 @@
+//! Views over the module's index spaces. Each space counts imports first
+//! and definitions after them, in section order.
+
+use super::code_section::FunctionBody;
+use super::global_section::GlobalType;
+use super::import_section::ImportEntry;
+use super::name_section::Naming;
+use super::table_section::TableType;
+use super::*;
+use std::collections::HashMap;
+
+#[derive(Debug)]
+pub struct Function<'a> {
+    pub index: u32,
+    /// The import providing the function, `None` if the module defines it.
+    pub import: Option<&'a ImportEntry>,
+    pub type_index: u32,
+    pub signature: Option<&'a FunctionType>,
+    pub body: Option<&'a FunctionBody>,
+    pub export_names: Vec<&'a str>,
+    pub debug_name: Option<&'a str>,
+}
+
+#[derive(Debug)]
+pub struct Table<'a> {
+    pub index: u32,
+    pub import: Option<&'a ImportEntry>,
+    pub ty: &'a TableType,
+    pub init: Option<&'a Expression>,
+    pub export_names: Vec<&'a str>,
+    pub debug_name: Option<&'a str>,
+}
+
+#[derive(Debug)]
+pub struct Memory<'a> {
+    pub index: u32,
+    pub import: Option<&'a ImportEntry>,
+    pub ty: &'a MemoryType,
+    pub export_names: Vec<&'a str>,
+    pub debug_name: Option<&'a str>,
+}
+
+#[derive(Debug)]
+pub struct Global<'a> {
+    pub index: u32,
+    pub import: Option<&'a ImportEntry>,
+    pub ty: &'a GlobalType,
+    pub init: Option<&'a Expression>,
+    pub export_names: Vec<&'a str>,
+    pub debug_name: Option<&'a str>,
+}
+
+impl<'a> Function<'a> {
+    pub fn is_imported(&self) -> bool {
+        self.import.is_some()
+    }
+}
+
+impl<'a> Table<'a> {
+    pub fn is_imported(&self) -> bool {
+        self.import.is_some()
+    }
+}
+
+impl<'a> Memory<'a> {
+    pub fn is_imported(&self) -> bool {
+        self.import.is_some()
+    }
+}
+
+impl<'a> Global<'a> {
+    pub fn is_imported(&self) -> bool {
+        self.import.is_some()
+    }
+}
+
+impl WasmModule {
+    pub fn functions(&self) -> impl Iterator<Item = Function<'_>> {
+        let types: Vec<&SubType> = self
+            .sections
+            .iter()
+            .filter_map(|section| match section.body {
+                WasmSectionBody::Types(ref types) => Some(types),
+                _ => None,
+            })
+            .flat_map(|types| types.types())
+            .collect();
+
+        let mut entries = Vec::new();
+        for import in self.imports() {
+            if let ExternalKind::Function(type_index) = import.kind {
+                entries.push((Some(import), type_index));
+            }
+        }
+        for section in &self.sections {
+            if let WasmSectionBody::Function(ref functions) = section.body {
+                entries.extend(functions.types.iter().map(|&type_index| (None, type_index)));
+            }
+        }
+
+        let bodies: Vec<&FunctionBody> = self
+            .sections
+            .iter()
+            .filter_map(|section| match section.body {
+                WasmSectionBody::Code(ref code) => Some(code),
+                _ => None,
+            })
+            .flat_map(|code| code.bodies.iter())
+            .collect();
+        let imported = entries.iter().filter(|entry| entry.0.is_some()).count();
+
+        let mut export_names = self.export_names(ExportKind::Func);
+        let debug_names = name_map(self.names().map(|names| &names.function_names[..]));
+        entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, (import, type_index))| Function {
+                index: index as u32,
+                import,
+                type_index,
+                signature: types
+                    .get(type_index as usize)
+                    .and_then(|sub_type| sub_type.func_type()),
+                body: match import {
+                    Some(_) => None,
+                    None => bodies.get(index - imported).cloned(),
+                },
+                export_names: export_names.remove(&(index as u32)).unwrap_or_default(),
+                debug_name: debug_names.get(&(index as u32)).cloned(),
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    pub fn tables(&self) -> impl Iterator<Item = Table<'_>> {
+        let mut entries = Vec::new();
+        for import in self.imports() {
+            if let ExternalKind::Table(ref ty) = import.kind {
+                entries.push((Some(import), ty, None));
+            }
+        }
+        for section in &self.sections {
+            if let WasmSectionBody::Table(ref tables) = section.body {
+                entries.extend(
+                    tables
+                        .entries
+                        .iter()
+                        .map(|table| (None, &table.t, table.init.as_ref())),
+                );
+            }
+        }
+
+        let mut export_names = self.export_names(ExportKind::Table);
+        let debug_names = name_map(self.names().map(|names| &names.table_names[..]));
+        entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, (import, ty, init))| Table {
+                index: index as u32,
+                import,
+                ty,
+                init,
+                export_names: export_names.remove(&(index as u32)).unwrap_or_default(),
+                debug_name: debug_names.get(&(index as u32)).cloned(),
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    pub fn memories(&self) -> impl Iterator<Item = Memory<'_>> {
+        let mut entries = Vec::new();
+        for import in self.imports() {
+            if let ExternalKind::Memory(ref ty) = import.kind {
+                entries.push((Some(import), ty));
+            }
+        }
+        for section in &self.sections {
+            if let WasmSectionBody::Memory(ref memories) = section.body {
+                entries.extend(memories.entries.iter().map(|ty| (None, ty)));
+            }
+        }
+
+        let mut export_names = self.export_names(ExportKind::Memory);
+        let debug_names = name_map(self.names().map(|names| &names.memory_names[..]));
+        entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, (import, ty))| Memory {
+                index: index as u32,
+                import,
+                ty,
+                export_names: export_names.remove(&(index as u32)).unwrap_or_default(),
+                debug_name: debug_names.get(&(index as u32)).cloned(),
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    pub fn globals(&self) -> impl Iterator<Item = Global<'_>> {
+        let mut entries = Vec::new();
+        for import in self.imports() {
+            if let ExternalKind::Global(ref ty) = import.kind {
+                entries.push((Some(import), ty, None));
+            }
+        }
+        for section in &self.sections {
+            if let WasmSectionBody::Global(ref globals) = section.body {
+                entries.extend(
+                    globals
+                        .globals
+                        .iter()
+                        .map(|global| (None, &global.t, Some(&global.init))),
+                );
+            }
+        }
+
+        let mut export_names = self.export_names(ExportKind::Global);
+        let debug_names = name_map(self.names().map(|names| &names.global_names[..]));
+        entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, (import, ty, init))| Global {
+                index: index as u32,
+                import,
+                ty,
+                init,
+                export_names: export_names.remove(&(index as u32)).unwrap_or_default(),
+                debug_name: debug_names.get(&(index as u32)).cloned(),
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// The decoded `name` section, if the module has one.
+    pub fn names(&self) -> Option<&NameSection> {
+        self.sections.iter().find_map(|section| match section.body {
+            WasmSectionBody::Name(ref names) => Some(&**names),
+            _ => None,
+        })
+    }
+
+    pub fn imports(&self) -> impl Iterator<Item = &ImportEntry> {
+        self.sections
+            .iter()
+            .filter_map(|section| match section.body {
+                WasmSectionBody::Import(ref imports) => Some(imports),
+                _ => None,
+            })
+            .flat_map(|imports| imports.entries.iter())
+    }
+
+    /// The names each entry of `kind` is exported under, by index.
+    fn export_names(&self, kind: ExportKind) -> HashMap<u32, Vec<&str>> {
+        let mut names: HashMap<u32, Vec<&str>> = HashMap::new();
+        let exports = self
+            .sections
+            .iter()
+            .filter_map(|section| match section.body {
+                WasmSectionBody::Export(ref exports) => Some(exports),
+                _ => None,
+            })
+            .flat_map(|exports| exports.of_kind(kind));
+
+        for entry in exports {
+            names
+                .entry(entry.index)
+                .or_default()
+                .push(entry.field_name.as_str());
+        }
+
+        names
+    }
+}
+
+/// Indexes a name map once rather than scanning it for every entry. The
+/// first name given to an index wins.
+fn name_map(names: Option<&[Naming]>) -> HashMap<u32, &str> {
+    names
+        .unwrap_or_default()
+        .iter()
+        .rev()
+        .map(|naming| (naming.index, naming.name.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use testing::*;
+    use types::ValueType;
+
+    #[test]
+    fn test_function_index_space() {
+        // (import "e" "f" (func $f)) (func $g (export "g"))
+        let module = module(&[
+            (TYPE, &[0x01, 0x60, 0x00, 0x00]),
+            (IMPORT, &[0x01, 0x01, 0x65, 0x01, 0x66, 0x00, 0x00]),
+            (FUNCTION, &[0x01, 0x00]),
+            (EXPORT, &[0x01, 0x01, 0x67, 0x00, 0x01]),
+            (CODE, &code(&[&[0x00, 0x0b]])),
+            (
+                CUSTOM,
+                &custom(
+                    "name",
+                    &[0x01, 0x07, 0x02, 0x00, 0x01, 0x66, 0x01, 0x01, 0x67],
+                ),
+            ),
+        ]);
+        let functions: Vec<_> = module.functions().collect();
+
+        assert_eq!(functions.len(), 2);
+        assert!(functions[0].is_imported() && functions[0].body.is_none());
+        assert_eq!(functions[0].debug_name, Some("f"));
+        assert!(functions[0].export_names.is_empty());
+
+        assert!(!functions[1].is_imported() && functions[1].body.is_some());
+        assert_eq!(functions[1].debug_name, Some("g"));
+        assert_eq!(functions[1].export_names, vec!["g"]);
+        assert_eq!(functions[1].signature.map(|sig| sig.param_count), Some(0));
+    }
+    #[test]
+    fn test_table_index_space() {
+        // (import "e" "t" (table 1 funcref)) (table (export "u") 2 externref)
+        let module = module(&[
+            (
+                IMPORT,
+                &[0x01, 0x01, 0x65, 0x01, 0x74, 0x01, 0x70, 0x00, 0x01],
+            ),
+            (TABLE, &[0x01, 0x6f, 0x00, 0x02]),
+            (EXPORT, &[0x01, 0x01, 0x75, 0x01, 0x01]),
+        ]);
+        let tables: Vec<_> = module.tables().collect();
+
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].index, 0);
+        assert!(tables[0].is_imported());
+        assert_eq!(tables[0].ty.limits.initial, 1);
+        assert!(tables[0].export_names.is_empty());
+
+        assert_eq!(tables[1].index, 1);
+        assert!(!tables[1].is_imported());
+        assert_eq!(tables[1].ty.limits.initial, 2);
+        assert_eq!(tables[1].export_names, vec!["u"]);
+    }
+
+    #[test]
+    fn test_memory_index_space() {
+        // (import "e" "m" (memory 1)) (memory (export "n") 2)
+        let module = module(&[
+            (IMPORT, &[0x01, 0x01, 0x65, 0x01, 0x6d, 0x02, 0x00, 0x01]),
+            (MEMORY, &[0x01, 0x00, 0x02]),
+            (EXPORT, &[0x01, 0x01, 0x6e, 0x02, 0x01]),
+        ]);
+        let memories: Vec<_> = module.memories().collect();
+
+        assert_eq!(memories.len(), 2);
+        assert_eq!(memories[0].index, 0);
+        assert!(memories[0].is_imported());
+        assert_eq!(memories[0].ty.limits.initial, 1);
+        assert!(memories[0].export_names.is_empty());
+
+        assert_eq!(memories[1].index, 1);
+        assert!(!memories[1].is_imported());
+        assert_eq!(memories[1].ty.limits.initial, 2);
+        assert_eq!(memories[1].export_names, vec!["n"]);
+    }
+
+    #[test]
+    fn test_global_index_space() {
+        // (import "e" "g" (global i32)) (global (export "h") i64 (i64.const 0))
+        let module = module(&[
+            (IMPORT, &[0x01, 0x01, 0x65, 0x01, 0x67, 0x03, 0x7f, 0x00]),
+            (GLOBAL, &[0x01, 0x7e, 0x00, 0x42, 0x00, 0x0b]),
+            (EXPORT, &[0x01, 0x01, 0x68, 0x03, 0x01]),
+        ]);
+        let globals: Vec<_> = module.globals().collect();
+
+        assert_eq!(globals.len(), 2);
+        assert_eq!(globals[0].index, 0);
+        assert!(globals[0].is_imported() && globals[0].init.is_none());
+        assert_eq!(globals[0].ty.content_type, ValueType::Integer32);
+        assert!(globals[0].export_names.is_empty());
+
+        assert_eq!(globals[1].index, 1);
+        assert!(!globals[1].is_imported() && globals[1].init.is_some());
+        assert_eq!(globals[1].ty.content_type, ValueType::Integer64);
+        assert_eq!(globals[1].export_names, vec!["h"]);
+    }
+}
@@ -0,0 +1,398 @@
+use byteorder::ReadBytesExt;
+use leb128::ReadLeb128Ext;
+use std::io::{Cursor, Error, Read};
+
+use super::*;
+
+pub const WASM_SYM_BINDING_WEAK: u32 = 0x01;
+pub const WASM_SYM_BINDING_LOCAL: u32 = 0x02;
+pub const WASM_SYM_VISIBILITY_HIDDEN: u32 = 0x04;
+pub const WASM_SYM_UNDEFINED: u32 = 0x10;
+pub const WASM_SYM_EXPORTED: u32 = 0x20;
+pub const WASM_SYM_EXPLICIT_NAME: u32 = 0x40;
+pub const WASM_SYM_NO_STRIP: u32 = 0x80;
+pub const WASM_SYM_TLS: u32 = 0x100;
+pub const WASM_SYM_ABSOLUTE: u32 = 0x200;
+
+/// The `linking` custom section emitted into relocatable object files.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct LinkingSection {
+    pub version: u32,
+    pub subsections: Vec<LinkingSubsection>,
+}
+
+impl LinkingSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<LinkingSection, Error> {
+        let (version, _) = reader.leb128_unsigned()?;
+
+        if version != 2 {
+            return Err(Error::other(format!(
+                "Unsupported linking section version {}",
+                version
+            )));
+        }
+
+        let mut subsections = Vec::new();
+
+        while let Ok(code) = reader.read_u8() {
+            let (payload_len, _) = reader.leb128_unsigned()?;
+            let payload = read_bytes(reader, payload_len as u64)?;
+
+            let subsection = LinkingSubsection::from_payload(code, payload)?;
+            subsections.push(subsection);
+        }
+
+        Ok(LinkingSection {
+            version: version as u32,
+            subsections,
+        })
+    }
+
+    pub fn segments(&self) -> &[SegmentInfo] {
+        for subsection in &self.subsections {
+            if let LinkingSubsection::SegmentInfo(ref segments) = *subsection {
+                return segments;
+            }
+        }
+        &[]
+    }
+
+    pub fn init_funcs(&self) -> &[InitFunc] {
+        for subsection in &self.subsections {
+            if let LinkingSubsection::InitFuncs(ref funcs) = *subsection {
+                return funcs;
+            }
+        }
+        &[]
+    }
+
+    pub fn comdats(&self) -> &[Comdat] {
+        for subsection in &self.subsections {
+            if let LinkingSubsection::ComdatInfo(ref comdats) = *subsection {
+                return comdats;
+            }
+        }
+        &[]
+    }
+
+    pub fn symbols(&self) -> &[SymbolInfo] {
+        for subsection in &self.subsections {
+            if let LinkingSubsection::SymbolTable(ref symbols) = *subsection {
+                return symbols;
+            }
+        }
+        &[]
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum LinkingSubsection {
+    SegmentInfo(Vec<SegmentInfo>),
+    InitFuncs(Vec<InitFunc>),
+    ComdatInfo(Vec<Comdat>),
+    SymbolTable(Vec<SymbolInfo>),
+    Unknown { code: u8, data: Vec<u8> },
+}
+
+impl LinkingSubsection {
+    fn from_payload(code: u8, payload: Vec<u8>) -> Result<LinkingSubsection, Error> {
+        let mut reader = Cursor::new(&payload);
+
+        let subsection = match code {
+            5 => LinkingSubsection::SegmentInfo(read_vec(&mut reader, SegmentInfo::from_reader)?),
+            6 => LinkingSubsection::InitFuncs(read_vec(&mut reader, InitFunc::from_reader)?),
+            7 => LinkingSubsection::ComdatInfo(read_vec(&mut reader, Comdat::from_reader)?),
+            8 => LinkingSubsection::SymbolTable(read_vec(&mut reader, SymbolInfo::from_reader)?),
+            _ => LinkingSubsection::Unknown {
+                code,
+                data: payload,
+            },
+        };
+
+        Ok(subsection)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SegmentInfo {
+    pub name: String,
+    pub alignment: u32,
+    pub flags: u32,
+}
+
+impl SegmentInfo {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<SegmentInfo, Error> {
+        let name = read_name(reader)?;
+        let (alignment, _) = reader.leb128_unsigned()?;
+        let (flags, _) = reader.leb128_unsigned()?;
+
+        Ok(SegmentInfo {
+            name,
+            alignment: alignment as u32,
+            flags: flags as u32,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct InitFunc {
+    pub priority: u32,
+    pub symbol_index: u32,
+}
+
+impl InitFunc {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<InitFunc, Error> {
+        let (priority, _) = reader.leb128_unsigned()?;
+        let (symbol_index, _) = reader.leb128_unsigned()?;
+
+        Ok(InitFunc {
+            priority: priority as u32,
+            symbol_index: symbol_index as u32,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Comdat {
+    pub name: String,
+    pub flags: u32,
+    pub entries: Vec<ComdatSym>,
+}
+
+impl Comdat {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<Comdat, Error> {
+        let name = read_name(reader)?;
+        let (flags, _) = reader.leb128_unsigned()?;
+        let (count, _) = reader.leb128_unsigned()?;
+
+        let mut entries = Vec::new();
+
+        for _ in 0..count {
+            let kind = ComdatSymKind::from_u8(reader.read_u8()?)?;
+            let (index, _) = reader.leb128_unsigned()?;
+            entries.push(ComdatSym {
+                kind,
+                index: index as u32,
+            });
+        }
+
+        Ok(Comdat {
+            name,
+            flags: flags as u32,
+            entries,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ComdatSym {
+    pub kind: ComdatSymKind,
+    pub index: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum ComdatSymKind {
+    Data,
+    Function,
+    Global,
+    Tag,
+    Table,
+    Section,
+}
+
+impl ComdatSymKind {
+    pub fn from_u8(num: u8) -> Result<ComdatSymKind, Error> {
+        match num {
+            0 => Ok(ComdatSymKind::Data),
+            1 => Ok(ComdatSymKind::Function),
+            2 => Ok(ComdatSymKind::Global),
+            3 => Ok(ComdatSymKind::Tag),
+            4 => Ok(ComdatSymKind::Table),
+            5 => Ok(ComdatSymKind::Section),
+            _ => Err(Error::other("Unknown Comdat Symbol Kind")),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SymbolInfo {
+    pub flags: u32,
+    pub name: Option<String>,
+    pub kind: SymbolKind,
+}
+
+impl SymbolInfo {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<SymbolInfo, Error> {
+        let kind_code = reader.read_u8()?;
+        let (flags, _) = reader.leb128_unsigned()?;
+        let flags = flags as u32;
+        let undefined = flags & WASM_SYM_UNDEFINED != 0;
+        let explicit_name = flags & WASM_SYM_EXPLICIT_NAME != 0;
+
+        let (name, kind) = match kind_code {
+            0 | 2 | 4 | 5 => {
+                let (index, _) = reader.leb128_unsigned()?;
+                let index = index as u32;
+                let name = if !undefined || explicit_name {
+                    Some(read_name(reader)?)
+                } else {
+                    None
+                };
+                let kind = match kind_code {
+                    0 => SymbolKind::Function(index),
+                    2 => SymbolKind::Global(index),
+                    4 => SymbolKind::Tag(index),
+                    _ => SymbolKind::Table(index),
+                };
+                (name, kind)
+            }
+            1 => {
+                let name = read_name(reader)?;
+                let definition = if undefined {
+                    None
+                } else {
+                    let (index, _) = reader.leb128_unsigned()?;
+                    let (offset, _) = reader.leb128_unsigned()?;
+                    let (size, _) = reader.leb128_unsigned()?;
+                    Some(DataSymbol {
+                        index: index as u32,
+                        offset: offset as u32,
+                        size: size as u32,
+                    })
+                };
+                (Some(name), SymbolKind::Data(definition))
+            }
+            3 => {
+                let (section, _) = reader.leb128_unsigned()?;
+                (None, SymbolKind::Section(section as u32))
+            }
+            _ => return Err(Error::other("Unknown Symbol Kind")),
+        };
+
+        Ok(SymbolInfo { flags, name, kind })
+    }
+
+    pub fn is_undefined(&self) -> bool {
+        self.flags & WASM_SYM_UNDEFINED != 0
+    }
+
+    pub fn is_local(&self) -> bool {
+        self.flags & WASM_SYM_BINDING_LOCAL != 0
+    }
+
+    pub fn is_weak(&self) -> bool {
+        self.flags & WASM_SYM_BINDING_WEAK != 0
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.flags & WASM_SYM_VISIBILITY_HIDDEN != 0
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum SymbolKind {
+    Function(u32),
+    Data(Option<DataSymbol>),
+    Global(u32),
+    Section(u32),
+    Tag(u32),
+    Table(u32),
+}
+
+/// Location of a defined data symbol within its data segment.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DataSymbol {
+    pub index: u32,
+    pub offset: u32,
+    pub size: u32,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::*;
+
+    #[test]
+    fn test_linking_section() {
+        let subsection = |id: u8, payload: &[u8]| {
+            let mut bytes = vec![id];
+            bytes.extend(uleb(payload.len() as u64));
+            bytes.extend(payload);
+            bytes
+        };
+
+        let mut payload = vec![0x02];
+        payload.extend(subsection(
+            8,
+            &[
+                0x04, // symbols
+                0x00, 0x00, 0x00, 0x01, 0x66, // function 0 "f"
+                0x01, 0x00, 0x01, 0x64, 0x00, 0x04, 0x08, // data "d" at segment 0 + 4
+                0x00, 0x10, 0x01, // undefined function 1
+                0x03, 0x02, 0x05, // local section symbol for section 5
+            ],
+        ));
+        payload.extend(subsection(
+            5,
+            &[0x01, 0x05, 0x2e, 0x64, 0x61, 0x74, 0x61, 0x02, 0x00],
+        ));
+        payload.extend(subsection(6, &[0x01, 0xff, 0xff, 0x03, 0x00]));
+        payload.extend(subsection(
+            7,
+            &[0x01, 0x01, 0x63, 0x00, 0x02, 0x01, 0x00, 0x00, 0x00],
+        ));
+
+        let module = module(&[(CUSTOM, &custom("linking", &payload))]);
+        let linking = match module.sections[0].body {
+            WasmSectionBody::Linking(ref linking) => linking,
+            ref body => panic!("Expected a linking section, got {:?}", body),
+        };
+
+        let symbols = linking.symbols();
+        assert_eq!(symbols.len(), 4);
+        assert_eq!(symbols[0].name, Some("f".to_string()));
+        assert_eq!(symbols[0].kind, SymbolKind::Function(0));
+        assert_eq!(
+            symbols[1].kind,
+            SymbolKind::Data(Some(DataSymbol {
+                index: 0,
+                offset: 4,
+                size: 8,
+            }))
+        );
+        assert!(symbols[2].is_undefined() && symbols[2].name.is_none());
+        assert!(symbols[3].is_local());
+        assert_eq!(symbols[3].kind, SymbolKind::Section(5));
+
+        assert_eq!(
+            linking.segments(),
+            &[SegmentInfo {
+                name: ".data".to_string(),
+                alignment: 2,
+                flags: 0,
+            }]
+        );
+        assert_eq!(
+            linking.init_funcs(),
+            &[InitFunc {
+                priority: 65535,
+                symbol_index: 0,
+            }]
+        );
+        assert_eq!(linking.comdats()[0].name, "c");
+        assert_eq!(
+            linking.comdats()[0].entries,
+            vec![
+                ComdatSym {
+                    kind: ComdatSymKind::Function,
+                    index: 0,
+                },
+                ComdatSym {
+                    kind: ComdatSymKind::Data,
+                    index: 0,
+                },
+            ]
+        );
+    }
+}
@@ -16,8 +16,8 @@ impl MemorySection {
         let mut entries = Vec::new();
 
         for _ in 0..count {
-            let limits = ResizableLimits::from_reader(reader)?;
-            entries.push(MemoryType { limits });
+            let entry = MemoryType::from_reader(reader)?;
+            entries.push(entry);
         }
 
         Ok(MemorySection {
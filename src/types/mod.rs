@@ -1,40 +1,73 @@
+pub mod branch_hint_section;
 pub mod code_section;
 pub mod custom_section;
+pub mod data_count_section;
 pub mod data_section;
+pub mod dylink_section;
 pub mod element_section;
 pub mod export_section;
+pub mod external_debug_info_section;
 pub mod function_section;
 pub mod global_section;
 pub mod import_section;
+pub mod index_space;
+pub mod linking_section;
 pub mod memory_section;
+pub mod name_section;
+pub mod operators;
+pub mod reloc_section;
+pub mod source_mapping_url_section;
 pub mod start_section;
 pub mod table_section;
+pub mod tag_section;
 pub mod type_section;
 
+pub use branch_hint_section::BranchHintSection;
 pub use code_section::CodeSection;
 pub use custom_section::CustomSection;
+pub use data_count_section::DataCountSection;
 pub use data_section::DataSection;
+pub use dylink_section::DylinkSection;
 pub use element_section::ElementSection;
-pub use export_section::ExportSection;
+pub use export_section::{ExportKind, ExportSection};
+pub use external_debug_info_section::ExternalDebugInfoSection;
 pub use function_section::FunctionSection;
 pub use global_section::GlobalSection;
 pub use import_section::ImportSection;
+pub use index_space::{Function, Global, Memory, Table};
+pub use linking_section::LinkingSection;
 pub use memory_section::MemorySection;
+pub use name_section::NameSection;
+pub use operators::{BlockType, MemArg, Operator, TryTableCatch};
+pub use reloc_section::RelocSection;
+pub use source_mapping_url_section::SourceMappingUrlSection;
 pub use start_section::StartSection;
 pub use table_section::TableSection;
-pub use type_section::TypeSection;
+pub use tag_section::TagSection;
+pub use type_section::{
+    ArrayType, CompositeType, FieldType, RecGroup, StorageType, StructType, SubType, TypeSection,
+};
 
 use byteorder::ReadBytesExt;
+use component::wit::ComponentTypeMetadata;
+use features::Features;
 use leb128::ReadLeb128Ext;
-use std::io::{Error, ErrorKind, Read};
+use std::io::{Cursor, Error, ErrorKind, Read};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, PartialEq, Serialize)]
 pub struct WasmModule {
     pub version: u32,
     pub sections: Vec<WasmSection>,
 }
 
-#[derive(Debug, Serialize)]
+impl WasmModule {
+    /// Returns the section a `reloc.*` section applies its relocations to.
+    pub fn reloc_target(&self, reloc: &RelocSection) -> Option<&WasmSection> {
+        self.sections.get(reloc.section_index as usize)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
 pub struct WasmSection {
     pub payload_len: u32,
     pub name: Option<String>,
@@ -55,30 +88,161 @@ pub enum WasmSectionBody {
     Code(Box<CodeSection>),
     Data(Box<DataSection>),
     Element(Box<ElementSection>),
+    DataCount(Box<DataCountSection>),
+    Tag(Box<TagSection>),
+    Linking(Box<LinkingSection>),
+    Reloc(Box<RelocSection>),
+    Dylink(Box<DylinkSection>),
+    SourceMappingUrl(Box<SourceMappingUrlSection>),
+    ExternalDebugInfo(Box<ExternalDebugInfoSection>),
+    ComponentType(Box<ComponentTypeMetadata>),
+    BranchHint(Box<BranchHintSection>),
+    Name(Box<NameSection>),
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum ValueType {
     Integer32,
     Integer64,
     Float32,
     Float64,
-    Anyfunc,
+    V128,
+    Ref(RefType),
     Func,
     EmptyBlockType,
 }
 
 impl ValueType {
+    pub const FUNCREF: ValueType = ValueType::Ref(RefType::FUNCREF);
+    pub const EXTERNREF: ValueType = ValueType::Ref(RefType::EXTERNREF);
+    pub const EXNREF: ValueType = ValueType::Ref(RefType::EXNREF);
+
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<ValueType, Error> {
+        let (num, _) = reader.leb128_signed()?;
+        ValueType::from_code(num, reader)
+    }
+
+    /// Decodes a value type whose leading byte has already been read as
+    /// `num`. `(ref ht)` and `(ref null ht)` continue with a heap type.
+    pub fn from_code<T: Read>(num: i64, reader: &mut T) -> Result<ValueType, Error> {
+        match num {
+            -0x1c => Ok(ValueType::Ref(RefType {
+                nullable: false,
+                heap_type: HeapType::from_reader(reader)?,
+            })),
+            -0x1d => Ok(ValueType::Ref(RefType {
+                nullable: true,
+                heap_type: HeapType::from_reader(reader)?,
+            })),
+            _ => ValueType::from_i64(num),
+        }
+    }
+
+    /// Decodes the single byte encodings, including the shorthands for
+    /// nullable abstract reference types such as `funcref`.
     pub fn from_i64(num: i64) -> Result<ValueType, Error> {
         match num {
             -0x01 => Ok(ValueType::Integer32),
             -0x02 => Ok(ValueType::Integer64),
             -0x03 => Ok(ValueType::Float32),
             -0x04 => Ok(ValueType::Float64),
-            -0x10 => Ok(ValueType::Anyfunc),
+            -0x05 => Ok(ValueType::V128),
             -0x20 => Ok(ValueType::Func),
             -0x40 => Ok(ValueType::EmptyBlockType),
-            _ => Err(Error::new(ErrorKind::Other, "Unknown Value Type")),
+            _ => match HeapType::from_abstract(num) {
+                Some(heap_type) => Ok(ValueType::Ref(RefType {
+                    nullable: true,
+                    heap_type,
+                })),
+                None => Err(Error::other("Unknown Value Type")),
+            },
+        }
+    }
+
+    pub fn is_reference(self) -> bool {
+        matches!(self, ValueType::Ref(_))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct RefType {
+    pub nullable: bool,
+    pub heap_type: HeapType,
+}
+
+impl RefType {
+    pub const FUNCREF: RefType = RefType {
+        nullable: true,
+        heap_type: HeapType::Func,
+    };
+    pub const EXTERNREF: RefType = RefType {
+        nullable: true,
+        heap_type: HeapType::Extern,
+    };
+    pub const EXNREF: RefType = RefType {
+        nullable: true,
+        heap_type: HeapType::Exn,
+    };
+
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<RefType, Error> {
+        let (num, _) = reader.leb128_signed()?;
+        RefType::from_code(num, reader)
+    }
+
+    pub fn from_code<T: Read>(num: i64, reader: &mut T) -> Result<RefType, Error> {
+        match ValueType::from_code(num, reader)? {
+            ValueType::Ref(ref_type) => Ok(ref_type),
+            _ => Err(Error::other("Expected a reference type")),
+        }
+    }
+}
+
+/// The referenced type of a reference. `Concrete` refers to a defined type
+/// by index, the other variants are the abstract types of the hierarchy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum HeapType {
+    Func,
+    Extern,
+    Any,
+    Eq,
+    I31,
+    Struct,
+    Array,
+    Exn,
+    None,
+    NoFunc,
+    NoExtern,
+    NoExn,
+    Concrete(u32),
+}
+
+impl HeapType {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<HeapType, Error> {
+        let (num, _) = reader.leb128_signed()?;
+
+        if num >= 0 {
+            return Ok(HeapType::Concrete(num as u32));
+        }
+
+        HeapType::from_abstract(num)
+            .ok_or_else(|| Error::other(format!("Unknown heap type {}", num)))
+    }
+
+    fn from_abstract(num: i64) -> Option<HeapType> {
+        match num {
+            -0x0c => Some(HeapType::NoExn),
+            -0x0d => Some(HeapType::NoFunc),
+            -0x0e => Some(HeapType::NoExtern),
+            -0x0f => Some(HeapType::None),
+            -0x10 => Some(HeapType::Func),
+            -0x11 => Some(HeapType::Extern),
+            -0x12 => Some(HeapType::Any),
+            -0x13 => Some(HeapType::Eq),
+            -0x14 => Some(HeapType::I31),
+            -0x15 => Some(HeapType::Struct),
+            -0x16 => Some(HeapType::Array),
+            -0x17 => Some(HeapType::Exn),
+            _ => None,
         }
     }
 }
@@ -89,40 +253,86 @@ pub struct FunctionType {
     pub param_count: u32,
     pub param_types: Vec<ValueType>,
     pub return_count: u32,
-    pub return_type: Option<ValueType>,
+    pub return_types: Vec<ValueType>,
 }
 
+pub const LIMITS_HAS_MAXIMUM: u8 = 0x01;
+pub const LIMITS_SHARED: u8 = 0x02;
+pub const LIMITS_64: u8 = 0x04;
+pub const LIMITS_CUSTOM_PAGE_SIZE: u8 = 0x08;
+
+pub const DEFAULT_PAGE_SIZE_LOG2: u32 = 16;
+
 #[derive(Debug, PartialEq, Serialize)]
 pub struct ResizableLimits {
-    flags: u8,
-    initial: u32,
-    maximum: Option<u32>,
+    pub flags: u8,
+    pub initial: u64,
+    pub maximum: Option<u64>,
+    pub page_size_log2: Option<u32>,
 }
 
 impl ResizableLimits {
     pub fn from_reader<T: Read>(reader: &mut T) -> Result<ResizableLimits, Error> {
-        let (flags, _) = reader.leb128_unsigned()?;
-        let (initial, _) = reader.leb128_unsigned()?;
+        let flags = reader.read_u8()?;
+
+        if flags & !(LIMITS_HAS_MAXIMUM | LIMITS_SHARED | LIMITS_64 | LIMITS_CUSTOM_PAGE_SIZE) != 0
+        {
+            return Err(Error::other(format!(
+                "Unknown limits flags 0x{:02x}",
+                flags
+            )));
+        }
+
+        let initial = read_limit(reader, flags)?;
         let mut maximum = None;
-        if flags == 1 {
-            let (max, _) = reader.leb128_unsigned()?;
-            maximum = Some(max as u32);
+        if flags & LIMITS_HAS_MAXIMUM != 0 {
+            maximum = Some(read_limit(reader, flags)?);
+        }
+        let mut page_size_log2 = None;
+        if flags & LIMITS_CUSTOM_PAGE_SIZE != 0 {
+            page_size_log2 = Some(reader.leb128_unsigned()?.0 as u32);
         }
 
         Ok(ResizableLimits {
-            flags: flags as u8,
-            initial: initial as u32,
+            flags,
+            initial,
             maximum,
+            page_size_log2,
         })
     }
+
+    pub fn is_shared(&self) -> bool {
+        self.flags & LIMITS_SHARED != 0
+    }
+
+    pub fn is_64(&self) -> bool {
+        self.flags & LIMITS_64 != 0
+    }
+}
+
+/// Limits of 64-bit memories and tables are encoded as u64.
+fn read_limit<T: Read>(reader: &mut T, flags: u8) -> Result<u64, Error> {
+    if flags & LIMITS_64 != 0 {
+        Ok(reader.leb128_unsigned_64()?.0)
+    } else {
+        let (limit, _) = reader.leb128_unsigned()?;
+        if limit > i64::from(u32::MAX) {
+            return Err(Error::other(format!(
+                "Limit {} does not fit in 32 bits",
+                limit
+            )));
+        }
+        Ok(limit as u64)
+    }
 }
 
 #[derive(Debug, PartialEq, Serialize)]
 pub enum ExternalKind {
     Function(u32),
-    Table,
+    Table(table_section::TableType),
     Memory(MemoryType),
-    Global,
+    Global(global_section::GlobalType),
+    Tag(tag_section::TagType),
 }
 
 impl ExternalKind {
@@ -134,10 +344,28 @@ impl ExternalKind {
                 let (fn_idx, _) = reader.leb128_unsigned()?;
                 Ok(ExternalKind::Function(fn_idx as u32))
             }
-            1 => unimplemented!("Table imports not implemented"),
-            2 => unimplemented!("Memory imports not implemented"),
-            3 => unimplemented!("Global imports not implemented"),
-            _ => Err(Error::new(ErrorKind::Other, "Unknown External Kind")),
+            1 => Ok(ExternalKind::Table(table_section::TableType::from_reader(
+                reader,
+            )?)),
+            2 => Ok(ExternalKind::Memory(MemoryType::from_reader(reader)?)),
+            3 => Ok(ExternalKind::Global(
+                global_section::GlobalType::from_reader(reader)?,
+            )),
+            4 => Ok(ExternalKind::Tag(tag_section::TagType::from_reader(
+                reader,
+            )?)),
+            _ => Err(Error::other("Unknown External Kind")),
+        }
+    }
+
+    /// The index space the import adds to, shared with exports.
+    pub fn kind(&self) -> ExportKind {
+        match *self {
+            ExternalKind::Function(_) => ExportKind::Func,
+            ExternalKind::Table(_) => ExportKind::Table,
+            ExternalKind::Memory(_) => ExportKind::Memory,
+            ExternalKind::Global(_) => ExportKind::Global,
+            ExternalKind::Tag(_) => ExportKind::Tag,
         }
     }
 }
@@ -147,19 +375,277 @@ pub struct MemoryType {
     pub limits: ResizableLimits,
 }
 
+impl MemoryType {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<MemoryType, Error> {
+        let limits = ResizableLimits::from_reader(reader)?;
+
+        if let Some(log2) = limits.page_size_log2 {
+            // Only byte-sized and the default 64KiB pages are allowed so far.
+            if log2 != 0 && log2 != DEFAULT_PAGE_SIZE_LOG2 {
+                return Err(Error::other(format!("Invalid page size 2^{}", log2)));
+            }
+        }
+
+        Ok(MemoryType { limits })
+    }
+
+    pub fn is_shared(&self) -> bool {
+        self.limits.is_shared()
+    }
+
+    pub fn is_64(&self) -> bool {
+        self.limits.is_64()
+    }
+
+    pub fn page_size_log2(&self) -> Option<u32> {
+        self.limits.page_size_log2
+    }
+
+    /// The size of a page in bytes, 64KiB unless the memory declares a
+    /// custom page size.
+    pub fn page_size(&self) -> u64 {
+        1 << self.page_size_log2().unwrap_or(DEFAULT_PAGE_SIZE_LOG2)
+    }
+
+    pub fn initial_size(&self) -> u128 {
+        u128::from(self.limits.initial) * u128::from(self.page_size())
+    }
+
+    pub fn maximum_size(&self) -> Option<u128> {
+        self.limits
+            .maximum
+            .map(|maximum| u128::from(maximum) * u128::from(self.page_size()))
+    }
+
+    /// The largest number of pages the memory's address space can hold,
+    /// capped by what the limits can encode.
+    pub fn max_pages(&self) -> u64 {
+        let (address_space, encodable) = if self.is_64() {
+            (1 << 64, u128::from(u64::MAX))
+        } else {
+            (1 << 32, u128::from(u32::MAX))
+        };
+        (address_space / u128::from(self.page_size())).min(encodable) as u64
+    }
+}
+
+/// A constant expression, kept as its encoded bytes including the final
+/// `end`.
 #[derive(Debug, PartialEq, Serialize)]
 pub struct Expression(Vec<u8>);
 
 impl Expression {
     pub fn from_reader<T: Read>(reader: &mut T) -> Result<Expression, Error> {
-        let mut bytes = Vec::new();
-        let mut buff = [0];
+        let mut recorder = Recorder {
+            inner: reader,
+            bytes: Vec::new(),
+        };
+
+        // Immediates such as `ref.func 11` can contain 0x0b, so the
+        // expression has to be decoded to find its end.
+        while Operator::from_reader(&mut recorder)? != Operator::End {}
+
+        Ok(Expression(recorder.bytes))
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn operators(&self) -> Result<Vec<Operator>, Error> {
+        let mut reader = Cursor::new(&self.0);
+        let mut operators = Vec::new();
+
+        while (reader.position() as usize) < self.0.len() {
+            operators.push(Operator::from_reader(&mut reader)?);
+        }
+
+        Ok(operators)
+    }
+
+    /// Computes the value of the expression. `global` supplies the values of
+    /// the globals it reads, such as an imported `__memory_base`.
+    pub fn evaluate<F>(&self, global: F) -> Result<Value, Error>
+    where
+        F: Fn(u32) -> Option<Value>,
+    {
+        let mut stack = Vec::new();
+
+        for operator in self.operators()? {
+            let value = match operator {
+                Operator::I32Const(value) => Value::I32(value),
+                Operator::I64Const(value) => Value::I64(value),
+                Operator::F32Const(value) => Value::F32(value),
+                Operator::F64Const(value) => Value::F64(value),
+                Operator::V128Const(bytes) => Value::V128(bytes),
+                Operator::RefNull(heap_type) => Value::RefNull(heap_type),
+                Operator::RefFunc(index) => Value::RefFunc(index),
+                Operator::GlobalGet(index) => global(index)
+                    .ok_or_else(|| Error::other(format!("No value for global {}", index)))?,
+                Operator::I32Add | Operator::I32Sub | Operator::I32Mul => {
+                    let (lhs, rhs) = match (stack.pop(), stack.pop()) {
+                        (Some(Value::I32(rhs)), Some(Value::I32(lhs))) => (lhs, rhs),
+                        _ => return Err(Error::other("Type mismatch in constant expression")),
+                    };
+                    Value::I32(match operator {
+                        Operator::I32Add => lhs.wrapping_add(rhs),
+                        Operator::I32Sub => lhs.wrapping_sub(rhs),
+                        _ => lhs.wrapping_mul(rhs),
+                    })
+                }
+                Operator::I64Add | Operator::I64Sub | Operator::I64Mul => {
+                    let (lhs, rhs) = match (stack.pop(), stack.pop()) {
+                        (Some(Value::I64(rhs)), Some(Value::I64(lhs))) => (lhs, rhs),
+                        _ => return Err(Error::other("Type mismatch in constant expression")),
+                    };
+                    Value::I64(match operator {
+                        Operator::I64Add => lhs.wrapping_add(rhs),
+                        Operator::I64Sub => lhs.wrapping_sub(rhs),
+                        _ => lhs.wrapping_mul(rhs),
+                    })
+                }
+                Operator::End => break,
+                _ => {
+                    return Err(Error::other(format!(
+                        "Cannot evaluate {:?} in a constant expression",
+                        operator
+                    )))
+                }
+            };
 
-        while buff[0] != 0x0b {
-            reader.read_exact(&mut buff)?;
-            bytes.push(buff[0]);
+            stack.push(value);
         }
 
-        Ok(Expression(bytes))
+        match (stack.pop(), stack.is_empty()) {
+            (Some(value), true) => Ok(value),
+            _ => Err(Error::other(
+                "Constant expression must produce exactly one value",
+            )),
+        }
+    }
+}
+
+/// The result of evaluating a constant expression.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Value {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    V128([u8; 16]),
+    RefNull(HeapType),
+    RefFunc(u32),
+}
+
+/// Keeps a copy of every byte read through it.
+struct Recorder<'a, T: 'a> {
+    inner: &'a mut T,
+    bytes: Vec<u8>,
+}
+
+impl<'a, T: Read> Read for Recorder<'a, T> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let read = self.inner.read(buf)?;
+        self.bytes.extend_from_slice(&buf[..read]);
+        Ok(read)
+    }
+}
+
+/// Reads exactly `len` bytes. The buffer only grows as bytes arrive, so a
+/// corrupt length runs into the end of the input instead of allocating it.
+pub fn read_bytes<T: Read>(reader: &mut T, len: u64) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    reader.by_ref().take(len).read_to_end(&mut bytes)?;
+
+    if (bytes.len() as u64) < len {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "failed to fill whole buffer",
+        ));
+    }
+
+    Ok(bytes)
+}
+
+pub fn read_name<T: Read>(reader: &mut T) -> Result<String, Error> {
+    let (len, _) = reader.leb128_unsigned()?;
+    let buff = read_bytes(reader, len as u64)?;
+    Ok(String::from_utf8_lossy(&buff).into_owned())
+}
+
+pub fn read_index<T: Read>(reader: &mut T) -> Result<u32, Error> {
+    let (index, _) = reader.leb128_unsigned()?;
+    Ok(index as u32)
+}
+
+pub fn read_vec<R, T, F>(reader: &mut R, read: F) -> Result<Vec<T>, Error>
+where
+    R: Read,
+    F: Fn(&mut R) -> Result<T, Error>,
+{
+    let (count, _) = reader.leb128_unsigned()?;
+
+    let mut entries = Vec::new();
+
+    for _ in 0..count {
+        entries.push(read(reader)?);
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Expression, MemoryType, Value, WasmSectionBody};
+    use std::io::Cursor;
+    use testing::*;
+
+    #[test]
+    fn test_evaluate_extended_const() {
+        // global.get 0; i32.const 16; i32.add; i32.const 3; i32.mul; end
+        let expr = Expression(vec![0x23, 0x00, 0x41, 0x10, 0x6a, 0x41, 0x03, 0x6c, 0x0b]);
+
+        let memory_base = |index| match index {
+            0 => Some(Value::I32(1024)),
+            _ => None,
+        };
+        assert_eq!(expr.evaluate(memory_base).unwrap(), Value::I32(3120));
+        assert!(expr.evaluate(|_| None).is_err());
+    }
+
+    #[test]
+    fn test_vector_count_is_not_trusted() {
+        // A function name map claiming 0xffffffff entries.
+        let names = custom("name", &[0x01, 0x05, 0xff, 0xff, 0xff, 0xff, 0x0f]);
+        let module = module(&[(CUSTOM, &names)]);
+
+        match module.sections[0].body {
+            WasmSectionBody::Custom(_) => {}
+            ref body => panic!("Expected a raw custom section, got {:?}", body),
+        }
+    }
+
+    #[test]
+    fn test_lengths_are_not_trusted() {
+        // An import whose module name claims 0xffffffff bytes.
+        let import = module_bytes(&[(IMPORT, &[0x01, 0xff, 0xff, 0xff, 0xff, 0x0f])]);
+        assert!(::parse(Cursor::new(import)).is_err());
+
+        // A function body claiming 0xffffffff local declarations.
+        let locals = code(&[&[0xff, 0xff, 0xff, 0xff, 0x0f, 0x0b]]);
+        let body = module_bytes(&[(CODE, &locals)]);
+        assert!(::parse(Cursor::new(body)).is_err());
+    }
+
+    #[test]
+    fn test_page_size_exponent() {
+        let memory = |log2| MemoryType::from_reader(&mut Cursor::new(vec![0x08, 0x01, log2]));
+
+        assert_eq!(memory(0x00).unwrap().page_size(), 1);
+        assert_eq!(memory(0x10).unwrap().page_size(), 65536);
+        // Any other exponent is an error rather than clamped, even past 2^63.
+        assert!(memory(0x01).is_err());
+        assert!(memory(0x40).is_err());
     }
 }
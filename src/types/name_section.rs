@@ -0,0 +1,114 @@
+use byteorder::ReadBytesExt;
+use leb128::ReadLeb128Ext;
+use std::io::{Cursor, Error, Read};
+
+use super::*;
+
+/// The `name` custom section, giving debug names to the module and to
+/// entries of its index spaces. Subsections this crate does not know are
+/// skipped.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct NameSection {
+    pub module_name: Option<String>,
+    pub function_names: Vec<Naming>,
+    pub local_names: Vec<IndirectNaming>,
+    pub label_names: Vec<IndirectNaming>,
+    pub type_names: Vec<Naming>,
+    pub table_names: Vec<Naming>,
+    pub memory_names: Vec<Naming>,
+    pub global_names: Vec<Naming>,
+    pub element_names: Vec<Naming>,
+    pub data_names: Vec<Naming>,
+    pub field_names: Vec<IndirectNaming>,
+    pub tag_names: Vec<Naming>,
+}
+
+impl NameSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<NameSection, Error> {
+        let mut section = NameSection::default();
+
+        while let Ok(id) = reader.read_u8() {
+            let (len, _) = reader.leb128_unsigned()?;
+            let payload = read_bytes(reader, len as u64)?;
+            let payload = &mut Cursor::new(payload);
+
+            match id {
+                0 => section.module_name = Some(read_name(payload)?),
+                1 => section.function_names = read_vec(payload, Naming::from_reader)?,
+                2 => section.local_names = read_vec(payload, IndirectNaming::from_reader)?,
+                3 => section.label_names = read_vec(payload, IndirectNaming::from_reader)?,
+                4 => section.type_names = read_vec(payload, Naming::from_reader)?,
+                5 => section.table_names = read_vec(payload, Naming::from_reader)?,
+                6 => section.memory_names = read_vec(payload, Naming::from_reader)?,
+                7 => section.global_names = read_vec(payload, Naming::from_reader)?,
+                8 => section.element_names = read_vec(payload, Naming::from_reader)?,
+                9 => section.data_names = read_vec(payload, Naming::from_reader)?,
+                10 => section.field_names = read_vec(payload, IndirectNaming::from_reader)?,
+                11 => section.tag_names = read_vec(payload, Naming::from_reader)?,
+                _ => {}
+            }
+        }
+
+        Ok(section)
+    }
+
+    pub fn function_name(&self, index: u32) -> Option<&str> {
+        find_name(&self.function_names, index)
+    }
+
+    pub fn type_name(&self, index: u32) -> Option<&str> {
+        find_name(&self.type_names, index)
+    }
+
+    pub fn table_name(&self, index: u32) -> Option<&str> {
+        find_name(&self.table_names, index)
+    }
+
+    pub fn memory_name(&self, index: u32) -> Option<&str> {
+        find_name(&self.memory_names, index)
+    }
+
+    pub fn global_name(&self, index: u32) -> Option<&str> {
+        find_name(&self.global_names, index)
+    }
+
+    pub fn data_name(&self, index: u32) -> Option<&str> {
+        find_name(&self.data_names, index)
+    }
+}
+
+fn find_name(names: &[Naming], index: u32) -> Option<&str> {
+    names
+        .iter()
+        .find(|naming| naming.index == index)
+        .map(|naming| naming.name.as_str())
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Naming {
+    pub index: u32,
+    pub name: String,
+}
+
+impl Naming {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<Naming, Error> {
+        let index = read_index(reader)?;
+        let name = read_name(reader)?;
+        Ok(Naming { index, name })
+    }
+}
+
+/// Names of entries nested in another, such as the locals of a function.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct IndirectNaming {
+    pub index: u32,
+    pub names: Vec<Naming>,
+}
+
+impl IndirectNaming {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<IndirectNaming, Error> {
+        let index = read_index(reader)?;
+        let names = read_vec(reader, Naming::from_reader)?;
+        Ok(IndirectNaming { index, names })
+    }
+}
@@ -0,0 +1,1754 @@
+use byteorder::{LittleEndian, ReadBytesExt};
+use leb128::ReadLeb128Ext;
+use std::io::{Error, Read};
+
+use super::*;
+
+/// The alignment hint (as a power of two), memory index and static offset of
+/// a memory access. Offsets are 64-bit so that memory64 accesses can be
+/// represented; validation checks that they fit the memory's address type.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MemArg {
+    pub align: u32,
+    pub memory: u32,
+    pub offset: u64,
+}
+
+/// Set in the encoded alignment when an explicit memory index follows it.
+const MEMARG_HAS_MEMORY: u32 = 0x40;
+
+impl MemArg {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<MemArg, Error> {
+        let (align, _) = reader.leb128_unsigned()?;
+        let mut align = align as u32;
+
+        let memory = if align & MEMARG_HAS_MEMORY != 0 {
+            align &= !MEMARG_HAS_MEMORY;
+            read_index(reader)?
+        } else {
+            0
+        };
+
+        let (offset, _) = reader.leb128_unsigned_64()?;
+
+        Ok(MemArg {
+            align,
+            memory,
+            offset,
+        })
+    }
+}
+
+/// The signature of a `block`, `loop` or `if`. Blocks with several params or
+/// results refer to a function type by index.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum BlockType {
+    Empty,
+    Value(ValueType),
+    FuncType(u32),
+}
+
+impl BlockType {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<BlockType, Error> {
+        let (num, _) = reader.leb128_signed()?;
+
+        match num {
+            -0x40 => Ok(BlockType::Empty),
+            _ if num < 0 => Ok(BlockType::Value(ValueType::from_code(num, reader)?)),
+            _ => Ok(BlockType::FuncType(num as u32)),
+        }
+    }
+}
+
+/// A handler of a `try_table`, branching to `label` when an exception is
+/// caught. The `Ref` forms also pass the caught exception as an `exnref`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum TryTableCatch {
+    Catch { tag: u32, label: u32 },
+    CatchRef { tag: u32, label: u32 },
+    CatchAll { label: u32 },
+    CatchAllRef { label: u32 },
+}
+
+impl TryTableCatch {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<TryTableCatch, Error> {
+        let kind = reader.read_u8()?;
+
+        match kind {
+            0x00 | 0x01 => {
+                let tag = read_index(reader)?;
+                let label = read_index(reader)?;
+
+                if kind == 0x00 {
+                    Ok(TryTableCatch::Catch { tag, label })
+                } else {
+                    Ok(TryTableCatch::CatchRef { tag, label })
+                }
+            }
+            0x02 => Ok(TryTableCatch::CatchAll {
+                label: read_index(reader)?,
+            }),
+            0x03 => Ok(TryTableCatch::CatchAllRef {
+                label: read_index(reader)?,
+            }),
+            _ => Err(Error::other(format!("Unknown catch kind 0x{:02x}", kind))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Operator {
+    Unreachable,
+    Nop,
+    Block(BlockType),
+    Loop(BlockType),
+    If(BlockType),
+    Else,
+    Try(BlockType),
+    Catch(u32),
+    Throw(u32),
+    Rethrow(u32),
+    ThrowRef,
+    End,
+    Br(u32),
+    BrIf(u32),
+    BrTable {
+        targets: Vec<u32>,
+        default: u32,
+    },
+    Return,
+    Call(u32),
+    CallIndirect {
+        type_index: u32,
+        table_index: u32,
+    },
+    ReturnCall(u32),
+    ReturnCallIndirect {
+        type_index: u32,
+        table_index: u32,
+    },
+    CallRef(u32),
+    ReturnCallRef(u32),
+    Delegate(u32),
+    CatchAll,
+    Drop,
+    Select,
+    TypedSelect(Vec<ValueType>),
+    TryTable {
+        block_type: BlockType,
+        catches: Vec<TryTableCatch>,
+    },
+    LocalGet(u32),
+    LocalSet(u32),
+    LocalTee(u32),
+    GlobalGet(u32),
+    GlobalSet(u32),
+    TableGet(u32),
+    TableSet(u32),
+    I32Load(MemArg),
+    I64Load(MemArg),
+    F32Load(MemArg),
+    F64Load(MemArg),
+    I32Load8S(MemArg),
+    I32Load8U(MemArg),
+    I32Load16S(MemArg),
+    I32Load16U(MemArg),
+    I64Load8S(MemArg),
+    I64Load8U(MemArg),
+    I64Load16S(MemArg),
+    I64Load16U(MemArg),
+    I64Load32S(MemArg),
+    I64Load32U(MemArg),
+    I32Store(MemArg),
+    I64Store(MemArg),
+    F32Store(MemArg),
+    F64Store(MemArg),
+    I32Store8(MemArg),
+    I32Store16(MemArg),
+    I64Store8(MemArg),
+    I64Store16(MemArg),
+    I64Store32(MemArg),
+    MemorySize(u32),
+    MemoryGrow(u32),
+    I32Const(i32),
+    I64Const(i64),
+    F32Const(f32),
+    F64Const(f64),
+    I32Eqz,
+    I32Eq,
+    I32Ne,
+    I32LtS,
+    I32LtU,
+    I32GtS,
+    I32GtU,
+    I32LeS,
+    I32LeU,
+    I32GeS,
+    I32GeU,
+    I64Eqz,
+    I64Eq,
+    I64Ne,
+    I64LtS,
+    I64LtU,
+    I64GtS,
+    I64GtU,
+    I64LeS,
+    I64LeU,
+    I64GeS,
+    I64GeU,
+    F32Eq,
+    F32Ne,
+    F32Lt,
+    F32Gt,
+    F32Le,
+    F32Ge,
+    F64Eq,
+    F64Ne,
+    F64Lt,
+    F64Gt,
+    F64Le,
+    F64Ge,
+    I32Clz,
+    I32Ctz,
+    I32Popcnt,
+    I32Add,
+    I32Sub,
+    I32Mul,
+    I32DivS,
+    I32DivU,
+    I32RemS,
+    I32RemU,
+    I32And,
+    I32Or,
+    I32Xor,
+    I32Shl,
+    I32ShrS,
+    I32ShrU,
+    I32Rotl,
+    I32Rotr,
+    I64Clz,
+    I64Ctz,
+    I64Popcnt,
+    I64Add,
+    I64Sub,
+    I64Mul,
+    I64DivS,
+    I64DivU,
+    I64RemS,
+    I64RemU,
+    I64And,
+    I64Or,
+    I64Xor,
+    I64Shl,
+    I64ShrS,
+    I64ShrU,
+    I64Rotl,
+    I64Rotr,
+    F32Abs,
+    F32Neg,
+    F32Ceil,
+    F32Floor,
+    F32Trunc,
+    F32Nearest,
+    F32Sqrt,
+    F32Add,
+    F32Sub,
+    F32Mul,
+    F32Div,
+    F32Min,
+    F32Max,
+    F32Copysign,
+    F64Abs,
+    F64Neg,
+    F64Ceil,
+    F64Floor,
+    F64Trunc,
+    F64Nearest,
+    F64Sqrt,
+    F64Add,
+    F64Sub,
+    F64Mul,
+    F64Div,
+    F64Min,
+    F64Max,
+    F64Copysign,
+    I32WrapI64,
+    I32TruncF32S,
+    I32TruncF32U,
+    I32TruncF64S,
+    I32TruncF64U,
+    I64ExtendI32S,
+    I64ExtendI32U,
+    I64TruncF32S,
+    I64TruncF32U,
+    I64TruncF64S,
+    I64TruncF64U,
+    F32ConvertI32S,
+    F32ConvertI32U,
+    F32ConvertI64S,
+    F32ConvertI64U,
+    F32DemoteF64,
+    F64ConvertI32S,
+    F64ConvertI32U,
+    F64ConvertI64S,
+    F64ConvertI64U,
+    F64PromoteF32,
+    I32ReinterpretF32,
+    I64ReinterpretF64,
+    F32ReinterpretI32,
+    F64ReinterpretI64,
+    I32Extend8S,
+    I32Extend16S,
+    I64Extend8S,
+    I64Extend16S,
+    I64Extend32S,
+    I32TruncSatF32S,
+    I32TruncSatF32U,
+    I32TruncSatF64S,
+    I32TruncSatF64U,
+    I64TruncSatF32S,
+    I64TruncSatF32U,
+    I64TruncSatF64S,
+    I64TruncSatF64U,
+    MemoryInit {
+        data_index: u32,
+        memory: u32,
+    },
+    DataDrop(u32),
+    MemoryCopy {
+        dst: u32,
+        src: u32,
+    },
+    MemoryFill(u32),
+    TableInit {
+        elem_index: u32,
+        table: u32,
+    },
+    ElemDrop(u32),
+    TableCopy {
+        dst: u32,
+        src: u32,
+    },
+    RefNull(HeapType),
+    RefIsNull,
+    RefFunc(u32),
+    RefEq,
+    RefAsNonNull,
+    BrOnNull(u32),
+    BrOnNonNull(u32),
+    TableGrow(u32),
+    TableSize(u32),
+    TableFill(u32),
+    V128Load(MemArg),
+    V128Load8x8S(MemArg),
+    V128Load8x8U(MemArg),
+    V128Load16x4S(MemArg),
+    V128Load16x4U(MemArg),
+    V128Load32x2S(MemArg),
+    V128Load32x2U(MemArg),
+    V128Load8Splat(MemArg),
+    V128Load16Splat(MemArg),
+    V128Load32Splat(MemArg),
+    V128Load64Splat(MemArg),
+    V128Store(MemArg),
+    V128Const([u8; 16]),
+    I8x16Shuffle([u8; 16]),
+    I8x16Swizzle,
+    I8x16Splat,
+    I16x8Splat,
+    I32x4Splat,
+    I64x2Splat,
+    F32x4Splat,
+    F64x2Splat,
+    I8x16ExtractLaneS(u8),
+    I8x16ExtractLaneU(u8),
+    I8x16ReplaceLane(u8),
+    I16x8ExtractLaneS(u8),
+    I16x8ExtractLaneU(u8),
+    I16x8ReplaceLane(u8),
+    I32x4ExtractLane(u8),
+    I32x4ReplaceLane(u8),
+    I64x2ExtractLane(u8),
+    I64x2ReplaceLane(u8),
+    F32x4ExtractLane(u8),
+    F32x4ReplaceLane(u8),
+    F64x2ExtractLane(u8),
+    F64x2ReplaceLane(u8),
+    I8x16Eq,
+    I8x16Ne,
+    I8x16LtS,
+    I8x16LtU,
+    I8x16GtS,
+    I8x16GtU,
+    I8x16LeS,
+    I8x16LeU,
+    I8x16GeS,
+    I8x16GeU,
+    I16x8Eq,
+    I16x8Ne,
+    I16x8LtS,
+    I16x8LtU,
+    I16x8GtS,
+    I16x8GtU,
+    I16x8LeS,
+    I16x8LeU,
+    I16x8GeS,
+    I16x8GeU,
+    I32x4Eq,
+    I32x4Ne,
+    I32x4LtS,
+    I32x4LtU,
+    I32x4GtS,
+    I32x4GtU,
+    I32x4LeS,
+    I32x4LeU,
+    I32x4GeS,
+    I32x4GeU,
+    F32x4Eq,
+    F32x4Ne,
+    F32x4Lt,
+    F32x4Gt,
+    F32x4Le,
+    F32x4Ge,
+    F64x2Eq,
+    F64x2Ne,
+    F64x2Lt,
+    F64x2Gt,
+    F64x2Le,
+    F64x2Ge,
+    V128Not,
+    V128And,
+    V128Andnot,
+    V128Or,
+    V128Xor,
+    V128Bitselect,
+    V128AnyTrue,
+    V128Load8Lane {
+        memarg: MemArg,
+        lane: u8,
+    },
+    V128Load16Lane {
+        memarg: MemArg,
+        lane: u8,
+    },
+    V128Load32Lane {
+        memarg: MemArg,
+        lane: u8,
+    },
+    V128Load64Lane {
+        memarg: MemArg,
+        lane: u8,
+    },
+    V128Store8Lane {
+        memarg: MemArg,
+        lane: u8,
+    },
+    V128Store16Lane {
+        memarg: MemArg,
+        lane: u8,
+    },
+    V128Store32Lane {
+        memarg: MemArg,
+        lane: u8,
+    },
+    V128Store64Lane {
+        memarg: MemArg,
+        lane: u8,
+    },
+    V128Load32Zero(MemArg),
+    V128Load64Zero(MemArg),
+    F32x4DemoteF64x2Zero,
+    F64x2PromoteLowF32x4,
+    I8x16Abs,
+    I8x16Neg,
+    I8x16Popcnt,
+    I8x16AllTrue,
+    I8x16Bitmask,
+    I8x16NarrowI16x8S,
+    I8x16NarrowI16x8U,
+    F32x4Ceil,
+    F32x4Floor,
+    F32x4Trunc,
+    F32x4Nearest,
+    I8x16Shl,
+    I8x16ShrS,
+    I8x16ShrU,
+    I8x16Add,
+    I8x16AddSatS,
+    I8x16AddSatU,
+    I8x16Sub,
+    I8x16SubSatS,
+    I8x16SubSatU,
+    F64x2Ceil,
+    F64x2Floor,
+    I8x16MinS,
+    I8x16MinU,
+    I8x16MaxS,
+    I8x16MaxU,
+    F64x2Trunc,
+    I8x16AvgrU,
+    I16x8ExtaddPairwiseI8x16S,
+    I16x8ExtaddPairwiseI8x16U,
+    I32x4ExtaddPairwiseI16x8S,
+    I32x4ExtaddPairwiseI16x8U,
+    I16x8Abs,
+    I16x8Neg,
+    I16x8Q15mulrSatS,
+    I16x8AllTrue,
+    I16x8Bitmask,
+    I16x8NarrowI32x4S,
+    I16x8NarrowI32x4U,
+    I16x8ExtendLowI8x16S,
+    I16x8ExtendHighI8x16S,
+    I16x8ExtendLowI8x16U,
+    I16x8ExtendHighI8x16U,
+    I16x8Shl,
+    I16x8ShrS,
+    I16x8ShrU,
+    I16x8Add,
+    I16x8AddSatS,
+    I16x8AddSatU,
+    I16x8Sub,
+    I16x8SubSatS,
+    I16x8SubSatU,
+    F64x2Nearest,
+    I16x8Mul,
+    I16x8MinS,
+    I16x8MinU,
+    I16x8MaxS,
+    I16x8MaxU,
+    I16x8AvgrU,
+    I16x8ExtmulLowI8x16S,
+    I16x8ExtmulHighI8x16S,
+    I16x8ExtmulLowI8x16U,
+    I16x8ExtmulHighI8x16U,
+    I32x4Abs,
+    I32x4Neg,
+    I32x4AllTrue,
+    I32x4Bitmask,
+    I32x4ExtendLowI16x8S,
+    I32x4ExtendHighI16x8S,
+    I32x4ExtendLowI16x8U,
+    I32x4ExtendHighI16x8U,
+    I32x4Shl,
+    I32x4ShrS,
+    I32x4ShrU,
+    I32x4Add,
+    I32x4Sub,
+    I32x4Mul,
+    I32x4MinS,
+    I32x4MinU,
+    I32x4MaxS,
+    I32x4MaxU,
+    I32x4DotI16x8S,
+    I32x4ExtmulLowI16x8S,
+    I32x4ExtmulHighI16x8S,
+    I32x4ExtmulLowI16x8U,
+    I32x4ExtmulHighI16x8U,
+    I64x2Abs,
+    I64x2Neg,
+    I64x2AllTrue,
+    I64x2Bitmask,
+    I64x2ExtendLowI32x4S,
+    I64x2ExtendHighI32x4S,
+    I64x2ExtendLowI32x4U,
+    I64x2ExtendHighI32x4U,
+    I64x2Shl,
+    I64x2ShrS,
+    I64x2ShrU,
+    I64x2Add,
+    I64x2Sub,
+    I64x2Mul,
+    I64x2Eq,
+    I64x2Ne,
+    I64x2LtS,
+    I64x2GtS,
+    I64x2LeS,
+    I64x2GeS,
+    I64x2ExtmulLowI32x4S,
+    I64x2ExtmulHighI32x4S,
+    I64x2ExtmulLowI32x4U,
+    I64x2ExtmulHighI32x4U,
+    F32x4Abs,
+    F32x4Neg,
+    F32x4Sqrt,
+    F32x4Add,
+    F32x4Sub,
+    F32x4Mul,
+    F32x4Div,
+    F32x4Min,
+    F32x4Max,
+    F32x4Pmin,
+    F32x4Pmax,
+    F64x2Abs,
+    F64x2Neg,
+    F64x2Sqrt,
+    F64x2Add,
+    F64x2Sub,
+    F64x2Mul,
+    F64x2Div,
+    F64x2Min,
+    F64x2Max,
+    F64x2Pmin,
+    F64x2Pmax,
+    I32x4TruncSatF32x4S,
+    I32x4TruncSatF32x4U,
+    F32x4ConvertI32x4S,
+    F32x4ConvertI32x4U,
+    I32x4TruncSatF64x2SZero,
+    I32x4TruncSatF64x2UZero,
+    F64x2ConvertLowI32x4S,
+    F64x2ConvertLowI32x4U,
+    I8x16RelaxedSwizzle,
+    I32x4RelaxedTruncF32x4S,
+    I32x4RelaxedTruncF32x4U,
+    I32x4RelaxedTruncF64x2SZero,
+    I32x4RelaxedTruncF64x2UZero,
+    F32x4RelaxedMadd,
+    F32x4RelaxedNmadd,
+    F64x2RelaxedMadd,
+    F64x2RelaxedNmadd,
+    I8x16RelaxedLaneselect,
+    I16x8RelaxedLaneselect,
+    I32x4RelaxedLaneselect,
+    I64x2RelaxedLaneselect,
+    F32x4RelaxedMin,
+    F32x4RelaxedMax,
+    F64x2RelaxedMin,
+    F64x2RelaxedMax,
+    I16x8RelaxedQ15mulrS,
+    I16x8RelaxedDotI8x16I7x16S,
+    I32x4RelaxedDotI8x16I7x16AddS,
+    MemoryAtomicNotify(MemArg),
+    MemoryAtomicWait32(MemArg),
+    MemoryAtomicWait64(MemArg),
+    AtomicFence,
+    I32AtomicLoad(MemArg),
+    I64AtomicLoad(MemArg),
+    I32AtomicLoad8U(MemArg),
+    I32AtomicLoad16U(MemArg),
+    I64AtomicLoad8U(MemArg),
+    I64AtomicLoad16U(MemArg),
+    I64AtomicLoad32U(MemArg),
+    I32AtomicStore(MemArg),
+    I64AtomicStore(MemArg),
+    I32AtomicStore8(MemArg),
+    I32AtomicStore16(MemArg),
+    I64AtomicStore8(MemArg),
+    I64AtomicStore16(MemArg),
+    I64AtomicStore32(MemArg),
+    I32AtomicRmwAdd(MemArg),
+    I64AtomicRmwAdd(MemArg),
+    I32AtomicRmw8AddU(MemArg),
+    I32AtomicRmw16AddU(MemArg),
+    I64AtomicRmw8AddU(MemArg),
+    I64AtomicRmw16AddU(MemArg),
+    I64AtomicRmw32AddU(MemArg),
+    I32AtomicRmwSub(MemArg),
+    I64AtomicRmwSub(MemArg),
+    I32AtomicRmw8SubU(MemArg),
+    I32AtomicRmw16SubU(MemArg),
+    I64AtomicRmw8SubU(MemArg),
+    I64AtomicRmw16SubU(MemArg),
+    I64AtomicRmw32SubU(MemArg),
+    I32AtomicRmwAnd(MemArg),
+    I64AtomicRmwAnd(MemArg),
+    I32AtomicRmw8AndU(MemArg),
+    I32AtomicRmw16AndU(MemArg),
+    I64AtomicRmw8AndU(MemArg),
+    I64AtomicRmw16AndU(MemArg),
+    I64AtomicRmw32AndU(MemArg),
+    I32AtomicRmwOr(MemArg),
+    I64AtomicRmwOr(MemArg),
+    I32AtomicRmw8OrU(MemArg),
+    I32AtomicRmw16OrU(MemArg),
+    I64AtomicRmw8OrU(MemArg),
+    I64AtomicRmw16OrU(MemArg),
+    I64AtomicRmw32OrU(MemArg),
+    I32AtomicRmwXor(MemArg),
+    I64AtomicRmwXor(MemArg),
+    I32AtomicRmw8XorU(MemArg),
+    I32AtomicRmw16XorU(MemArg),
+    I64AtomicRmw8XorU(MemArg),
+    I64AtomicRmw16XorU(MemArg),
+    I64AtomicRmw32XorU(MemArg),
+    I32AtomicRmwXchg(MemArg),
+    I64AtomicRmwXchg(MemArg),
+    I32AtomicRmw8XchgU(MemArg),
+    I32AtomicRmw16XchgU(MemArg),
+    I64AtomicRmw8XchgU(MemArg),
+    I64AtomicRmw16XchgU(MemArg),
+    I64AtomicRmw32XchgU(MemArg),
+    I32AtomicRmwCmpxchg(MemArg),
+    I64AtomicRmwCmpxchg(MemArg),
+    I32AtomicRmw8CmpxchgU(MemArg),
+    I32AtomicRmw16CmpxchgU(MemArg),
+    I64AtomicRmw8CmpxchgU(MemArg),
+    I64AtomicRmw16CmpxchgU(MemArg),
+    I64AtomicRmw32CmpxchgU(MemArg),
+    StructNew(u32),
+    StructNewDefault(u32),
+    StructGet {
+        type_index: u32,
+        field_index: u32,
+    },
+    StructGetS {
+        type_index: u32,
+        field_index: u32,
+    },
+    StructGetU {
+        type_index: u32,
+        field_index: u32,
+    },
+    StructSet {
+        type_index: u32,
+        field_index: u32,
+    },
+    ArrayNew(u32),
+    ArrayNewDefault(u32),
+    ArrayNewFixed {
+        type_index: u32,
+        size: u32,
+    },
+    ArrayNewData {
+        type_index: u32,
+        data_index: u32,
+    },
+    ArrayNewElem {
+        type_index: u32,
+        elem_index: u32,
+    },
+    ArrayGet(u32),
+    ArrayGetS(u32),
+    ArrayGetU(u32),
+    ArraySet(u32),
+    ArrayLen,
+    ArrayFill(u32),
+    ArrayCopy {
+        dst: u32,
+        src: u32,
+    },
+    ArrayInitData {
+        type_index: u32,
+        data_index: u32,
+    },
+    ArrayInitElem {
+        type_index: u32,
+        elem_index: u32,
+    },
+    RefTest(RefType),
+    RefCast(RefType),
+    BrOnCast {
+        label: u32,
+        from: RefType,
+        to: RefType,
+    },
+    BrOnCastFail {
+        label: u32,
+        from: RefType,
+        to: RefType,
+    },
+    AnyConvertExtern,
+    ExternConvertAny,
+    RefI31,
+    I31GetS,
+    I31GetU,
+}
+
+impl Operator {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<Operator, Error> {
+        Operator::from_reader_with_features(reader, &Features::default())
+    }
+
+    pub fn from_reader_with_features<T: Read>(
+        reader: &mut T,
+        features: &Features,
+    ) -> Result<Operator, Error> {
+        let code = reader.read_u8()?;
+
+        let operator = match code {
+            0x00 => Operator::Unreachable,
+            0x01 => Operator::Nop,
+            0x02 => Operator::Block(BlockType::from_reader(reader)?),
+            0x03 => Operator::Loop(BlockType::from_reader(reader)?),
+            0x04 => Operator::If(BlockType::from_reader(reader)?),
+            0x05 => Operator::Else,
+            0x06 => Operator::Try(BlockType::from_reader(reader)?),
+            0x07 => Operator::Catch(read_index(reader)?),
+            0x08 => Operator::Throw(read_index(reader)?),
+            0x09 => Operator::Rethrow(read_index(reader)?),
+            0x0a => Operator::ThrowRef,
+            0x0b => Operator::End,
+            0x0c => Operator::Br(read_index(reader)?),
+            0x0d => Operator::BrIf(read_index(reader)?),
+            0x0e => {
+                let targets = read_vec(reader, read_index)?;
+                let default = read_index(reader)?;
+                Operator::BrTable { targets, default }
+            }
+            0x0f => Operator::Return,
+            0x10 => Operator::Call(read_index(reader)?),
+            0x11 => {
+                let type_index = read_index(reader)?;
+                let table_index = read_index(reader)?;
+                Operator::CallIndirect {
+                    type_index,
+                    table_index,
+                }
+            }
+            0x12 => Operator::ReturnCall(read_index(reader)?),
+            0x13 => {
+                let type_index = read_index(reader)?;
+                let table_index = read_index(reader)?;
+                Operator::ReturnCallIndirect {
+                    type_index,
+                    table_index,
+                }
+            }
+            0x14 => Operator::CallRef(read_index(reader)?),
+            0x15 => Operator::ReturnCallRef(read_index(reader)?),
+            0x18 => Operator::Delegate(read_index(reader)?),
+            0x19 => Operator::CatchAll,
+            0x1a => Operator::Drop,
+            0x1b => Operator::Select,
+            0x1c => Operator::TypedSelect(read_vec(reader, ValueType::from_reader)?),
+            0x1f => {
+                let block_type = BlockType::from_reader(reader)?;
+                let catches = read_vec(reader, TryTableCatch::from_reader)?;
+                Operator::TryTable {
+                    block_type,
+                    catches,
+                }
+            }
+            0x20 => Operator::LocalGet(read_index(reader)?),
+            0x21 => Operator::LocalSet(read_index(reader)?),
+            0x22 => Operator::LocalTee(read_index(reader)?),
+            0x23 => Operator::GlobalGet(read_index(reader)?),
+            0x24 => Operator::GlobalSet(read_index(reader)?),
+            0x25 => Operator::TableGet(read_index(reader)?),
+            0x26 => Operator::TableSet(read_index(reader)?),
+            0x28 => Operator::I32Load(MemArg::from_reader(reader)?),
+            0x29 => Operator::I64Load(MemArg::from_reader(reader)?),
+            0x2a => Operator::F32Load(MemArg::from_reader(reader)?),
+            0x2b => Operator::F64Load(MemArg::from_reader(reader)?),
+            0x2c => Operator::I32Load8S(MemArg::from_reader(reader)?),
+            0x2d => Operator::I32Load8U(MemArg::from_reader(reader)?),
+            0x2e => Operator::I32Load16S(MemArg::from_reader(reader)?),
+            0x2f => Operator::I32Load16U(MemArg::from_reader(reader)?),
+            0x30 => Operator::I64Load8S(MemArg::from_reader(reader)?),
+            0x31 => Operator::I64Load8U(MemArg::from_reader(reader)?),
+            0x32 => Operator::I64Load16S(MemArg::from_reader(reader)?),
+            0x33 => Operator::I64Load16U(MemArg::from_reader(reader)?),
+            0x34 => Operator::I64Load32S(MemArg::from_reader(reader)?),
+            0x35 => Operator::I64Load32U(MemArg::from_reader(reader)?),
+            0x36 => Operator::I32Store(MemArg::from_reader(reader)?),
+            0x37 => Operator::I64Store(MemArg::from_reader(reader)?),
+            0x38 => Operator::F32Store(MemArg::from_reader(reader)?),
+            0x39 => Operator::F64Store(MemArg::from_reader(reader)?),
+            0x3a => Operator::I32Store8(MemArg::from_reader(reader)?),
+            0x3b => Operator::I32Store16(MemArg::from_reader(reader)?),
+            0x3c => Operator::I64Store8(MemArg::from_reader(reader)?),
+            0x3d => Operator::I64Store16(MemArg::from_reader(reader)?),
+            0x3e => Operator::I64Store32(MemArg::from_reader(reader)?),
+            0x3f => Operator::MemorySize(read_index(reader)?),
+            0x40 => Operator::MemoryGrow(read_index(reader)?),
+            0x41 => Operator::I32Const(reader.leb128_signed()?.0 as i32),
+            0x42 => Operator::I64Const(reader.leb128_signed_64()?.0),
+            0x43 => Operator::F32Const(reader.read_f32::<LittleEndian>()?),
+            0x44 => Operator::F64Const(reader.read_f64::<LittleEndian>()?),
+            0x45 => Operator::I32Eqz,
+            0x46 => Operator::I32Eq,
+            0x47 => Operator::I32Ne,
+            0x48 => Operator::I32LtS,
+            0x49 => Operator::I32LtU,
+            0x4a => Operator::I32GtS,
+            0x4b => Operator::I32GtU,
+            0x4c => Operator::I32LeS,
+            0x4d => Operator::I32LeU,
+            0x4e => Operator::I32GeS,
+            0x4f => Operator::I32GeU,
+            0x50 => Operator::I64Eqz,
+            0x51 => Operator::I64Eq,
+            0x52 => Operator::I64Ne,
+            0x53 => Operator::I64LtS,
+            0x54 => Operator::I64LtU,
+            0x55 => Operator::I64GtS,
+            0x56 => Operator::I64GtU,
+            0x57 => Operator::I64LeS,
+            0x58 => Operator::I64LeU,
+            0x59 => Operator::I64GeS,
+            0x5a => Operator::I64GeU,
+            0x5b => Operator::F32Eq,
+            0x5c => Operator::F32Ne,
+            0x5d => Operator::F32Lt,
+            0x5e => Operator::F32Gt,
+            0x5f => Operator::F32Le,
+            0x60 => Operator::F32Ge,
+            0x61 => Operator::F64Eq,
+            0x62 => Operator::F64Ne,
+            0x63 => Operator::F64Lt,
+            0x64 => Operator::F64Gt,
+            0x65 => Operator::F64Le,
+            0x66 => Operator::F64Ge,
+            0x67 => Operator::I32Clz,
+            0x68 => Operator::I32Ctz,
+            0x69 => Operator::I32Popcnt,
+            0x6a => Operator::I32Add,
+            0x6b => Operator::I32Sub,
+            0x6c => Operator::I32Mul,
+            0x6d => Operator::I32DivS,
+            0x6e => Operator::I32DivU,
+            0x6f => Operator::I32RemS,
+            0x70 => Operator::I32RemU,
+            0x71 => Operator::I32And,
+            0x72 => Operator::I32Or,
+            0x73 => Operator::I32Xor,
+            0x74 => Operator::I32Shl,
+            0x75 => Operator::I32ShrS,
+            0x76 => Operator::I32ShrU,
+            0x77 => Operator::I32Rotl,
+            0x78 => Operator::I32Rotr,
+            0x79 => Operator::I64Clz,
+            0x7a => Operator::I64Ctz,
+            0x7b => Operator::I64Popcnt,
+            0x7c => Operator::I64Add,
+            0x7d => Operator::I64Sub,
+            0x7e => Operator::I64Mul,
+            0x7f => Operator::I64DivS,
+            0x80 => Operator::I64DivU,
+            0x81 => Operator::I64RemS,
+            0x82 => Operator::I64RemU,
+            0x83 => Operator::I64And,
+            0x84 => Operator::I64Or,
+            0x85 => Operator::I64Xor,
+            0x86 => Operator::I64Shl,
+            0x87 => Operator::I64ShrS,
+            0x88 => Operator::I64ShrU,
+            0x89 => Operator::I64Rotl,
+            0x8a => Operator::I64Rotr,
+            0x8b => Operator::F32Abs,
+            0x8c => Operator::F32Neg,
+            0x8d => Operator::F32Ceil,
+            0x8e => Operator::F32Floor,
+            0x8f => Operator::F32Trunc,
+            0x90 => Operator::F32Nearest,
+            0x91 => Operator::F32Sqrt,
+            0x92 => Operator::F32Add,
+            0x93 => Operator::F32Sub,
+            0x94 => Operator::F32Mul,
+            0x95 => Operator::F32Div,
+            0x96 => Operator::F32Min,
+            0x97 => Operator::F32Max,
+            0x98 => Operator::F32Copysign,
+            0x99 => Operator::F64Abs,
+            0x9a => Operator::F64Neg,
+            0x9b => Operator::F64Ceil,
+            0x9c => Operator::F64Floor,
+            0x9d => Operator::F64Trunc,
+            0x9e => Operator::F64Nearest,
+            0x9f => Operator::F64Sqrt,
+            0xa0 => Operator::F64Add,
+            0xa1 => Operator::F64Sub,
+            0xa2 => Operator::F64Mul,
+            0xa3 => Operator::F64Div,
+            0xa4 => Operator::F64Min,
+            0xa5 => Operator::F64Max,
+            0xa6 => Operator::F64Copysign,
+            0xa7 => Operator::I32WrapI64,
+            0xa8 => Operator::I32TruncF32S,
+            0xa9 => Operator::I32TruncF32U,
+            0xaa => Operator::I32TruncF64S,
+            0xab => Operator::I32TruncF64U,
+            0xac => Operator::I64ExtendI32S,
+            0xad => Operator::I64ExtendI32U,
+            0xae => Operator::I64TruncF32S,
+            0xaf => Operator::I64TruncF32U,
+            0xb0 => Operator::I64TruncF64S,
+            0xb1 => Operator::I64TruncF64U,
+            0xb2 => Operator::F32ConvertI32S,
+            0xb3 => Operator::F32ConvertI32U,
+            0xb4 => Operator::F32ConvertI64S,
+            0xb5 => Operator::F32ConvertI64U,
+            0xb6 => Operator::F32DemoteF64,
+            0xb7 => Operator::F64ConvertI32S,
+            0xb8 => Operator::F64ConvertI32U,
+            0xb9 => Operator::F64ConvertI64S,
+            0xba => Operator::F64ConvertI64U,
+            0xbb => Operator::F64PromoteF32,
+            0xbc => Operator::I32ReinterpretF32,
+            0xbd => Operator::I64ReinterpretF64,
+            0xbe => Operator::F32ReinterpretI32,
+            0xbf => Operator::F64ReinterpretI64,
+            0xc0 => Operator::I32Extend8S,
+            0xc1 => Operator::I32Extend16S,
+            0xc2 => Operator::I64Extend8S,
+            0xc3 => Operator::I64Extend16S,
+            0xc4 => Operator::I64Extend32S,
+            0xd0 => Operator::RefNull(HeapType::from_reader(reader)?),
+            0xd1 => Operator::RefIsNull,
+            0xd2 => Operator::RefFunc(read_index(reader)?),
+            0xd3 => Operator::RefEq,
+            0xd4 => Operator::RefAsNonNull,
+            0xd5 => Operator::BrOnNull(read_index(reader)?),
+            0xd6 => Operator::BrOnNonNull(read_index(reader)?),
+            0xfb => Operator::from_reader_fb(reader)?,
+            0xfc => Operator::from_reader_fc(reader)?,
+            0xfd => Operator::from_reader_fd(reader, features)?,
+            0xfe => Operator::from_reader_fe(reader)?,
+            _ => return Err(Error::other(format!("Unknown opcode 0x{:02x}", code))),
+        };
+
+        Ok(operator)
+    }
+
+    fn from_reader_fb<T: Read>(reader: &mut T) -> Result<Operator, Error> {
+        let code = read_index(reader)?;
+
+        let operator = match code {
+            0 => Operator::StructNew(read_index(reader)?),
+            1 => Operator::StructNewDefault(read_index(reader)?),
+            2..=5 => {
+                let type_index = read_index(reader)?;
+                let field_index = read_index(reader)?;
+                match code {
+                    2 => Operator::StructGet {
+                        type_index,
+                        field_index,
+                    },
+                    3 => Operator::StructGetS {
+                        type_index,
+                        field_index,
+                    },
+                    4 => Operator::StructGetU {
+                        type_index,
+                        field_index,
+                    },
+                    _ => Operator::StructSet {
+                        type_index,
+                        field_index,
+                    },
+                }
+            }
+            6 => Operator::ArrayNew(read_index(reader)?),
+            7 => Operator::ArrayNewDefault(read_index(reader)?),
+            8 => {
+                let type_index = read_index(reader)?;
+                let size = read_index(reader)?;
+                Operator::ArrayNewFixed { type_index, size }
+            }
+            9 => {
+                let type_index = read_index(reader)?;
+                let data_index = read_index(reader)?;
+                Operator::ArrayNewData {
+                    type_index,
+                    data_index,
+                }
+            }
+            10 => {
+                let type_index = read_index(reader)?;
+                let elem_index = read_index(reader)?;
+                Operator::ArrayNewElem {
+                    type_index,
+                    elem_index,
+                }
+            }
+            11 => Operator::ArrayGet(read_index(reader)?),
+            12 => Operator::ArrayGetS(read_index(reader)?),
+            13 => Operator::ArrayGetU(read_index(reader)?),
+            14 => Operator::ArraySet(read_index(reader)?),
+            15 => Operator::ArrayLen,
+            16 => Operator::ArrayFill(read_index(reader)?),
+            17 => {
+                let dst = read_index(reader)?;
+                let src = read_index(reader)?;
+                Operator::ArrayCopy { dst, src }
+            }
+            18 => {
+                let type_index = read_index(reader)?;
+                let data_index = read_index(reader)?;
+                Operator::ArrayInitData {
+                    type_index,
+                    data_index,
+                }
+            }
+            19 => {
+                let type_index = read_index(reader)?;
+                let elem_index = read_index(reader)?;
+                Operator::ArrayInitElem {
+                    type_index,
+                    elem_index,
+                }
+            }
+            20..=23 => {
+                let ref_type = RefType {
+                    nullable: code & 1 != 0,
+                    heap_type: HeapType::from_reader(reader)?,
+                };
+                if code < 22 {
+                    Operator::RefTest(ref_type)
+                } else {
+                    Operator::RefCast(ref_type)
+                }
+            }
+            24 | 25 => {
+                // Bits 0 and 1 of the flags make the source and target
+                // types nullable.
+                let flags = reader.read_u8()?;
+                if flags > 3 {
+                    return Err(Error::other(format!("Invalid cast flags 0x{:02x}", flags)));
+                }
+                let label = read_index(reader)?;
+                let from = RefType {
+                    nullable: flags & 1 != 0,
+                    heap_type: HeapType::from_reader(reader)?,
+                };
+                let to = RefType {
+                    nullable: flags & 2 != 0,
+                    heap_type: HeapType::from_reader(reader)?,
+                };
+                if code == 24 {
+                    Operator::BrOnCast { label, from, to }
+                } else {
+                    Operator::BrOnCastFail { label, from, to }
+                }
+            }
+            26 => Operator::AnyConvertExtern,
+            27 => Operator::ExternConvertAny,
+            28 => Operator::RefI31,
+            29 => Operator::I31GetS,
+            30 => Operator::I31GetU,
+            _ => return Err(Error::other(format!("Unknown opcode 0xfb 0x{:02x}", code))),
+        };
+
+        Ok(operator)
+    }
+
+    fn from_reader_fc<T: Read>(reader: &mut T) -> Result<Operator, Error> {
+        let code = read_index(reader)?;
+
+        let operator = match code {
+            0 => Operator::I32TruncSatF32S,
+            1 => Operator::I32TruncSatF32U,
+            2 => Operator::I32TruncSatF64S,
+            3 => Operator::I32TruncSatF64U,
+            4 => Operator::I64TruncSatF32S,
+            5 => Operator::I64TruncSatF32U,
+            6 => Operator::I64TruncSatF64S,
+            7 => Operator::I64TruncSatF64U,
+            8 => {
+                let data_index = read_index(reader)?;
+                let memory = read_index(reader)?;
+                Operator::MemoryInit { data_index, memory }
+            }
+            9 => Operator::DataDrop(read_index(reader)?),
+            10 => {
+                let dst = read_index(reader)?;
+                let src = read_index(reader)?;
+                Operator::MemoryCopy { dst, src }
+            }
+            11 => Operator::MemoryFill(read_index(reader)?),
+            12 => {
+                let elem_index = read_index(reader)?;
+                let table = read_index(reader)?;
+                Operator::TableInit { elem_index, table }
+            }
+            13 => Operator::ElemDrop(read_index(reader)?),
+            14 => {
+                let dst = read_index(reader)?;
+                let src = read_index(reader)?;
+                Operator::TableCopy { dst, src }
+            }
+            15 => Operator::TableGrow(read_index(reader)?),
+            16 => Operator::TableSize(read_index(reader)?),
+            17 => Operator::TableFill(read_index(reader)?),
+            _ => return Err(Error::other(format!("Unknown opcode 0xfc 0x{:02x}", code))),
+        };
+
+        Ok(operator)
+    }
+
+    fn from_reader_fd<T: Read>(reader: &mut T, features: &Features) -> Result<Operator, Error> {
+        let code = read_index(reader)?;
+
+        let operator = match code {
+            0x00 => Operator::V128Load(MemArg::from_reader(reader)?),
+            0x01 => Operator::V128Load8x8S(MemArg::from_reader(reader)?),
+            0x02 => Operator::V128Load8x8U(MemArg::from_reader(reader)?),
+            0x03 => Operator::V128Load16x4S(MemArg::from_reader(reader)?),
+            0x04 => Operator::V128Load16x4U(MemArg::from_reader(reader)?),
+            0x05 => Operator::V128Load32x2S(MemArg::from_reader(reader)?),
+            0x06 => Operator::V128Load32x2U(MemArg::from_reader(reader)?),
+            0x07 => Operator::V128Load8Splat(MemArg::from_reader(reader)?),
+            0x08 => Operator::V128Load16Splat(MemArg::from_reader(reader)?),
+            0x09 => Operator::V128Load32Splat(MemArg::from_reader(reader)?),
+            0x0a => Operator::V128Load64Splat(MemArg::from_reader(reader)?),
+            0x0b => Operator::V128Store(MemArg::from_reader(reader)?),
+            0x0c => Operator::V128Const(read_bytes_16(reader)?),
+            0x0d => Operator::I8x16Shuffle(read_bytes_16(reader)?),
+            0x0e => Operator::I8x16Swizzle,
+            0x0f => Operator::I8x16Splat,
+            0x10 => Operator::I16x8Splat,
+            0x11 => Operator::I32x4Splat,
+            0x12 => Operator::I64x2Splat,
+            0x13 => Operator::F32x4Splat,
+            0x14 => Operator::F64x2Splat,
+            0x15 => Operator::I8x16ExtractLaneS(reader.read_u8()?),
+            0x16 => Operator::I8x16ExtractLaneU(reader.read_u8()?),
+            0x17 => Operator::I8x16ReplaceLane(reader.read_u8()?),
+            0x18 => Operator::I16x8ExtractLaneS(reader.read_u8()?),
+            0x19 => Operator::I16x8ExtractLaneU(reader.read_u8()?),
+            0x1a => Operator::I16x8ReplaceLane(reader.read_u8()?),
+            0x1b => Operator::I32x4ExtractLane(reader.read_u8()?),
+            0x1c => Operator::I32x4ReplaceLane(reader.read_u8()?),
+            0x1d => Operator::I64x2ExtractLane(reader.read_u8()?),
+            0x1e => Operator::I64x2ReplaceLane(reader.read_u8()?),
+            0x1f => Operator::F32x4ExtractLane(reader.read_u8()?),
+            0x20 => Operator::F32x4ReplaceLane(reader.read_u8()?),
+            0x21 => Operator::F64x2ExtractLane(reader.read_u8()?),
+            0x22 => Operator::F64x2ReplaceLane(reader.read_u8()?),
+            0x23 => Operator::I8x16Eq,
+            0x24 => Operator::I8x16Ne,
+            0x25 => Operator::I8x16LtS,
+            0x26 => Operator::I8x16LtU,
+            0x27 => Operator::I8x16GtS,
+            0x28 => Operator::I8x16GtU,
+            0x29 => Operator::I8x16LeS,
+            0x2a => Operator::I8x16LeU,
+            0x2b => Operator::I8x16GeS,
+            0x2c => Operator::I8x16GeU,
+            0x2d => Operator::I16x8Eq,
+            0x2e => Operator::I16x8Ne,
+            0x2f => Operator::I16x8LtS,
+            0x30 => Operator::I16x8LtU,
+            0x31 => Operator::I16x8GtS,
+            0x32 => Operator::I16x8GtU,
+            0x33 => Operator::I16x8LeS,
+            0x34 => Operator::I16x8LeU,
+            0x35 => Operator::I16x8GeS,
+            0x36 => Operator::I16x8GeU,
+            0x37 => Operator::I32x4Eq,
+            0x38 => Operator::I32x4Ne,
+            0x39 => Operator::I32x4LtS,
+            0x3a => Operator::I32x4LtU,
+            0x3b => Operator::I32x4GtS,
+            0x3c => Operator::I32x4GtU,
+            0x3d => Operator::I32x4LeS,
+            0x3e => Operator::I32x4LeU,
+            0x3f => Operator::I32x4GeS,
+            0x40 => Operator::I32x4GeU,
+            0x41 => Operator::F32x4Eq,
+            0x42 => Operator::F32x4Ne,
+            0x43 => Operator::F32x4Lt,
+            0x44 => Operator::F32x4Gt,
+            0x45 => Operator::F32x4Le,
+            0x46 => Operator::F32x4Ge,
+            0x47 => Operator::F64x2Eq,
+            0x48 => Operator::F64x2Ne,
+            0x49 => Operator::F64x2Lt,
+            0x4a => Operator::F64x2Gt,
+            0x4b => Operator::F64x2Le,
+            0x4c => Operator::F64x2Ge,
+            0x4d => Operator::V128Not,
+            0x4e => Operator::V128And,
+            0x4f => Operator::V128Andnot,
+            0x50 => Operator::V128Or,
+            0x51 => Operator::V128Xor,
+            0x52 => Operator::V128Bitselect,
+            0x53 => Operator::V128AnyTrue,
+            0x54 => {
+                let memarg = MemArg::from_reader(reader)?;
+                let lane = reader.read_u8()?;
+                Operator::V128Load8Lane { memarg, lane }
+            }
+            0x55 => {
+                let memarg = MemArg::from_reader(reader)?;
+                let lane = reader.read_u8()?;
+                Operator::V128Load16Lane { memarg, lane }
+            }
+            0x56 => {
+                let memarg = MemArg::from_reader(reader)?;
+                let lane = reader.read_u8()?;
+                Operator::V128Load32Lane { memarg, lane }
+            }
+            0x57 => {
+                let memarg = MemArg::from_reader(reader)?;
+                let lane = reader.read_u8()?;
+                Operator::V128Load64Lane { memarg, lane }
+            }
+            0x58 => {
+                let memarg = MemArg::from_reader(reader)?;
+                let lane = reader.read_u8()?;
+                Operator::V128Store8Lane { memarg, lane }
+            }
+            0x59 => {
+                let memarg = MemArg::from_reader(reader)?;
+                let lane = reader.read_u8()?;
+                Operator::V128Store16Lane { memarg, lane }
+            }
+            0x5a => {
+                let memarg = MemArg::from_reader(reader)?;
+                let lane = reader.read_u8()?;
+                Operator::V128Store32Lane { memarg, lane }
+            }
+            0x5b => {
+                let memarg = MemArg::from_reader(reader)?;
+                let lane = reader.read_u8()?;
+                Operator::V128Store64Lane { memarg, lane }
+            }
+            0x5c => Operator::V128Load32Zero(MemArg::from_reader(reader)?),
+            0x5d => Operator::V128Load64Zero(MemArg::from_reader(reader)?),
+            0x5e => Operator::F32x4DemoteF64x2Zero,
+            0x5f => Operator::F64x2PromoteLowF32x4,
+            0x60 => Operator::I8x16Abs,
+            0x61 => Operator::I8x16Neg,
+            0x62 => Operator::I8x16Popcnt,
+            0x63 => Operator::I8x16AllTrue,
+            0x64 => Operator::I8x16Bitmask,
+            0x65 => Operator::I8x16NarrowI16x8S,
+            0x66 => Operator::I8x16NarrowI16x8U,
+            0x67 => Operator::F32x4Ceil,
+            0x68 => Operator::F32x4Floor,
+            0x69 => Operator::F32x4Trunc,
+            0x6a => Operator::F32x4Nearest,
+            0x6b => Operator::I8x16Shl,
+            0x6c => Operator::I8x16ShrS,
+            0x6d => Operator::I8x16ShrU,
+            0x6e => Operator::I8x16Add,
+            0x6f => Operator::I8x16AddSatS,
+            0x70 => Operator::I8x16AddSatU,
+            0x71 => Operator::I8x16Sub,
+            0x72 => Operator::I8x16SubSatS,
+            0x73 => Operator::I8x16SubSatU,
+            0x74 => Operator::F64x2Ceil,
+            0x75 => Operator::F64x2Floor,
+            0x76 => Operator::I8x16MinS,
+            0x77 => Operator::I8x16MinU,
+            0x78 => Operator::I8x16MaxS,
+            0x79 => Operator::I8x16MaxU,
+            0x7a => Operator::F64x2Trunc,
+            0x7b => Operator::I8x16AvgrU,
+            0x7c => Operator::I16x8ExtaddPairwiseI8x16S,
+            0x7d => Operator::I16x8ExtaddPairwiseI8x16U,
+            0x7e => Operator::I32x4ExtaddPairwiseI16x8S,
+            0x7f => Operator::I32x4ExtaddPairwiseI16x8U,
+            0x80 => Operator::I16x8Abs,
+            0x81 => Operator::I16x8Neg,
+            0x82 => Operator::I16x8Q15mulrSatS,
+            0x83 => Operator::I16x8AllTrue,
+            0x84 => Operator::I16x8Bitmask,
+            0x85 => Operator::I16x8NarrowI32x4S,
+            0x86 => Operator::I16x8NarrowI32x4U,
+            0x87 => Operator::I16x8ExtendLowI8x16S,
+            0x88 => Operator::I16x8ExtendHighI8x16S,
+            0x89 => Operator::I16x8ExtendLowI8x16U,
+            0x8a => Operator::I16x8ExtendHighI8x16U,
+            0x8b => Operator::I16x8Shl,
+            0x8c => Operator::I16x8ShrS,
+            0x8d => Operator::I16x8ShrU,
+            0x8e => Operator::I16x8Add,
+            0x8f => Operator::I16x8AddSatS,
+            0x90 => Operator::I16x8AddSatU,
+            0x91 => Operator::I16x8Sub,
+            0x92 => Operator::I16x8SubSatS,
+            0x93 => Operator::I16x8SubSatU,
+            0x94 => Operator::F64x2Nearest,
+            0x95 => Operator::I16x8Mul,
+            0x96 => Operator::I16x8MinS,
+            0x97 => Operator::I16x8MinU,
+            0x98 => Operator::I16x8MaxS,
+            0x99 => Operator::I16x8MaxU,
+            0x9b => Operator::I16x8AvgrU,
+            0x9c => Operator::I16x8ExtmulLowI8x16S,
+            0x9d => Operator::I16x8ExtmulHighI8x16S,
+            0x9e => Operator::I16x8ExtmulLowI8x16U,
+            0x9f => Operator::I16x8ExtmulHighI8x16U,
+            0xa0 => Operator::I32x4Abs,
+            0xa1 => Operator::I32x4Neg,
+            0xa3 => Operator::I32x4AllTrue,
+            0xa4 => Operator::I32x4Bitmask,
+            0xa7 => Operator::I32x4ExtendLowI16x8S,
+            0xa8 => Operator::I32x4ExtendHighI16x8S,
+            0xa9 => Operator::I32x4ExtendLowI16x8U,
+            0xaa => Operator::I32x4ExtendHighI16x8U,
+            0xab => Operator::I32x4Shl,
+            0xac => Operator::I32x4ShrS,
+            0xad => Operator::I32x4ShrU,
+            0xae => Operator::I32x4Add,
+            0xb1 => Operator::I32x4Sub,
+            0xb5 => Operator::I32x4Mul,
+            0xb6 => Operator::I32x4MinS,
+            0xb7 => Operator::I32x4MinU,
+            0xb8 => Operator::I32x4MaxS,
+            0xb9 => Operator::I32x4MaxU,
+            0xba => Operator::I32x4DotI16x8S,
+            0xbc => Operator::I32x4ExtmulLowI16x8S,
+            0xbd => Operator::I32x4ExtmulHighI16x8S,
+            0xbe => Operator::I32x4ExtmulLowI16x8U,
+            0xbf => Operator::I32x4ExtmulHighI16x8U,
+            0xc0 => Operator::I64x2Abs,
+            0xc1 => Operator::I64x2Neg,
+            0xc3 => Operator::I64x2AllTrue,
+            0xc4 => Operator::I64x2Bitmask,
+            0xc7 => Operator::I64x2ExtendLowI32x4S,
+            0xc8 => Operator::I64x2ExtendHighI32x4S,
+            0xc9 => Operator::I64x2ExtendLowI32x4U,
+            0xca => Operator::I64x2ExtendHighI32x4U,
+            0xcb => Operator::I64x2Shl,
+            0xcc => Operator::I64x2ShrS,
+            0xcd => Operator::I64x2ShrU,
+            0xce => Operator::I64x2Add,
+            0xd1 => Operator::I64x2Sub,
+            0xd5 => Operator::I64x2Mul,
+            0xd6 => Operator::I64x2Eq,
+            0xd7 => Operator::I64x2Ne,
+            0xd8 => Operator::I64x2LtS,
+            0xd9 => Operator::I64x2GtS,
+            0xda => Operator::I64x2LeS,
+            0xdb => Operator::I64x2GeS,
+            0xdc => Operator::I64x2ExtmulLowI32x4S,
+            0xdd => Operator::I64x2ExtmulHighI32x4S,
+            0xde => Operator::I64x2ExtmulLowI32x4U,
+            0xdf => Operator::I64x2ExtmulHighI32x4U,
+            0xe0 => Operator::F32x4Abs,
+            0xe1 => Operator::F32x4Neg,
+            0xe3 => Operator::F32x4Sqrt,
+            0xe4 => Operator::F32x4Add,
+            0xe5 => Operator::F32x4Sub,
+            0xe6 => Operator::F32x4Mul,
+            0xe7 => Operator::F32x4Div,
+            0xe8 => Operator::F32x4Min,
+            0xe9 => Operator::F32x4Max,
+            0xea => Operator::F32x4Pmin,
+            0xeb => Operator::F32x4Pmax,
+            0xec => Operator::F64x2Abs,
+            0xed => Operator::F64x2Neg,
+            0xef => Operator::F64x2Sqrt,
+            0xf0 => Operator::F64x2Add,
+            0xf1 => Operator::F64x2Sub,
+            0xf2 => Operator::F64x2Mul,
+            0xf3 => Operator::F64x2Div,
+            0xf4 => Operator::F64x2Min,
+            0xf5 => Operator::F64x2Max,
+            0xf6 => Operator::F64x2Pmin,
+            0xf7 => Operator::F64x2Pmax,
+            0xf8 => Operator::I32x4TruncSatF32x4S,
+            0xf9 => Operator::I32x4TruncSatF32x4U,
+            0xfa => Operator::F32x4ConvertI32x4S,
+            0xfb => Operator::F32x4ConvertI32x4U,
+            0xfc => Operator::I32x4TruncSatF64x2SZero,
+            0xfd => Operator::I32x4TruncSatF64x2UZero,
+            0xfe => Operator::F64x2ConvertLowI32x4S,
+            0xff => Operator::F64x2ConvertLowI32x4U,
+            0x100..=0x113 if !features.relaxed_simd => {
+                return Err(Error::other(format!(
+                    "Relaxed SIMD opcode 0xfd 0x{:02x} is not enabled",
+                    code
+                )))
+            }
+            0x100 => Operator::I8x16RelaxedSwizzle,
+            0x101 => Operator::I32x4RelaxedTruncF32x4S,
+            0x102 => Operator::I32x4RelaxedTruncF32x4U,
+            0x103 => Operator::I32x4RelaxedTruncF64x2SZero,
+            0x104 => Operator::I32x4RelaxedTruncF64x2UZero,
+            0x105 => Operator::F32x4RelaxedMadd,
+            0x106 => Operator::F32x4RelaxedNmadd,
+            0x107 => Operator::F64x2RelaxedMadd,
+            0x108 => Operator::F64x2RelaxedNmadd,
+            0x109 => Operator::I8x16RelaxedLaneselect,
+            0x10a => Operator::I16x8RelaxedLaneselect,
+            0x10b => Operator::I32x4RelaxedLaneselect,
+            0x10c => Operator::I64x2RelaxedLaneselect,
+            0x10d => Operator::F32x4RelaxedMin,
+            0x10e => Operator::F32x4RelaxedMax,
+            0x10f => Operator::F64x2RelaxedMin,
+            0x110 => Operator::F64x2RelaxedMax,
+            0x111 => Operator::I16x8RelaxedQ15mulrS,
+            0x112 => Operator::I16x8RelaxedDotI8x16I7x16S,
+            0x113 => Operator::I32x4RelaxedDotI8x16I7x16AddS,
+            _ => return Err(Error::other(format!("Unknown opcode 0xfd 0x{:02x}", code))),
+        };
+
+        Ok(operator)
+    }
+
+    fn from_reader_fe<T: Read>(reader: &mut T) -> Result<Operator, Error> {
+        let code = read_index(reader)?;
+
+        let operator = match code {
+            0x00 => Operator::MemoryAtomicNotify(MemArg::from_reader(reader)?),
+            0x01 => Operator::MemoryAtomicWait32(MemArg::from_reader(reader)?),
+            0x02 => Operator::MemoryAtomicWait64(MemArg::from_reader(reader)?),
+            0x03 => {
+                if reader.read_u8()? != 0 {
+                    return Err(Error::other("atomic.fence must have a zero flags byte"));
+                }
+                Operator::AtomicFence
+            }
+            0x10 => Operator::I32AtomicLoad(MemArg::from_reader(reader)?),
+            0x11 => Operator::I64AtomicLoad(MemArg::from_reader(reader)?),
+            0x12 => Operator::I32AtomicLoad8U(MemArg::from_reader(reader)?),
+            0x13 => Operator::I32AtomicLoad16U(MemArg::from_reader(reader)?),
+            0x14 => Operator::I64AtomicLoad8U(MemArg::from_reader(reader)?),
+            0x15 => Operator::I64AtomicLoad16U(MemArg::from_reader(reader)?),
+            0x16 => Operator::I64AtomicLoad32U(MemArg::from_reader(reader)?),
+            0x17 => Operator::I32AtomicStore(MemArg::from_reader(reader)?),
+            0x18 => Operator::I64AtomicStore(MemArg::from_reader(reader)?),
+            0x19 => Operator::I32AtomicStore8(MemArg::from_reader(reader)?),
+            0x1a => Operator::I32AtomicStore16(MemArg::from_reader(reader)?),
+            0x1b => Operator::I64AtomicStore8(MemArg::from_reader(reader)?),
+            0x1c => Operator::I64AtomicStore16(MemArg::from_reader(reader)?),
+            0x1d => Operator::I64AtomicStore32(MemArg::from_reader(reader)?),
+            0x1e => Operator::I32AtomicRmwAdd(MemArg::from_reader(reader)?),
+            0x1f => Operator::I64AtomicRmwAdd(MemArg::from_reader(reader)?),
+            0x20 => Operator::I32AtomicRmw8AddU(MemArg::from_reader(reader)?),
+            0x21 => Operator::I32AtomicRmw16AddU(MemArg::from_reader(reader)?),
+            0x22 => Operator::I64AtomicRmw8AddU(MemArg::from_reader(reader)?),
+            0x23 => Operator::I64AtomicRmw16AddU(MemArg::from_reader(reader)?),
+            0x24 => Operator::I64AtomicRmw32AddU(MemArg::from_reader(reader)?),
+            0x25 => Operator::I32AtomicRmwSub(MemArg::from_reader(reader)?),
+            0x26 => Operator::I64AtomicRmwSub(MemArg::from_reader(reader)?),
+            0x27 => Operator::I32AtomicRmw8SubU(MemArg::from_reader(reader)?),
+            0x28 => Operator::I32AtomicRmw16SubU(MemArg::from_reader(reader)?),
+            0x29 => Operator::I64AtomicRmw8SubU(MemArg::from_reader(reader)?),
+            0x2a => Operator::I64AtomicRmw16SubU(MemArg::from_reader(reader)?),
+            0x2b => Operator::I64AtomicRmw32SubU(MemArg::from_reader(reader)?),
+            0x2c => Operator::I32AtomicRmwAnd(MemArg::from_reader(reader)?),
+            0x2d => Operator::I64AtomicRmwAnd(MemArg::from_reader(reader)?),
+            0x2e => Operator::I32AtomicRmw8AndU(MemArg::from_reader(reader)?),
+            0x2f => Operator::I32AtomicRmw16AndU(MemArg::from_reader(reader)?),
+            0x30 => Operator::I64AtomicRmw8AndU(MemArg::from_reader(reader)?),
+            0x31 => Operator::I64AtomicRmw16AndU(MemArg::from_reader(reader)?),
+            0x32 => Operator::I64AtomicRmw32AndU(MemArg::from_reader(reader)?),
+            0x33 => Operator::I32AtomicRmwOr(MemArg::from_reader(reader)?),
+            0x34 => Operator::I64AtomicRmwOr(MemArg::from_reader(reader)?),
+            0x35 => Operator::I32AtomicRmw8OrU(MemArg::from_reader(reader)?),
+            0x36 => Operator::I32AtomicRmw16OrU(MemArg::from_reader(reader)?),
+            0x37 => Operator::I64AtomicRmw8OrU(MemArg::from_reader(reader)?),
+            0x38 => Operator::I64AtomicRmw16OrU(MemArg::from_reader(reader)?),
+            0x39 => Operator::I64AtomicRmw32OrU(MemArg::from_reader(reader)?),
+            0x3a => Operator::I32AtomicRmwXor(MemArg::from_reader(reader)?),
+            0x3b => Operator::I64AtomicRmwXor(MemArg::from_reader(reader)?),
+            0x3c => Operator::I32AtomicRmw8XorU(MemArg::from_reader(reader)?),
+            0x3d => Operator::I32AtomicRmw16XorU(MemArg::from_reader(reader)?),
+            0x3e => Operator::I64AtomicRmw8XorU(MemArg::from_reader(reader)?),
+            0x3f => Operator::I64AtomicRmw16XorU(MemArg::from_reader(reader)?),
+            0x40 => Operator::I64AtomicRmw32XorU(MemArg::from_reader(reader)?),
+            0x41 => Operator::I32AtomicRmwXchg(MemArg::from_reader(reader)?),
+            0x42 => Operator::I64AtomicRmwXchg(MemArg::from_reader(reader)?),
+            0x43 => Operator::I32AtomicRmw8XchgU(MemArg::from_reader(reader)?),
+            0x44 => Operator::I32AtomicRmw16XchgU(MemArg::from_reader(reader)?),
+            0x45 => Operator::I64AtomicRmw8XchgU(MemArg::from_reader(reader)?),
+            0x46 => Operator::I64AtomicRmw16XchgU(MemArg::from_reader(reader)?),
+            0x47 => Operator::I64AtomicRmw32XchgU(MemArg::from_reader(reader)?),
+            0x48 => Operator::I32AtomicRmwCmpxchg(MemArg::from_reader(reader)?),
+            0x49 => Operator::I64AtomicRmwCmpxchg(MemArg::from_reader(reader)?),
+            0x4a => Operator::I32AtomicRmw8CmpxchgU(MemArg::from_reader(reader)?),
+            0x4b => Operator::I32AtomicRmw16CmpxchgU(MemArg::from_reader(reader)?),
+            0x4c => Operator::I64AtomicRmw8CmpxchgU(MemArg::from_reader(reader)?),
+            0x4d => Operator::I64AtomicRmw16CmpxchgU(MemArg::from_reader(reader)?),
+            0x4e => Operator::I64AtomicRmw32CmpxchgU(MemArg::from_reader(reader)?),
+            _ => return Err(Error::other(format!("Unknown opcode 0xfe 0x{:02x}", code))),
+        };
+
+        Ok(operator)
+    }
+}
+
+fn read_bytes_16<T: Read>(reader: &mut T) -> Result<[u8; 16], Error> {
+    let mut bytes = [0; 16];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::io::Cursor;
+
+    fn decode_with_features(bytes: &[u8], features: &Features) -> Result<Vec<Operator>, Error> {
+        let mut reader = Cursor::new(bytes);
+        let mut operators = Vec::new();
+
+        while (reader.position() as usize) < bytes.len() {
+            operators.push(Operator::from_reader_with_features(&mut reader, features)?);
+        }
+
+        Ok(operators)
+    }
+
+    fn decode(bytes: &[u8]) -> Vec<Operator> {
+        decode_with_features(bytes, &Features::default()).unwrap()
+    }
+
+    #[test]
+    fn test_reference_and_table_operators() {
+        #[rustfmt::skip]
+        let bytes = [
+            0x1c, 0x01, 0x6f, // select (result externref)
+            0x1c, 0x02, 0x7f, 0x7e, // select (result i32 i64)
+            0x25, 0x01, // table.get 1
+            0x26, 0x02, // table.set 2
+            0xd0, 0x70, // ref.null func
+            0xd0, 0x6f, // ref.null extern
+            0xd1, // ref.is_null
+            0xd2, 0x03, // ref.func 3
+            0xfc, 0x0c, 0x04, 0x01, // table.init 1 4
+            0xfc, 0x0d, 0x04, // elem.drop 4
+            0xfc, 0x0e, 0x01, 0x02, // table.copy 1 2
+            0xfc, 0x0f, 0x01, // table.grow 1
+            0xfc, 0x10, 0x02, // table.size 2
+            0xfc, 0x11, 0x03, // table.fill 3
+        ];
+
+        assert_eq!(
+            decode(&bytes),
+            vec![
+                Operator::TypedSelect(vec![ValueType::EXTERNREF]),
+                Operator::TypedSelect(vec![ValueType::Integer32, ValueType::Integer64]),
+                Operator::TableGet(1),
+                Operator::TableSet(2),
+                Operator::RefNull(HeapType::Func),
+                Operator::RefNull(HeapType::Extern),
+                Operator::RefIsNull,
+                Operator::RefFunc(3),
+                Operator::TableInit {
+                    elem_index: 4,
+                    table: 1
+                },
+                Operator::ElemDrop(4),
+                Operator::TableCopy { dst: 1, src: 2 },
+                Operator::TableGrow(1),
+                Operator::TableSize(2),
+                Operator::TableFill(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bulk_memory_operators() {
+        #[rustfmt::skip]
+        let bytes = [
+            0xfc, 0x08, 0x02, 0x00, // memory.init 2 0
+            0xfc, 0x09, 0x03, // data.drop 3
+            0xfc, 0x0a, 0x01, 0x00, // memory.copy 1 0
+            0xfc, 0x0b, 0x01, // memory.fill 1
+        ];
+
+        assert_eq!(
+            decode(&bytes),
+            vec![
+                Operator::MemoryInit {
+                    data_index: 2,
+                    memory: 0
+                },
+                Operator::DataDrop(3),
+                Operator::MemoryCopy { dst: 1, src: 0 },
+                Operator::MemoryFill(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_simd_operators() {
+        let memarg = |align, offset| MemArg {
+            align,
+            memory: 0,
+            offset,
+        };
+        let lanes = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+        #[rustfmt::skip]
+        let mut bytes = vec![
+            0xfd, 0x00, 0x04, 0x10, // v128.load align=16 offset=16
+            0xfd, 0x0b, 0x03, 0x00, // v128.store align=8
+            0xfd, 0x15, 0x0f, // i8x16.extract_lane_s 15
+            0xfd, 0x17, 0x03, // i8x16.replace_lane 3
+            0xfd, 0x54, 0x00, 0x08, 0x07, // v128.load8_lane offset=8 7
+            0xfd, 0x0d, // i8x16.shuffle
+        ];
+        bytes.extend(&lanes);
+        // i32x4.add has a two-byte opcode.
+        bytes.extend(&[0xfd, 0xae, 0x01]);
+
+        assert_eq!(
+            decode(&bytes),
+            vec![
+                Operator::V128Load(memarg(4, 16)),
+                Operator::V128Store(memarg(3, 0)),
+                Operator::I8x16ExtractLaneS(15),
+                Operator::I8x16ReplaceLane(3),
+                Operator::V128Load8Lane {
+                    memarg: memarg(0, 8),
+                    lane: 7
+                },
+                Operator::I8x16Shuffle(lanes),
+                Operator::I32x4Add,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relaxed_simd_requires_feature() {
+        // i8x16.relaxed_swizzle and i32x4.relaxed_dot_i8x16_i7x16_add_s
+        let bytes = [0xfd, 0x80, 0x02, 0xfd, 0x93, 0x02];
+        let relaxed = Features { relaxed_simd: true };
+
+        assert!(decode_with_features(&bytes[..3], &Features::default()).is_err());
+        assert!(decode_with_features(&bytes[3..], &Features::default()).is_err());
+        assert_eq!(
+            decode_with_features(&bytes, &relaxed).unwrap(),
+            vec![
+                Operator::I8x16RelaxedSwizzle,
+                Operator::I32x4RelaxedDotI8x16I7x16AddS,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exception_operators() {
+        #[rustfmt::skip]
+        let bytes = [
+            0x06, 0x40, // try
+            0x08, 0x00, // throw 0
+            0x07, 0x00, // catch 0
+            0x09, 0x00, // rethrow 0
+            0x19, // catch_all
+            0x0b, // end
+            0x06, 0x7f, // try (result i32)
+            0x18, 0x01, // delegate 1
+            0x1f, 0x40, 0x04, // try_table with four handlers
+            0x00, 0x01, 0x00, // catch 1 0
+            0x01, 0x02, 0x01, // catch_ref 2 1
+            0x02, 0x02, // catch_all 2
+            0x03, 0x03, // catch_all_ref 3
+            0x0a, // throw_ref
+            0x0b, // end
+        ];
+
+        assert_eq!(
+            decode(&bytes),
+            vec![
+                Operator::Try(BlockType::Empty),
+                Operator::Throw(0),
+                Operator::Catch(0),
+                Operator::Rethrow(0),
+                Operator::CatchAll,
+                Operator::End,
+                Operator::Try(BlockType::Value(ValueType::Integer32)),
+                Operator::Delegate(1),
+                Operator::TryTable {
+                    block_type: BlockType::Empty,
+                    catches: vec![
+                        TryTableCatch::Catch { tag: 1, label: 0 },
+                        TryTableCatch::CatchRef { tag: 2, label: 1 },
+                        TryTableCatch::CatchAll { label: 2 },
+                        TryTableCatch::CatchAllRef { label: 3 },
+                    ],
+                },
+                Operator::ThrowRef,
+                Operator::End,
+            ]
+        );
+        assert!(Operator::from_reader(&mut &[0x1f, 0x40, 0x01, 0x04, 0x00][..]).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+use byteorder::ReadBytesExt;
+use leb128::ReadLeb128Ext;
+use std::io::{Error, Read};
+
+/// A `reloc.*` custom section. `section_index` is the position of the
+/// section the relocations apply to within the module.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RelocSection {
+    pub section_index: u32,
+    pub count: u32,
+    pub entries: Vec<RelocEntry>,
+}
+
+impl RelocSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<RelocSection, Error> {
+        let (section_index, _) = reader.leb128_unsigned()?;
+        let (count, _) = reader.leb128_unsigned()?;
+
+        let mut entries = Vec::new();
+
+        for _ in 0..count {
+            let entry = RelocEntry::from_reader(reader)?;
+            entries.push(entry);
+        }
+
+        Ok(RelocSection {
+            section_index: section_index as u32,
+            count: count as u32,
+            entries,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RelocEntry {
+    pub reloc_type: RelocType,
+    pub offset: u32,
+    pub index: u32,
+    pub addend: Option<i64>,
+}
+
+impl RelocEntry {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<RelocEntry, Error> {
+        let reloc_type = RelocType::from_u8(reader.read_u8()?)?;
+        let (offset, _) = reader.leb128_unsigned()?;
+        let (index, _) = reader.leb128_unsigned()?;
+
+        let addend = if !reloc_type.has_addend() {
+            None
+        } else if reloc_type.is_64() {
+            Some(reader.leb128_signed_64()?.0)
+        } else {
+            Some(reader.leb128_signed()?.0)
+        };
+
+        Ok(RelocEntry {
+            reloc_type,
+            offset: offset as u32,
+            index: index as u32,
+            addend,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum RelocType {
+    FunctionIndexLeb,
+    TableIndexSleb,
+    TableIndexI32,
+    MemoryAddrLeb,
+    MemoryAddrSleb,
+    MemoryAddrI32,
+    TypeIndexLeb,
+    GlobalIndexLeb,
+    FunctionOffsetI32,
+    SectionOffsetI32,
+    TagIndexLeb,
+    MemoryAddrRelSleb,
+    TableIndexRelSleb,
+    GlobalIndexI32,
+    MemoryAddrLeb64,
+    MemoryAddrSleb64,
+    MemoryAddrI64,
+    MemoryAddrRelSleb64,
+    TableIndexSleb64,
+    TableIndexI64,
+    TableNumberLeb,
+    MemoryAddrTlsSleb,
+    FunctionOffsetI64,
+    MemoryAddrLocrelI32,
+    TableIndexRelSleb64,
+    MemoryAddrTlsSleb64,
+    FunctionIndexI32,
+}
+
+impl RelocType {
+    pub fn from_u8(num: u8) -> Result<RelocType, Error> {
+        match num {
+            0 => Ok(RelocType::FunctionIndexLeb),
+            1 => Ok(RelocType::TableIndexSleb),
+            2 => Ok(RelocType::TableIndexI32),
+            3 => Ok(RelocType::MemoryAddrLeb),
+            4 => Ok(RelocType::MemoryAddrSleb),
+            5 => Ok(RelocType::MemoryAddrI32),
+            6 => Ok(RelocType::TypeIndexLeb),
+            7 => Ok(RelocType::GlobalIndexLeb),
+            8 => Ok(RelocType::FunctionOffsetI32),
+            9 => Ok(RelocType::SectionOffsetI32),
+            10 => Ok(RelocType::TagIndexLeb),
+            11 => Ok(RelocType::MemoryAddrRelSleb),
+            12 => Ok(RelocType::TableIndexRelSleb),
+            13 => Ok(RelocType::GlobalIndexI32),
+            14 => Ok(RelocType::MemoryAddrLeb64),
+            15 => Ok(RelocType::MemoryAddrSleb64),
+            16 => Ok(RelocType::MemoryAddrI64),
+            17 => Ok(RelocType::MemoryAddrRelSleb64),
+            18 => Ok(RelocType::TableIndexSleb64),
+            19 => Ok(RelocType::TableIndexI64),
+            20 => Ok(RelocType::TableNumberLeb),
+            21 => Ok(RelocType::MemoryAddrTlsSleb),
+            22 => Ok(RelocType::FunctionOffsetI64),
+            23 => Ok(RelocType::MemoryAddrLocrelI32),
+            24 => Ok(RelocType::TableIndexRelSleb64),
+            25 => Ok(RelocType::MemoryAddrTlsSleb64),
+            26 => Ok(RelocType::FunctionIndexI32),
+            _ => Err(Error::other("Unknown Relocation Type")),
+        }
+    }
+
+    /// Whether entries of this type carry a signed addend after the index.
+    pub fn has_addend(self) -> bool {
+        matches!(
+            self,
+            RelocType::MemoryAddrLeb
+                | RelocType::MemoryAddrSleb
+                | RelocType::MemoryAddrI32
+                | RelocType::FunctionOffsetI32
+                | RelocType::SectionOffsetI32
+                | RelocType::MemoryAddrRelSleb
+                | RelocType::MemoryAddrLeb64
+                | RelocType::MemoryAddrSleb64
+                | RelocType::MemoryAddrI64
+                | RelocType::MemoryAddrRelSleb64
+                | RelocType::MemoryAddrTlsSleb
+                | RelocType::FunctionOffsetI64
+                | RelocType::MemoryAddrLocrelI32
+                | RelocType::MemoryAddrTlsSleb64
+        )
+    }
+
+    pub fn is_64(self) -> bool {
+        matches!(
+            self,
+            RelocType::MemoryAddrLeb64
+                | RelocType::MemoryAddrSleb64
+                | RelocType::MemoryAddrI64
+                | RelocType::MemoryAddrRelSleb64
+                | RelocType::TableIndexSleb64
+                | RelocType::TableIndexI64
+                | RelocType::FunctionOffsetI64
+                | RelocType::TableIndexRelSleb64
+                | RelocType::MemoryAddrTlsSleb64
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::*;
+    use types::WasmSectionBody;
+
+    #[test]
+    fn test_reloc_code_section() {
+        let relocs = custom(
+            "reloc.CODE",
+            &[
+                0x02, 0x03, // section 2, three entries
+                0x00, 0x04, 0x00, // R_WASM_FUNCTION_INDEX_LEB
+                0x04, 0x0a, 0x01, 0x78, // R_WASM_MEMORY_ADDR_SLEB - 8
+                0x0f, 0x14, 0x01, 0x80, 0x80, 0x80, 0x80, 0x80, 0x20, // ..._SLEB64 + 2^40
+            ],
+        );
+        let module = module(&[
+            (TYPE, &[0x01, 0x60, 0x00, 0x00]),
+            (FUNCTION, &[0x01, 0x00]),
+            (CODE, &code(&[&[0x00, 0x0b]])),
+            (CUSTOM, &relocs),
+        ]);
+        let reloc = match module.sections[3].body {
+            WasmSectionBody::Reloc(ref reloc) => reloc,
+            ref body => panic!("Expected a reloc section, got {:?}", body),
+        };
+
+        assert_eq!(
+            reloc.entries,
+            vec![
+                RelocEntry {
+                    reloc_type: RelocType::FunctionIndexLeb,
+                    offset: 4,
+                    index: 0,
+                    addend: None,
+                },
+                RelocEntry {
+                    reloc_type: RelocType::MemoryAddrSleb,
+                    offset: 10,
+                    index: 1,
+                    addend: Some(-8),
+                },
+                RelocEntry {
+                    reloc_type: RelocType::MemoryAddrSleb64,
+                    offset: 20,
+                    index: 1,
+                    addend: Some(1 << 40),
+                },
+            ]
+        );
+        match module.reloc_target(reloc).map(|section| &section.body) {
+            Some(&WasmSectionBody::Code(_)) => {}
+            target => panic!("Expected the code section, got {:?}", target),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::io::{Error, Read};
+
+use super::*;
+
+/// The `sourceMappingURL` custom section, pointing at the module's source map.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SourceMappingUrlSection {
+    pub url: String,
+}
+
+impl SourceMappingUrlSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<SourceMappingUrlSection, Error> {
+        let url = read_name(reader)?;
+        Ok(SourceMappingUrlSection { url })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::*;
+
+    #[test]
+    fn test_source_mapping_url_section() {
+        let module = module(&[(CUSTOM, &custom("sourceMappingURL", b"\x0dmain.wasm.map"))]);
+
+        match module.sections[0].body {
+            WasmSectionBody::SourceMappingUrl(ref section) => {
+                assert_eq!(section.url, "main.wasm.map")
+            }
+            ref body => panic!("Expected a sourceMappingURL section, got {:?}", body),
+        }
+    }
+}
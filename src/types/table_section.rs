@@ -1,3 +1,4 @@
+use byteorder::ReadBytesExt;
 use leb128::ReadLeb128Ext;
 use std::io::{Error, Read};
 
@@ -6,7 +7,7 @@ use super::*;
 #[derive(Debug, PartialEq, Serialize)]
 pub struct TableSection {
     pub count: u32,
-    pub entries: Vec<TableType>,
+    pub entries: Vec<TableEntry>,
 }
 
 impl TableSection {
@@ -16,16 +17,7 @@ impl TableSection {
         let mut entries = Vec::new();
 
         for _ in 0..count {
-            let (element_type_num, _) = reader.leb128_signed()?;
-            let element_type = ElementType::from_i64(element_type_num)?;
-
-            let limits = ResizableLimits::from_reader(reader)?;
-
-            let entry = TableType {
-                element_type,
-                limits,
-            };
-
+            let entry = TableEntry::from_reader(reader)?;
             entries.push(entry);
         }
 
@@ -36,22 +28,69 @@ impl TableSection {
     }
 }
 
+/// A defined table. Tables of non-nullable references are declared with an
+/// `init` expression that fills every slot.
 #[derive(Debug, PartialEq, Serialize)]
-pub struct TableType {
-    pub element_type: ElementType,
-    pub limits: ResizableLimits,
+pub struct TableEntry {
+    pub t: TableType,
+    pub init: Option<Expression>,
+}
+
+impl TableEntry {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<TableEntry, Error> {
+        let (code, _) = reader.leb128_signed()?;
+
+        if code != -0x40 {
+            let t = TableType::from_code(code, reader)?;
+            return Ok(TableEntry { t, init: None });
+        }
+
+        if reader.read_u8()? != 0x00 {
+            return Err(Error::other("Invalid table declaration"));
+        }
+
+        let t = TableType::from_reader(reader)?;
+        let init = Expression::from_reader(reader)?;
+
+        Ok(TableEntry {
+            t,
+            init: Some(init),
+        })
+    }
 }
 
 #[derive(Debug, PartialEq, Serialize)]
-pub enum ElementType {
-    Anyfunc,
+pub struct TableType {
+    pub element_type: RefType,
+    pub limits: ResizableLimits,
+    pub table64: bool,
 }
 
-impl ElementType {
-    pub fn from_i64(num: i64) -> Result<ElementType, Error> {
-        match num {
-            -0x10 => Ok(ElementType::Anyfunc),
-            _ => Err(Error::new(ErrorKind::Other, "Unknown Element Type")),
+impl TableType {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<TableType, Error> {
+        let (code, _) = reader.leb128_signed()?;
+        TableType::from_code(code, reader)
+    }
+
+    fn from_code<T: Read>(code: i64, reader: &mut T) -> Result<TableType, Error> {
+        let element_type = RefType::from_code(code, reader)?;
+
+        let limits = ResizableLimits::from_reader(reader)?;
+
+        if limits.is_shared() {
+            return Err(Error::other("Tables cannot be shared"));
+        }
+
+        if limits.page_size_log2.is_some() {
+            return Err(Error::other("Tables cannot have a custom page size"));
         }
+
+        let table64 = limits.is_64();
+
+        Ok(TableType {
+            element_type,
+            limits,
+            table64,
+        })
     }
 }
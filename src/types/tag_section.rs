@@ -0,0 +1,106 @@
+use byteorder::ReadBytesExt;
+use leb128::ReadLeb128Ext;
+use std::io::{Error, Read};
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct TagSection {
+    pub count: u32,
+    pub entries: Vec<TagType>,
+}
+
+impl TagSection {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<TagSection, Error> {
+        let (count, _) = reader.leb128_unsigned()?;
+
+        let mut entries = Vec::new();
+
+        for _ in 0..count {
+            let entry = TagType::from_reader(reader)?;
+            entries.push(entry);
+        }
+
+        Ok(TagSection {
+            count: count as u32,
+            entries,
+        })
+    }
+}
+
+/// An exception tag. The params of the function type at `type_index` are
+/// the values an exception with this tag carries.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct TagType {
+    pub attribute: u8,
+    pub type_index: u32,
+}
+
+impl TagType {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<TagType, Error> {
+        let attribute = reader.read_u8()?;
+
+        if attribute != 0 {
+            return Err(Error::other("Unknown Tag Attribute"));
+        }
+
+        let (type_index, _) = reader.leb128_unsigned()?;
+
+        Ok(TagType {
+            attribute,
+            type_index: type_index as u32,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::*;
+    use types::export_section::ExportKind;
+    use types::{ExternalKind, WasmSectionBody};
+
+    #[test]
+    fn test_tag_imports_and_exports() {
+        // (import "env" "e" (tag (param i32))) (tag (param i32)) (export "t" (tag 1))
+        let module = module(&[
+            (TYPE, &[0x01, 0x60, 0x01, 0x7f, 0x00]),
+            (
+                IMPORT,
+                &[0x01, 0x03, 0x65, 0x6e, 0x76, 0x01, 0x65, 0x04, 0x00, 0x00],
+            ),
+            (TAG, &[0x01, 0x00, 0x00]),
+            (EXPORT, &[0x01, 0x01, 0x74, 0x04, 0x01]),
+        ]);
+        let tag = TagType {
+            attribute: 0,
+            type_index: 0,
+        };
+
+        match module.sections[1].body {
+            WasmSectionBody::Import(ref section) => {
+                assert_eq!(section.entries[0].kind, ExternalKind::Tag(tag))
+            }
+            ref body => panic!("Expected an import section, got {:?}", body),
+        }
+        match module.sections[2].body {
+            WasmSectionBody::Tag(ref section) => assert_eq!(
+                section.entries,
+                vec![TagType {
+                    attribute: 0,
+                    type_index: 0
+                }]
+            ),
+            ref body => panic!("Expected a tag section, got {:?}", body),
+        }
+        match module.sections[3].body {
+            WasmSectionBody::Export(ref section) => {
+                assert_eq!(section.entries[0].kind, ExportKind::Tag);
+                assert_eq!(section.entries[0].index, 1);
+            }
+            ref body => panic!("Expected an export section, got {:?}", body),
+        }
+
+        // Only the exception attribute is defined.
+        assert!(TagType::from_reader(&mut &[0x01, 0x00][..]).is_err());
+    }
+}
@@ -3,55 +3,277 @@ use std::io::{Error, Read};
 
 use super::*;
 
+/// Each entry is a recursion group. A type declared outside of an explicit
+/// `rec` forms a group of its own, so the type index space is the groups'
+/// types flattened in order.
 #[derive(Debug, PartialEq, Serialize)]
 pub struct TypeSection {
+    /// The number of types, which is more than the number of entries once
+    /// a group declares several.
     pub count: u32,
-    pub entries: Vec<FunctionType>,
+    pub entries: Vec<RecGroup>,
 }
 
 impl TypeSection {
     pub fn from_reader<T: Read>(reader: &mut T) -> Result<TypeSection, Error> {
-        let (count, _) = reader.leb128_unsigned()?;
+        let (group_count, _) = reader.leb128_unsigned()?;
 
         let mut entries = Vec::new();
+        let mut count = 0;
 
-        for _ in 0..count {
-            let (form_num, _) = reader.leb128_signed()?;
-            let form = ValueType::from_i64(form_num)?;
+        for _ in 0..group_count {
+            let group = RecGroup::from_reader(reader)?;
+            count += group.types.len() as u32;
+            entries.push(group);
+        }
+
+        Ok(TypeSection { count, entries })
+    }
+
+    /// The types in index order.
+    pub fn types(&self) -> impl Iterator<Item = &SubType> {
+        self.entries.iter().flat_map(|group| group.types.iter())
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RecGroup {
+    pub explicit: bool,
+    pub types: Vec<SubType>,
+}
 
-            let (param_count, _) = reader.leb128_unsigned()?;
-            let mut param_types = Vec::new();
+impl RecGroup {
+    pub fn from_reader<T: Read>(reader: &mut T) -> Result<RecGroup, Error> {
+        let (code, _) = reader.leb128_signed()?;
 
-            for _ in 0..param_count {
-                let (type_num, _) = reader.leb128_signed()?;
-                let typ = ValueType::from_i64(type_num)?;
-                param_types.push(typ);
+        if code == -0x32 {
+            let (count, _) = reader.leb128_unsigned()?;
+            let mut types = Vec::new();
+
+            for _ in 0..count {
+                let (code, _) = reader.leb128_signed()?;
+                types.push(SubType::from_code(code, reader)?);
             }
 
-            let (return_count, _) = reader.leb128_unsigned()?;
-            let return_type = match return_count {
-                1 => {
-                    let (type_num, _) = reader.leb128_signed()?;
-                    let typ = ValueType::from_i64(type_num)?;
-                    Some(typ)
+            return Ok(RecGroup {
+                explicit: true,
+                types,
+            });
+        }
+
+        Ok(RecGroup {
+            explicit: false,
+            types: vec![SubType::from_code(code, reader)?],
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SubType {
+    pub is_final: bool,
+    pub supertypes: Vec<u32>,
+    pub composite: CompositeType,
+}
+
+impl SubType {
+    fn from_code<T: Read>(code: i64, reader: &mut T) -> Result<SubType, Error> {
+        match code {
+            -0x30 | -0x31 => {
+                let supertypes = read_vec(reader, read_index)?;
+                if supertypes.len() > 1 {
+                    return Err(Error::other("A type can have at most one supertype"));
                 }
-                _ => None,
-            };
-
-            let entry = FunctionType {
-                form,
-                param_count: param_count as u32,
-                param_types,
-                return_count: return_count as u32,
-                return_type,
-            };
-
-            entries.push(entry);
+
+                let (composite_code, _) = reader.leb128_signed()?;
+
+                Ok(SubType {
+                    is_final: code == -0x31,
+                    supertypes,
+                    composite: CompositeType::from_code(composite_code, reader)?,
+                })
+            }
+            _ => Ok(SubType {
+                is_final: true,
+                supertypes: Vec::new(),
+                composite: CompositeType::from_code(code, reader)?,
+            }),
+        }
+    }
+
+    pub fn func_type(&self) -> Option<&FunctionType> {
+        match self.composite {
+            CompositeType::Func(ref func_type) => Some(func_type),
+            _ => None,
         }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum CompositeType {
+    Func(FunctionType),
+    Struct(StructType),
+    Array(ArrayType),
+}
+
+impl CompositeType {
+    fn from_code<T: Read>(code: i64, reader: &mut T) -> Result<CompositeType, Error> {
+        match code {
+            -0x20 => {
+                let param_types = read_vec(reader, ValueType::from_reader)?;
+                let return_types = read_vec(reader, ValueType::from_reader)?;
 
-        Ok(TypeSection {
-            count: count as u32,
-            entries,
+                Ok(CompositeType::Func(FunctionType {
+                    form: ValueType::Func,
+                    param_count: param_types.len() as u32,
+                    param_types,
+                    return_count: return_types.len() as u32,
+                    return_types,
+                }))
+            }
+            -0x21 => Ok(CompositeType::Struct(StructType {
+                fields: read_vec(reader, FieldType::from_reader)?,
+            })),
+            -0x22 => Ok(CompositeType::Array(ArrayType {
+                field: FieldType::from_reader(reader)?,
+            })),
+            _ => Err(Error::other(format!("Unknown type form {}", code))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct StructType {
+    pub fields: Vec<FieldType>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ArrayType {
+    pub field: FieldType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FieldType {
+    pub storage_type: StorageType,
+    pub mutable: bool,
+}
+
+impl FieldType {
+    fn from_reader<T: Read>(reader: &mut T) -> Result<FieldType, Error> {
+        let (code, _) = reader.leb128_signed()?;
+
+        let storage_type = match code {
+            -0x08 => StorageType::I8,
+            -0x09 => StorageType::I16,
+            _ => StorageType::Value(ValueType::from_code(code, reader)?),
+        };
+
+        let (mutability, _) = reader.leb128_unsigned()?;
+
+        let mutable = match mutability {
+            0 => false,
+            1 => true,
+            _ => return Err(Error::other("Invalid field mutability")),
+        };
+
+        Ok(FieldType {
+            storage_type,
+            mutable,
         })
     }
 }
+
+/// Packed storage types may only appear as struct and array fields, where
+/// they are widened to `i32` on access.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum StorageType {
+    I8,
+    I16,
+    Value(ValueType),
+}
+
+impl StorageType {
+    pub fn unpacked(self) -> ValueType {
+        match self {
+            StorageType::I8 | StorageType::I16 => ValueType::Integer32,
+            StorageType::Value(value_type) => value_type,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::*;
+
+    #[test]
+    fn test_multi_value_types() {
+        // (type (func (result i32 i64))) (type (func (param i32) (result f32 f64 v128)))
+        // (func (type 0) block (type 0) i32.const 1 i64.const 2 end)
+        let module = module(&[
+            (
+                TYPE,
+                &[
+                    0x02, 0x60, 0x00, 0x02, 0x7f, 0x7e, 0x60, 0x01, 0x7f, 0x03, 0x7d, 0x7c, 0x7b,
+                ],
+            ),
+            (FUNCTION, &[0x01, 0x00]),
+            (
+                CODE,
+                &code(&[&[0x00, 0x02, 0x00, 0x41, 0x01, 0x42, 0x02, 0x0b, 0x0b]]),
+            ),
+        ]);
+
+        let types: Vec<_> = match module.sections[0].body {
+            WasmSectionBody::Types(ref section) => section
+                .types()
+                .map(|sub_type| sub_type.func_type().unwrap())
+                .map(|func_type| {
+                    (
+                        func_type.param_types.clone(),
+                        func_type.return_types.clone(),
+                    )
+                })
+                .collect(),
+            ref body => panic!("Expected a type section, got {:?}", body),
+        };
+        assert_eq!(
+            types,
+            vec![
+                (vec![], vec![ValueType::Integer32, ValueType::Integer64]),
+                (
+                    vec![ValueType::Integer32],
+                    vec![ValueType::Float32, ValueType::Float64, ValueType::V128]
+                ),
+            ]
+        );
+
+        let function = module.functions().next().unwrap();
+        assert_eq!(
+            function.body.unwrap().operators().unwrap(),
+            vec![
+                Operator::Block(BlockType::FuncType(0)),
+                Operator::I32Const(1),
+                Operator::I64Const(2),
+                Operator::End,
+                Operator::End,
+            ]
+        );
+    }
+    #[test]
+    fn test_count_is_types_not_groups() {
+        // (rec (type (struct)) (type (struct))) (type (func))
+        let module = module(&[(
+            TYPE,
+            &[0x02, 0x4e, 0x02, 0x5f, 0x00, 0x5f, 0x00, 0x60, 0x00, 0x00],
+        )]);
+
+        match module.sections[0].body {
+            WasmSectionBody::Types(ref section) => {
+                assert_eq!(section.count, 3);
+                assert_eq!(section.entries.len(), 2);
+            }
+            ref body => panic!("Expected a type section, got {:?}", body),
+        }
+    }
+}
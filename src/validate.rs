@@ -0,0 +1,648 @@
+//! Validation rules that decoding alone does not enforce.
+
+mod func;
+mod subtype;
+
+use features::Features;
+use std::io::Error;
+use types::data_section::DataMode;
+use types::element_section::{ElementItems, ElementMode, ElementSegment};
+use types::global_section::GlobalType;
+use types::table_section::TableType;
+use types::tag_section::TagType;
+use types::{
+    ArrayType, CompositeType, ExternalKind, FunctionType, MemoryType, RefType, StructType, SubType,
+    ValueType, WasmModule, WasmSectionBody,
+};
+
+pub fn validate(module: &WasmModule) -> Result<(), Error> {
+    validate_with_features(module, &Features::default())
+}
+
+pub fn validate_with_features(module: &WasmModule, features: &Features) -> Result<(), Error> {
+    let context = Context::from_module(module, features);
+
+    for section in &module.sections {
+        if let WasmSectionBody::Types(ref section) = section.body {
+            subtype::validate_types(&context, section)?;
+        }
+    }
+
+    validate_tables(module, &context)?;
+    validate_memories(&context)?;
+    validate_tags(&context)?;
+    validate_data(module, &context)?;
+    validate_const_exprs(module, &context)?;
+    validate_functions(module, &context)?;
+
+    Ok(())
+}
+
+/// The index spaces of a module. Imported entities come first in each space.
+struct Context<'a> {
+    features: &'a Features,
+    types: Vec<&'a SubType>,
+    canonical: Vec<u32>,
+    functions: Vec<u32>,
+    imported_functions: usize,
+    imported_globals: usize,
+    tables: Vec<&'a TableType>,
+    memories: Vec<&'a MemoryType>,
+    globals: Vec<&'a GlobalType>,
+    tags: Vec<&'a TagType>,
+    elements: Vec<RefType>,
+    data_count: Option<u32>,
+}
+
+impl<'a> Context<'a> {
+    fn from_module(module: &'a WasmModule, features: &'a Features) -> Context<'a> {
+        let mut context = Context {
+            features,
+            types: Vec::new(),
+            canonical: Vec::new(),
+            functions: Vec::new(),
+            imported_functions: 0,
+            imported_globals: 0,
+            tables: Vec::new(),
+            memories: Vec::new(),
+            globals: Vec::new(),
+            tags: Vec::new(),
+            elements: Vec::new(),
+            data_count: None,
+        };
+
+        for section in &module.sections {
+            match section.body {
+                WasmSectionBody::Types(ref section) => {
+                    context.types = section.types().collect();
+                    context.canonical = subtype::canonicalize(section);
+                }
+                WasmSectionBody::Import(ref section) => {
+                    for entry in &section.entries {
+                        match entry.kind {
+                            ExternalKind::Function(index) => {
+                                context.functions.push(index);
+                                context.imported_functions += 1;
+                            }
+                            ExternalKind::Table(ref table) => context.tables.push(table),
+                            ExternalKind::Memory(ref memory) => context.memories.push(memory),
+                            ExternalKind::Global(ref global) => {
+                                context.globals.push(global);
+                                context.imported_globals += 1;
+                            }
+                            ExternalKind::Tag(ref tag) => context.tags.push(tag),
+                        }
+                    }
+                }
+                WasmSectionBody::Function(ref section) => context.functions.extend(&section.types),
+                WasmSectionBody::Table(ref section) => {
+                    context
+                        .tables
+                        .extend(section.entries.iter().map(|table| &table.t));
+                }
+                WasmSectionBody::Memory(ref section) => context.memories.extend(&section.entries),
+                WasmSectionBody::Global(ref section) => {
+                    context
+                        .globals
+                        .extend(section.globals.iter().map(|global| &global.t));
+                }
+                WasmSectionBody::Tag(ref section) => context.tags.extend(&section.entries),
+                WasmSectionBody::Element(ref section) => {
+                    context.elements = section
+                        .entries
+                        .iter()
+                        .map(|segment| segment.element_type)
+                        .collect();
+                }
+                WasmSectionBody::DataCount(ref section) => context.data_count = Some(section.count),
+                _ => {}
+            }
+        }
+
+        context
+    }
+
+    fn func_type(&self, index: u32) -> Result<&'a FunctionType, Error> {
+        self.sub_type(index)?
+            .func_type()
+            .ok_or_else(|| Error::other(format!("Type {} is not a function type", index)))
+    }
+
+    fn struct_type(&self, index: u32) -> Result<&'a StructType, Error> {
+        match self.sub_type(index)?.composite {
+            CompositeType::Struct(ref struct_type) => Ok(struct_type),
+            _ => Err(Error::other(format!("Type {} is not a struct type", index))),
+        }
+    }
+
+    fn array_type(&self, index: u32) -> Result<&'a ArrayType, Error> {
+        match self.sub_type(index)?.composite {
+            CompositeType::Array(ref array_type) => Ok(array_type),
+            _ => Err(Error::other(format!("Type {} is not an array type", index))),
+        }
+    }
+
+    fn function_type(&self, index: u32) -> Result<&'a FunctionType, Error> {
+        match self.functions.get(index as usize) {
+            Some(&type_index) => self.func_type(type_index),
+            None => Err(Error::other(format!("Unknown function {}", index))),
+        }
+    }
+
+    fn table(&self, index: u32) -> Result<&'a TableType, Error> {
+        self.tables
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| Error::other(format!("Unknown table {}", index)))
+    }
+
+    fn memory(&self, index: u32) -> Result<&'a MemoryType, Error> {
+        self.memories
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| Error::other(format!("Unknown memory {}", index)))
+    }
+
+    fn global(&self, index: u32) -> Result<&'a GlobalType, Error> {
+        self.globals
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| Error::other(format!("Unknown global {}", index)))
+    }
+
+    /// The function type whose params are the payload of tag `index`.
+    fn tag_type(&self, index: u32) -> Result<&'a FunctionType, Error> {
+        match self.tags.get(index as usize) {
+            Some(tag) => self.func_type(tag.type_index),
+            None => Err(Error::other(format!("Unknown tag {}", index))),
+        }
+    }
+}
+
+/// Tables of non-nullable references have no default element, so they
+/// must be declared with an initializer.
+fn validate_tables(module: &WasmModule, context: &Context) -> Result<(), Error> {
+    for table in &context.tables {
+        context.check_heap_type(table.element_type.heap_type)?;
+    }
+
+    for section in &module.sections {
+        let tables = match section.body {
+            WasmSectionBody::Table(ref tables) => tables,
+            _ => continue,
+        };
+
+        let imported = context.tables.len() - tables.entries.len();
+
+        for (offset, table) in tables.entries.iter().enumerate() {
+            if !table.t.element_type.nullable && table.init.is_none() {
+                return Err(Error::other(format!(
+                    "Table {} of non-nullable references needs an initializer",
+                    imported + offset
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_memories(context: &Context) -> Result<(), Error> {
+    for (index, memory) in context.memories.iter().enumerate() {
+        let max_pages = memory.max_pages();
+        let limits = &memory.limits;
+        if limits.initial > max_pages || limits.maximum.is_some_and(|max| max > max_pages) {
+            return Err(Error::other(format!(
+                "Memory {} size must be at most {} pages of {} bytes",
+                index,
+                max_pages,
+                memory.page_size()
+            )));
+        }
+
+        if limits.maximum.is_some_and(|max| limits.initial > max) {
+            return Err(Error::other(format!(
+                "Memory {} minimum size is greater than its maximum",
+                index
+            )));
+        }
+
+        if memory.is_shared() && memory.limits.maximum.is_none() {
+            return Err(Error::other(format!(
+                "Shared memory {} must declare a maximum",
+                index
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_tags(context: &Context) -> Result<(), Error> {
+    for index in 0..context.tags.len() {
+        if !context.tag_type(index as u32)?.return_types.is_empty() {
+            return Err(Error::other(format!(
+                "Tag {} has a type with results",
+                index
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_data(module: &WasmModule, context: &Context) -> Result<(), Error> {
+    for section in &module.sections {
+        let data = match section.body {
+            WasmSectionBody::Data(ref data) => data,
+            _ => continue,
+        };
+
+        for (index, segment) in data.entries.iter().enumerate() {
+            if let DataMode::Active { memory_index, .. } = segment.mode {
+                context
+                    .memory(memory_index)
+                    .map_err(|error| Error::other(format!("Data segment {}: {}", index, error)))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_const_exprs(module: &WasmModule, context: &Context) -> Result<(), Error> {
+    for section in &module.sections {
+        match section.body {
+            WasmSectionBody::Global(ref section) => {
+                // Initializers may read imported globals and, as in the GC
+                // proposal, the globals defined before them.
+                let imported = context.imported_globals;
+                for (offset, global) in section.globals.iter().enumerate() {
+                    let index = imported + offset;
+                    func::validate_const_expr(context, &global.init, global.t.content_type, index)
+                        .map_err(|error| Error::other(format!("Global {}: {}", index, error)))?;
+                }
+            }
+            WasmSectionBody::Table(ref section) => {
+                let imported = context.tables.len() - section.entries.len();
+                for (offset, table) in section.entries.iter().enumerate() {
+                    if let Some(ref init) = table.init {
+                        let element_type = ValueType::Ref(table.t.element_type);
+                        // The global section comes later, so only imported
+                        // globals exist yet.
+                        let globals = context.imported_globals;
+                        func::validate_const_expr(context, init, element_type, globals).map_err(
+                            |error| Error::other(format!("Table {}: {}", imported + offset, error)),
+                        )?;
+                    }
+                }
+            }
+            WasmSectionBody::Element(ref section) => {
+                for (index, segment) in section.entries.iter().enumerate() {
+                    validate_element_exprs(context, segment).map_err(|error| {
+                        Error::other(format!("Element segment {}: {}", index, error))
+                    })?;
+                }
+            }
+            WasmSectionBody::Data(ref section) => {
+                for (index, segment) in section.entries.iter().enumerate() {
+                    if let DataMode::Active {
+                        memory_index,
+                        ref offset,
+                    } = segment.mode
+                    {
+                        let address = func::address_type(context.memory(memory_index)?);
+                        let globals = context.globals.len();
+                        func::validate_const_expr(context, offset, address, globals).map_err(
+                            |error| Error::other(format!("Data segment {}: {}", index, error)),
+                        )?;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_element_exprs(context: &Context, segment: &ElementSegment) -> Result<(), Error> {
+    if let ElementMode::Active {
+        table_index,
+        ref offset,
+    } = segment.mode
+    {
+        let index_type = func::table_index_type(context.table(table_index)?);
+        func::validate_const_expr(context, offset, index_type, context.globals.len())?;
+    }
+
+    if let ElementItems::Expressions(ref exprs) = segment.items {
+        for expr in exprs {
+            let element_type = ValueType::Ref(segment.element_type);
+            func::validate_const_expr(context, expr, element_type, context.globals.len())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_functions(module: &WasmModule, context: &Context) -> Result<(), Error> {
+    for section in &module.sections {
+        let code = match section.body {
+            WasmSectionBody::Code(ref code) => code,
+            _ => continue,
+        };
+
+        for (offset, body) in code.bodies.iter().enumerate() {
+            let index = (context.imported_functions + offset) as u32;
+
+            func::validate_function(context, context.function_type(index)?, body)
+                .map_err(|error| Error::other(format!("Function {}: {}", index, error)))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::validate;
+    use testing::*;
+    use types::WasmModule;
+
+    #[test]
+    fn test_shared_memory_requires_maximum() {
+        let unbounded = module(&[(MEMORY, &[0x01, 0x02, 0x01])]);
+        assert!(validate(&unbounded).is_err());
+
+        let bounded = module(&[(MEMORY, &[0x01, 0x03, 0x01, 0x02])]);
+        assert!(validate(&bounded).is_ok());
+    }
+
+    /// A module with one `[] -> [i32]` function whose body (without the
+    /// locals) is `code_bytes`.
+    fn function_returning_i32(code_bytes: &[u8]) -> WasmModule {
+        let mut body = vec![0x00];
+        body.extend(code_bytes);
+
+        module(&[
+            (TYPE, &[0x01, 0x60, 0x00, 0x01, 0x7f]),
+            (FUNCTION, &[0x01, 0x00]),
+            (CODE, &code(&[&body])),
+        ])
+    }
+
+    #[test]
+    fn test_operand_typing() {
+        let valid = |code: &[u8]| validate(&function_returning_i32(code)).is_ok();
+
+        // i32.const 1 i32.const 2 i32.add
+        assert!(valid(&[0x41, 0x01, 0x41, 0x02, 0x6a, 0x0b]));
+        // i32.const 1 i64.const 2 i32.add
+        assert!(!valid(&[0x41, 0x01, 0x42, 0x02, 0x6a, 0x0b]));
+        // i32.add with an empty stack
+        assert!(!valid(&[0x6a, 0x0b]));
+        // The result is missing, or the end is.
+        assert!(!valid(&[0x0b]));
+        assert!(!valid(&[0x41, 0x01]));
+        // unreachable makes the stack polymorphic.
+        assert!(valid(&[0x00, 0x6a, 0x0b]));
+    }
+
+    #[test]
+    fn test_control_flow_typing() {
+        let valid = |code: &[u8]| validate(&function_returning_i32(code)).is_ok();
+
+        // block (result i32) i32.const 1 end
+        assert!(valid(&[0x02, 0x7f, 0x41, 0x01, 0x0b, 0x0b]));
+        // block (result i32) br 0 end
+        assert!(!valid(&[0x02, 0x7f, 0x0c, 0x00, 0x0b, 0x0b]));
+        // i32.const 1 br 1
+        assert!(!valid(&[0x41, 0x01, 0x0c, 0x01, 0x0b]));
+        // br 0xffffffff
+        assert!(!valid(&[0x0c, 0xff, 0xff, 0xff, 0xff, 0x0f, 0x0b]));
+        // i32.const 0 if (result i32) i32.const 1 else i32.const 2 end
+        assert!(valid(&[
+            0x41, 0x00, 0x04, 0x7f, 0x41, 0x01, 0x05, 0x41, 0x02, 0x0b, 0x0b
+        ]));
+        // An if with a result needs an else.
+        assert!(!valid(&[0x41, 0x00, 0x04, 0x7f, 0x41, 0x01, 0x0b, 0x0b]));
+    }
+
+    #[test]
+    fn test_custom_page_size_limits() {
+        let memory = |limits: &[u8]| {
+            let mut memories = vec![0x01];
+            memories.extend(limits);
+            module(&[(MEMORY, &memories)])
+        };
+
+        // 65537 pages only fit in memory with byte-sized pages.
+        assert!(validate(&memory(&[0x08, 0x81, 0x80, 0x04, 0x00])).is_ok());
+        assert!(validate(&memory(&[0x00, 0x81, 0x80, 0x04])).is_err());
+    }
+
+    /// A module with one `[] -> []` function, the given memory section and
+    /// the function body `body`, locals included.
+    fn function_with_memories(memories: &[u8], body: &[u8]) -> WasmModule {
+        module(&[
+            (TYPE, &[0x01, 0x60, 0x00, 0x00]),
+            (FUNCTION, &[0x01, 0x00]),
+            (MEMORY, memories),
+            (CODE, &code(&[body])),
+        ])
+    }
+
+    #[test]
+    fn test_memory64_addresses() {
+        let memories = [0x01, 0x04, 0x01];
+
+        // (func i64.const 0 i32.load drop)
+        let i64_address = [0x00, 0x42, 0x00, 0x28, 0x02, 0x00, 0x1a, 0x0b];
+        assert!(validate(&function_with_memories(&memories, &i64_address)).is_ok());
+
+        let i32_address = [0x00, 0x41, 0x00, 0x28, 0x02, 0x00, 0x1a, 0x0b];
+        assert!(validate(&function_with_memories(&memories, &i32_address)).is_err());
+    }
+
+    #[test]
+    fn test_rec_group_equivalence() {
+        let returning = |result_type| {
+            // Two identical groups of mutually recursive structs, then a
+            // function from the first group's struct to `result_type`.
+            let mut types = vec![0x03];
+            types.extend(&[
+                0x4e, 0x02, 0x5f, 0x01, 0x63, 0x01, 0x00, 0x5f, 0x01, 0x63, 0x00, 0x00,
+            ]);
+            types.extend(&[
+                0x4e, 0x02, 0x5f, 0x01, 0x63, 0x03, 0x00, 0x5f, 0x01, 0x63, 0x02, 0x00,
+            ]);
+            types.extend(&[0x60, 0x01, 0x64, 0x00, 0x01, 0x64, result_type]);
+
+            module(&[
+                (TYPE, &types),
+                (FUNCTION, &[0x01, 0x04]),
+                (CODE, &code(&[&[0x00, 0x20, 0x00, 0x0b]])),
+            ])
+        };
+
+        assert!(validate(&returning(0x02)).is_ok());
+        assert!(validate(&returning(0x03)).is_err());
+    }
+
+    #[test]
+    fn test_non_nullable_local_initialization() {
+        let with_local = |code_bytes: &[u8]| {
+            // One local of type (ref 0).
+            let mut body = vec![0x01, 0x01, 0x64, 0x00];
+            body.extend(code_bytes);
+
+            module(&[
+                (TYPE, &[0x01, 0x60, 0x00, 0x00]),
+                (FUNCTION, &[0x01, 0x00]),
+                (ELEMENT, &[0x01, 0x03, 0x00, 0x01, 0x00]),
+                (CODE, &code(&[&body])),
+            ])
+        };
+
+        let set_then_get = [0xd2, 0x00, 0x21, 0x00, 0x20, 0x00, 0x1a, 0x0b];
+        assert!(validate(&with_local(&set_then_get)).is_ok());
+
+        let get = [0x20, 0x00, 0x1a, 0x0b];
+        assert!(validate(&with_local(&get)).is_err());
+    }
+
+    #[test]
+    fn test_multiple_memories() {
+        // A 32-bit memory 0 and a 64-bit memory 1.
+        let memories = [0x02, 0x00, 0x01, 0x04, 0x01];
+
+        // (func i64.const 0 i32.load 1 drop)
+        let i64_address = [0x00, 0x42, 0x00, 0x28, 0x42, 0x01, 0x00, 0x1a, 0x0b];
+        assert!(validate(&function_with_memories(&memories, &i64_address)).is_ok());
+
+        let i32_address = [0x00, 0x41, 0x00, 0x28, 0x42, 0x01, 0x00, 0x1a, 0x0b];
+        assert!(validate(&function_with_memories(&memories, &i32_address)).is_err());
+    }
+
+    #[test]
+    fn test_throw_typing() {
+        // A tag carrying an i32 and one [] -> [] function.
+        let valid = |code_bytes: &[u8]| {
+            let mut body = vec![0x00];
+            body.extend(code_bytes);
+
+            let module = module(&[
+                (TYPE, &[0x02, 0x60, 0x01, 0x7f, 0x00, 0x60, 0x00, 0x00]),
+                (FUNCTION, &[0x01, 0x01]),
+                (TAG, &[0x01, 0x00, 0x00]),
+                (CODE, &code(&[&body])),
+            ]);
+            validate(&module).is_ok()
+        };
+
+        // i32.const 1 throw 0
+        assert!(valid(&[0x41, 0x01, 0x08, 0x00, 0x0b]));
+        // throw 0 without its payload, with the wrong one or an unknown tag
+        assert!(!valid(&[0x08, 0x00, 0x0b]));
+        assert!(!valid(&[0x42, 0x01, 0x08, 0x00, 0x0b]));
+        assert!(!valid(&[0x41, 0x01, 0x08, 0x01, 0x0b]));
+        // throw is stack-polymorphic.
+        assert!(valid(&[0x41, 0x01, 0x08, 0x00, 0x6a, 0x1a, 0x0b]));
+
+        // ref.null exn throw_ref
+        assert!(valid(&[0xd0, 0x69, 0x0a, 0x0b]));
+        // throw_ref of an i32 or of nothing
+        assert!(!valid(&[0x41, 0x00, 0x0a, 0x0b]));
+        assert!(!valid(&[0x0a, 0x0b]));
+    }
+
+    #[test]
+    fn test_tail_call_results() {
+        // Function 0 is [] -> [i32] and tail calls with `code`, function 1
+        // returns an i32 and function 2 an i64. Table 0 holds funcrefs.
+        let valid = |code_bytes: &[u8]| {
+            let mut body = vec![0x00];
+            body.extend(code_bytes);
+
+            let module = module(&[
+                (
+                    TYPE,
+                    &[0x02, 0x60, 0x00, 0x01, 0x7f, 0x60, 0x00, 0x01, 0x7e],
+                ),
+                (FUNCTION, &[0x03, 0x00, 0x00, 0x01]),
+                (TABLE, &[0x01, 0x70, 0x00, 0x01]),
+                (
+                    CODE,
+                    &code(&[&body, &[0x00, 0x41, 0x00, 0x0b], &[0x00, 0x42, 0x00, 0x0b]]),
+                ),
+            ]);
+            validate(&module).is_ok()
+        };
+
+        // return_call 1, return_call 2
+        assert!(valid(&[0x12, 0x01, 0x0b]));
+        assert!(!valid(&[0x12, 0x02, 0x0b]));
+        // i32.const 0 return_call_indirect (type 0) or (type 1)
+        assert!(valid(&[0x41, 0x00, 0x13, 0x00, 0x00, 0x0b]));
+        assert!(!valid(&[0x41, 0x00, 0x13, 0x01, 0x00, 0x0b]));
+    }
+
+    #[test]
+    fn test_array_new_fixed_size() {
+        // (type (array (mut i32))) and one [] -> [] function.
+        let valid = |code_bytes: &[u8]| {
+            let mut body = vec![0x00];
+            body.extend(code_bytes);
+
+            let module = module(&[
+                (TYPE, &[0x02, 0x5e, 0x7f, 0x01, 0x60, 0x00, 0x00]),
+                (FUNCTION, &[0x01, 0x01]),
+                (CODE, &code(&[&body])),
+            ]);
+            validate(&module).is_ok()
+        };
+
+        // i32.const 1 i32.const 2 array.new_fixed 0 2 drop
+        assert!(valid(&[
+            0x41, 0x01, 0x41, 0x02, 0xfb, 0x08, 0x00, 0x02, 0x1a, 0x0b
+        ]));
+        assert!(!valid(&[
+            0x41, 0x01, 0x42, 0x02, 0xfb, 0x08, 0x00, 0x02, 0x1a, 0x0b
+        ]));
+        // A size far beyond the operands is an underflow, not an allocation.
+        let huge = [0xfb, 0x08, 0x00, 0xff, 0xff, 0xff, 0xff, 0x0f, 0x1a, 0x0b];
+        assert!(!valid(&huge));
+        // unreachable makes it valid.
+        let mut unreachable = vec![0x00];
+        unreachable.extend(&huge);
+        assert!(valid(&unreachable));
+    }
+
+    #[test]
+    fn test_global_initializer_visibility() {
+        let valid = |sections: &[(u8, &[u8])]| validate(&module(sections)).is_ok();
+        // (import "env" "g" (global i32))
+        let import: &[u8] = &[0x01, 0x03, 0x65, 0x6e, 0x76, 0x01, 0x67, 0x03, 0x7f, 0x00];
+
+        // (global i32 (global.get 0)), reading itself or an import
+        assert!(!valid(&[(GLOBAL, &[0x01, 0x7f, 0x00, 0x23, 0x00, 0x0b])]));
+        assert!(valid(&[
+            (IMPORT, import),
+            (GLOBAL, &[0x01, 0x7f, 0x00, 0x23, 0x00, 0x0b]),
+        ]));
+
+        // (global i32 (global.get 1)) (global i32 (i32.const 0))
+        let forward = [
+            0x02, 0x7f, 0x00, 0x23, 0x01, 0x0b, 0x7f, 0x00, 0x41, 0x00, 0x0b,
+        ];
+        assert!(!valid(&[(GLOBAL, &forward)]));
+
+        // (global i32 (i32.const 0)) (global i32 (global.get 0))
+        let backward = [
+            0x02, 0x7f, 0x00, 0x41, 0x00, 0x0b, 0x7f, 0x00, 0x23, 0x00, 0x0b,
+        ];
+        assert!(valid(&[(GLOBAL, &backward)]));
+    }
+}
@@ -0,0 +1,1717 @@
+use std::io::{Cursor, Error};
+use types::code_section::FunctionBody;
+use types::table_section::TableType;
+use types::ValueType::{Float32 as F32, Float64 as F64, Integer32 as I32, Integer64 as I64, V128};
+use types::{
+    BlockType, Expression, FieldType, FunctionType, HeapType, MemArg, MemoryType, Operator,
+    RefType, StorageType, TryTableCatch, ValueType,
+};
+
+use super::Context;
+
+/// An operand whose type is `None` was produced in unreachable code and
+/// matches any type.
+type Operand = Option<ValueType>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FrameKind {
+    Block,
+    Loop,
+    If,
+    Else,
+    Try,
+    Catch,
+    CatchAll,
+}
+
+struct Frame {
+    kind: FrameKind,
+    params: Vec<ValueType>,
+    results: Vec<ValueType>,
+    height: usize,
+    init_height: usize,
+    unreachable: bool,
+}
+
+impl Frame {
+    /// The types a branch to this frame's label carries.
+    fn label_types(&self) -> &[ValueType] {
+        match self.kind {
+            FrameKind::Loop => &self.params,
+            _ => &self.results,
+        }
+    }
+}
+
+struct FuncValidator<'a, 'b: 'a> {
+    context: &'a Context<'b>,
+    locals: Vec<ValueType>,
+    /// Whether each local holds a value. Only non-defaultable locals start
+    /// out uninitialized.
+    initialized: Vec<bool>,
+    /// Locals initialized since the start of each open block, which become
+    /// uninitialized again when the block ends.
+    inits: Vec<u32>,
+    results: Vec<ValueType>,
+    operands: Vec<Operand>,
+    frames: Vec<Frame>,
+}
+
+/// Type checks a function body with the algorithm from the validation
+/// appendix of the specification.
+pub(super) fn validate_function(
+    context: &Context,
+    func_type: &FunctionType,
+    body: &FunctionBody,
+) -> Result<(), Error> {
+    let mut locals = func_type.param_types.clone();
+    for entry in &body.locals {
+        context.check_value_type(entry.t)?;
+        locals.extend((0..entry.count).map(|_| entry.t));
+    }
+
+    let params = func_type.param_types.len();
+    let initialized = locals
+        .iter()
+        .enumerate()
+        .map(|(index, &ty)| index < params || is_defaultable(StorageType::Value(ty)))
+        .collect();
+
+    let mut validator = FuncValidator {
+        context,
+        locals,
+        initialized,
+        inits: Vec::new(),
+        results: func_type.return_types.clone(),
+        operands: Vec::new(),
+        frames: Vec::new(),
+    };
+    validator.push_frame(FrameKind::Block, Vec::new(), func_type.return_types.clone());
+
+    let mut reader = Cursor::new(&body.code);
+
+    while (reader.position() as usize) < body.code.len() {
+        let offset = reader.position();
+
+        if validator.frames.is_empty() {
+            return Err(Error::other(format!(
+                "Operators after the end of the function at offset {}",
+                offset
+            )));
+        }
+
+        let operator = Operator::from_reader_with_features(&mut reader, context.features)?;
+
+        validator
+            .operator(&operator)
+            .map_err(|error| Error::other(format!("{} at offset {}", error, offset)))?;
+    }
+
+    if !validator.frames.is_empty() {
+        return Err(Error::other("Function body is missing its end"));
+    }
+
+    Ok(())
+}
+
+/// Type checks a constant expression, such as a global initializer or a
+/// segment offset, which must produce a single `expected` value. Only the
+/// first `visible_globals` globals may be read.
+pub(super) fn validate_const_expr(
+    context: &Context,
+    expr: &Expression,
+    expected: ValueType,
+    visible_globals: usize,
+) -> Result<(), Error> {
+    let mut validator = FuncValidator {
+        context,
+        locals: Vec::new(),
+        initialized: Vec::new(),
+        inits: Vec::new(),
+        results: vec![expected],
+        operands: Vec::new(),
+        frames: Vec::new(),
+    };
+    validator.push_frame(FrameKind::Block, Vec::new(), vec![expected]);
+
+    for operator in expr.operators()? {
+        if !is_constant(&operator) {
+            return Err(Error::other(format!(
+                "{:?} is not allowed in a constant expression",
+                operator
+            )));
+        }
+        if let Operator::GlobalGet(index) = operator {
+            if index as usize >= visible_globals {
+                return Err(Error::other(format!(
+                    "Global {} is not visible in this constant expression",
+                    index
+                )));
+            }
+            if context.global(index)?.mutability != 0 {
+                return Err(Error::other(format!(
+                    "Constant expressions cannot read mutable global {}",
+                    index
+                )));
+            }
+        }
+
+        validator.operator(&operator)?;
+    }
+
+    Ok(())
+}
+
+impl<'a, 'b> FuncValidator<'a, 'b> {
+    fn push(&mut self, operand: ValueType) {
+        self.operands.push(Some(operand));
+    }
+
+    fn pop(&mut self) -> Result<Operand, Error> {
+        let frame = &self.frames[self.frames.len() - 1];
+
+        if self.operands.len() == frame.height {
+            if frame.unreachable {
+                return Ok(None);
+            }
+            return Err(Error::other("Operand stack underflow"));
+        }
+
+        Ok(self.operands.pop().unwrap())
+    }
+
+    fn pop_expect(&mut self, expected: ValueType) -> Result<Operand, Error> {
+        match self.pop()? {
+            Some(actual) if !self.context.is_subtype(actual, expected) => Err(Error::other(
+                format!("Type mismatch: expected {:?}, found {:?}", expected, actual),
+            )),
+            Some(actual) => Ok(Some(actual)),
+            None => Ok(Some(expected)),
+        }
+    }
+
+    fn pop_all(&mut self, expected: &[ValueType]) -> Result<Vec<Operand>, Error> {
+        let mut popped = Vec::with_capacity(expected.len());
+        for &ty in expected.iter().rev() {
+            popped.push(self.pop_expect(ty)?);
+        }
+        popped.reverse();
+        Ok(popped)
+    }
+
+    /// Pops `params` and pushes `results`, as most instructions do.
+    fn op(&mut self, params: &[ValueType], results: &[ValueType]) -> Result<(), Error> {
+        self.pop_all(params)?;
+        self.operands.extend(results.iter().map(|&ty| Some(ty)));
+        Ok(())
+    }
+
+    fn push_frame(&mut self, kind: FrameKind, params: Vec<ValueType>, results: Vec<ValueType>) {
+        self.operands.extend(params.iter().map(|&ty| Some(ty)));
+        let height = self.operands.len() - params.len();
+        let init_height = self.inits.len();
+
+        self.frames.push(Frame {
+            kind,
+            params,
+            results,
+            height,
+            init_height,
+            unreachable: false,
+        });
+    }
+
+    fn pop_frame(&mut self) -> Result<Frame, Error> {
+        let results = self.frames[self.frames.len() - 1].results.clone();
+        self.pop_all(&results)?;
+
+        let frame = self.frames.pop().unwrap();
+        if self.operands.len() != frame.height {
+            return Err(Error::other(
+                "Values remain on the stack at the end of a block",
+            ));
+        }
+
+        for index in self.inits.drain(frame.init_height..) {
+            self.initialized[index as usize] = false;
+        }
+
+        Ok(frame)
+    }
+
+    fn set_unreachable(&mut self) {
+        let frame = self.frames.last_mut().unwrap();
+        self.operands.truncate(frame.height);
+        frame.unreachable = true;
+    }
+
+    /// The frame a label `depth` refers to, counting outwards from the
+    /// innermost block.
+    fn frame(&self, depth: u32) -> Result<&Frame, Error> {
+        self.frames
+            .len()
+            .checked_sub(1)
+            .and_then(|top| top.checked_sub(depth as usize))
+            .map(|index| &self.frames[index])
+            .ok_or_else(|| Error::other(format!("Unknown label {}", depth)))
+    }
+
+    fn label_types(&self, depth: u32) -> Result<Vec<ValueType>, Error> {
+        Ok(self.frame(depth)?.label_types().to_vec())
+    }
+
+    /// Checks that a `try_table` handler's label accepts what it passes.
+    fn try_table_catch(&self, catch: TryTableCatch) -> Result<(), Error> {
+        let (label, mut types) = match catch {
+            TryTableCatch::Catch { tag, label } | TryTableCatch::CatchRef { tag, label } => {
+                (label, self.context.tag_type(tag)?.param_types.clone())
+            }
+            TryTableCatch::CatchAll { label } | TryTableCatch::CatchAllRef { label } => {
+                (label, Vec::new())
+            }
+        };
+
+        if let TryTableCatch::CatchRef { .. } | TryTableCatch::CatchAllRef { .. } = catch {
+            types.push(ValueType::EXNREF);
+        }
+
+        if !self.all_subtypes(&types, &self.label_types(label)?) {
+            return Err(Error::other(format!(
+                "Catch does not match the type of label {}",
+                label
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn block_type(&self, block_type: BlockType) -> Result<(Vec<ValueType>, Vec<ValueType>), Error> {
+        match block_type {
+            BlockType::Empty => Ok((Vec::new(), Vec::new())),
+            BlockType::Value(ty) => {
+                self.context.check_value_type(ty)?;
+                Ok((Vec::new(), vec![ty]))
+            }
+            BlockType::FuncType(index) => {
+                let func_type = self.context.func_type(index)?;
+                Ok((
+                    func_type.param_types.clone(),
+                    func_type.return_types.clone(),
+                ))
+            }
+        }
+    }
+
+    fn local(&self, index: u32) -> Result<ValueType, Error> {
+        self.locals
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| Error::other(format!("Unknown local {}", index)))
+    }
+
+    fn set_local(&mut self, index: u32) {
+        if !self.initialized[index as usize] {
+            self.initialized[index as usize] = true;
+            self.inits.push(index);
+        }
+    }
+
+    /// Pops any reference, returning `None` for an unknown operand.
+    fn pop_ref(&mut self) -> Result<Option<RefType>, Error> {
+        match self.pop()? {
+            Some(ValueType::Ref(ref_type)) => Ok(Some(ref_type)),
+            Some(ty) => Err(Error::other(format!(
+                "Type mismatch: expected a reference, found {:?}",
+                ty
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    /// Pops the function reference of `call_ref` and returns its type.
+    fn call_ref(&mut self, type_index: u32) -> Result<&'b FunctionType, Error> {
+        let func_type = self.context.func_type(type_index)?;
+        self.pop_expect(reference(true, HeapType::Concrete(type_index)))?;
+        Ok(func_type)
+    }
+
+    /// Checks a memory access against the memory it names and returns the
+    /// type of its address operand.
+    fn memarg(&self, memarg: &MemArg, max_align: u32) -> Result<ValueType, Error> {
+        let memory = self.context.memory(memarg.memory)?;
+
+        if memarg.align > max_align {
+            return Err(Error::other("Alignment must not be larger than natural"));
+        }
+        if !memory.is_64() && memarg.offset > u64::from(u32::MAX) {
+            return Err(Error::other("Offset out of range for a 32-bit memory"));
+        }
+
+        Ok(address_type(memory))
+    }
+
+    fn load(&mut self, memarg: &MemArg, max_align: u32, result: ValueType) -> Result<(), Error> {
+        let address = self.memarg(memarg, max_align)?;
+        self.op(&[address], &[result])
+    }
+
+    fn store(&mut self, memarg: &MemArg, max_align: u32, value: ValueType) -> Result<(), Error> {
+        let address = self.memarg(memarg, max_align)?;
+        self.op(&[address, value], &[])
+    }
+
+    fn load_lane(&mut self, memarg: &MemArg, align: u32, lane: u8, lanes: u8) -> Result<(), Error> {
+        self.lane(lane, lanes)?;
+        let address = self.memarg(memarg, align)?;
+        self.op(&[address, V128], &[V128])
+    }
+
+    fn store_lane(
+        &mut self,
+        memarg: &MemArg,
+        align: u32,
+        lane: u8,
+        lanes: u8,
+    ) -> Result<(), Error> {
+        self.lane(lane, lanes)?;
+        let address = self.memarg(memarg, align)?;
+        self.op(&[address, V128], &[])
+    }
+
+    /// Atomic accesses take the address followed by `params`, and must be
+    /// naturally aligned.
+    fn atomic(
+        &mut self,
+        memarg: &MemArg,
+        align: u32,
+        params: &[ValueType],
+        results: &[ValueType],
+    ) -> Result<(), Error> {
+        if memarg.align != align {
+            return Err(Error::other("Atomic accesses must be naturally aligned"));
+        }
+
+        let address = self.memarg(memarg, align)?;
+        let mut operands = vec![address];
+        operands.extend(params);
+        self.op(&operands, results)
+    }
+
+    fn lane(&self, lane: u8, lanes: u8) -> Result<(), Error> {
+        if lane >= lanes {
+            return Err(Error::other(format!("Lane index {} out of range", lane)));
+        }
+        Ok(())
+    }
+
+    /// Pops the table index of an indirect call and returns the callee type.
+    fn call_indirect(
+        &mut self,
+        type_index: u32,
+        table_index: u32,
+    ) -> Result<&'b FunctionType, Error> {
+        let table = self.table(table_index)?;
+        if !self
+            .context
+            .is_ref_subtype(table.element_type, RefType::FUNCREF)
+        {
+            return Err(Error::other("Indirect calls require a funcref table"));
+        }
+        let func_type = self.context.func_type(type_index)?;
+        self.pop_expect(table_index_type(table))?;
+        Ok(func_type)
+    }
+
+    /// A tail call replaces the caller's frame, so the callee must return
+    /// exactly what the caller does.
+    fn return_call(&mut self, func_type: &FunctionType) -> Result<(), Error> {
+        if !self.all_subtypes(&func_type.return_types, &self.results) {
+            return Err(Error::other(
+                "Tail call results do not match the caller's results",
+            ));
+        }
+        self.pop_all(&func_type.param_types)?;
+        self.set_unreachable();
+        Ok(())
+    }
+
+    fn table(&self, index: u32) -> Result<&'b TableType, Error> {
+        self.context.table(index)
+    }
+
+    fn data(&self, index: u32) -> Result<(), Error> {
+        match self.context.data_count {
+            Some(count) if index < count => Ok(()),
+            Some(_) => Err(Error::other(format!("Unknown data segment {}", index))),
+            None => Err(Error::other("Data count section required")),
+        }
+    }
+
+    fn elem(&self, index: u32) -> Result<RefType, Error> {
+        self.context
+            .elements
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| Error::other(format!("Unknown element segment {}", index)))
+    }
+
+    fn all_subtypes(&self, subs: &[ValueType], sups: &[ValueType]) -> bool {
+        subs.len() == sups.len()
+            && subs
+                .iter()
+                .zip(sups)
+                .all(|(&sub, &sup)| self.context.is_subtype(sub, sup))
+    }
+
+    fn struct_field(&self, type_index: u32, field_index: u32) -> Result<FieldType, Error> {
+        self.context
+            .struct_type(type_index)?
+            .fields
+            .get(field_index as usize)
+            .cloned()
+            .ok_or_else(|| Error::other(format!("Unknown field {}", field_index)))
+    }
+
+    fn array_field(&self, type_index: u32) -> Result<FieldType, Error> {
+        Ok(self.context.array_type(type_index)?.field)
+    }
+
+    /// Pops a reference that must belong to the same hierarchy as
+    /// `ref_type`, as casts and type tests do.
+    fn pop_castable(&mut self, ref_type: RefType) -> Result<(), Error> {
+        self.context.check_heap_type(ref_type.heap_type)?;
+        let top = self.context.top_type(ref_type.heap_type);
+        self.pop_expect(reference(true, top))?;
+        Ok(())
+    }
+
+    fn struct_get(
+        &mut self,
+        type_index: u32,
+        field_index: u32,
+        signed: Option<bool>,
+    ) -> Result<(), Error> {
+        let field = self.struct_field(type_index, field_index)?;
+        check_packing(field, signed)?;
+        let object = reference(true, HeapType::Concrete(type_index));
+        self.op(&[object], &[field.storage_type.unpacked()])
+    }
+
+    fn array_get(&mut self, type_index: u32, signed: Option<bool>) -> Result<(), Error> {
+        let field = self.array_field(type_index)?;
+        check_packing(field, signed)?;
+        let array = reference(true, HeapType::Concrete(type_index));
+        self.op(&[array, I32], &[field.storage_type.unpacked()])
+    }
+
+    /// Arrays can only be filled from data segments if their elements are
+    /// numbers or vectors.
+    fn array_data(&self, type_index: u32, data_index: u32) -> Result<FieldType, Error> {
+        let field = self.array_field(type_index)?;
+        if field.storage_type.unpacked().is_reference() {
+            return Err(Error::other("Array elements must be numeric or vector"));
+        }
+        self.data(data_index)?;
+        Ok(field)
+    }
+
+    fn array_elem(&self, type_index: u32, elem_index: u32) -> Result<FieldType, Error> {
+        let field = self.array_field(type_index)?;
+        let element_type = ValueType::Ref(self.elem(elem_index)?);
+        if !self
+            .context
+            .is_storage_subtype(StorageType::Value(element_type), field.storage_type)
+        {
+            return Err(Error::other(format!(
+                "Element segment {} does not match the array element type",
+                elem_index
+            )));
+        }
+        Ok(field)
+    }
+
+    /// Pops a reference to `heap_type` and returns whether it may be null.
+    /// An unknown operand is treated as non-null.
+    fn pop_nullable(&mut self, heap_type: HeapType) -> Result<bool, Error> {
+        match self.pop_expect(reference(true, heap_type))? {
+            Some(ValueType::Ref(ref_type)) => Ok(ref_type.nullable),
+            _ => Ok(false),
+        }
+    }
+
+    /// Types `br_on_cast` and `br_on_cast_fail`: the operand is popped as
+    /// `from`, `taken` is passed to the label and `fallthrough` stays on
+    /// the stack.
+    fn br_on_cast(
+        &mut self,
+        label: u32,
+        from: RefType,
+        to: RefType,
+        taken: RefType,
+        fallthrough: RefType,
+    ) -> Result<(), Error> {
+        self.context.check_heap_type(from.heap_type)?;
+        self.context.check_heap_type(to.heap_type)?;
+        if !self.context.is_ref_subtype(to, from) {
+            return Err(Error::other("Cast target must be a subtype of its source"));
+        }
+
+        let mut types = self.label_types(label)?;
+        match types.pop() {
+            Some(last) if self.context.is_subtype(ValueType::Ref(taken), last) => {}
+            _ => {
+                return Err(Error::other(format!(
+                    "Cast does not match the type of label {}",
+                    label
+                )))
+            }
+        }
+
+        self.pop_expect(ValueType::Ref(from))?;
+        self.op(&types, &types)?;
+        self.push(ValueType::Ref(fallthrough));
+        Ok(())
+    }
+
+    fn operator(&mut self, operator: &Operator) -> Result<(), Error> {
+        match *operator {
+            Operator::Unreachable => self.set_unreachable(),
+            Operator::Nop => {}
+            Operator::Block(block_type) => {
+                let (params, results) = self.block_type(block_type)?;
+                self.pop_all(&params)?;
+                self.push_frame(FrameKind::Block, params, results);
+            }
+            Operator::Loop(block_type) => {
+                let (params, results) = self.block_type(block_type)?;
+                self.pop_all(&params)?;
+                self.push_frame(FrameKind::Loop, params, results);
+            }
+            Operator::If(block_type) => {
+                let (params, results) = self.block_type(block_type)?;
+                self.pop_expect(I32)?;
+                self.pop_all(&params)?;
+                self.push_frame(FrameKind::If, params, results);
+            }
+            Operator::Else => {
+                let frame = self.pop_frame()?;
+                if frame.kind != FrameKind::If {
+                    return Err(Error::other("Else without a matching if"));
+                }
+                self.push_frame(FrameKind::Else, frame.params, frame.results);
+            }
+            Operator::Try(block_type) => {
+                let (params, results) = self.block_type(block_type)?;
+                self.pop_all(&params)?;
+                self.push_frame(FrameKind::Try, params, results);
+            }
+            Operator::Catch(tag) => {
+                let frame = self.pop_frame()?;
+                if frame.kind != FrameKind::Try && frame.kind != FrameKind::Catch {
+                    return Err(Error::other("Catch without a matching try"));
+                }
+                let params = self.context.tag_type(tag)?.param_types.clone();
+                self.push_frame(FrameKind::Catch, params, frame.results);
+            }
+            Operator::CatchAll => {
+                let frame = self.pop_frame()?;
+                if frame.kind != FrameKind::Try && frame.kind != FrameKind::Catch {
+                    return Err(Error::other("Catch_all without a matching try"));
+                }
+                self.push_frame(FrameKind::CatchAll, Vec::new(), frame.results);
+            }
+            Operator::Delegate(depth) => {
+                let frame = self.pop_frame()?;
+                if frame.kind != FrameKind::Try {
+                    return Err(Error::other("Delegate without a matching try"));
+                }
+                self.label_types(depth)?;
+                self.operands
+                    .extend(frame.results.iter().map(|&ty| Some(ty)));
+            }
+            Operator::Throw(tag) => {
+                let params = self.context.tag_type(tag)?.param_types.clone();
+                self.pop_all(&params)?;
+                self.set_unreachable();
+            }
+            Operator::Rethrow(depth) => {
+                let kind = self.frame(depth)?.kind;
+                if kind != FrameKind::Catch && kind != FrameKind::CatchAll {
+                    return Err(Error::other("Rethrow must target a catch block"));
+                }
+                self.set_unreachable();
+            }
+            Operator::ThrowRef => {
+                self.pop_expect(ValueType::EXNREF)?;
+                self.set_unreachable();
+            }
+            Operator::TryTable {
+                block_type,
+                ref catches,
+            } => {
+                let (params, results) = self.block_type(block_type)?;
+                self.pop_all(&params)?;
+                for &catch in catches {
+                    self.try_table_catch(catch)?;
+                }
+                self.push_frame(FrameKind::Block, params, results);
+            }
+            Operator::End => {
+                let frame = self.pop_frame()?;
+                if frame.kind == FrameKind::If && frame.params != frame.results {
+                    return Err(Error::other(
+                        "If without else must leave its params unchanged",
+                    ));
+                }
+                self.operands
+                    .extend(frame.results.iter().map(|&ty| Some(ty)));
+            }
+            Operator::Br(depth) => {
+                let types = self.label_types(depth)?;
+                self.pop_all(&types)?;
+                self.set_unreachable();
+            }
+            Operator::BrIf(depth) => {
+                let types = self.label_types(depth)?;
+                self.pop_expect(I32)?;
+                self.op(&types, &types)?;
+            }
+            Operator::BrTable {
+                ref targets,
+                default,
+            } => {
+                self.pop_expect(I32)?;
+                let arity = self.label_types(default)?.len();
+
+                for &target in targets.iter().chain(Some(&default)) {
+                    let types = self.label_types(target)?;
+                    if types.len() != arity {
+                        return Err(Error::other("br_table targets have different arity"));
+                    }
+                    let popped = self.pop_all(&types)?;
+                    self.operands.extend(popped);
+                }
+
+                let types = self.label_types(default)?;
+                self.pop_all(&types)?;
+                self.set_unreachable();
+            }
+            Operator::Return => {
+                let results = self.results.clone();
+                self.pop_all(&results)?;
+                self.set_unreachable();
+            }
+            Operator::Call(index) => {
+                let func_type = self.context.function_type(index)?;
+                self.op(&func_type.param_types, &func_type.return_types)?;
+            }
+            Operator::CallIndirect {
+                type_index,
+                table_index,
+            } => {
+                let func_type = self.call_indirect(type_index, table_index)?;
+                self.op(&func_type.param_types, &func_type.return_types)?;
+            }
+            Operator::ReturnCall(index) => {
+                let func_type = self.context.function_type(index)?;
+                self.return_call(func_type)?;
+            }
+            Operator::ReturnCallIndirect {
+                type_index,
+                table_index,
+            } => {
+                let func_type = self.call_indirect(type_index, table_index)?;
+                self.return_call(func_type)?;
+            }
+            Operator::CallRef(type_index) => {
+                let func_type = self.call_ref(type_index)?;
+                self.op(&func_type.param_types, &func_type.return_types)?;
+            }
+            Operator::ReturnCallRef(type_index) => {
+                let func_type = self.call_ref(type_index)?;
+                self.return_call(func_type)?;
+            }
+            Operator::Drop => {
+                self.pop()?;
+            }
+            Operator::Select => {
+                self.pop_expect(I32)?;
+                let first = self.pop()?;
+                let second = self.pop()?;
+
+                for ty in first.iter().chain(second.iter()) {
+                    if ty.is_reference() {
+                        return Err(Error::other("Untyped select requires numeric operands"));
+                    }
+                }
+
+                match (first, second) {
+                    (Some(first), Some(second)) if first != second => {
+                        return Err(Error::other("Select operands have different types"))
+                    }
+                    _ => self.operands.push(first.or(second)),
+                }
+            }
+            Operator::TypedSelect(ref types) => {
+                if types.len() != 1 {
+                    return Err(Error::other("Typed select must have exactly one type"));
+                }
+                self.context.check_value_type(types[0])?;
+                self.op(&[types[0], types[0], I32], &[types[0]])?;
+            }
+            Operator::LocalGet(index) => {
+                let ty = self.local(index)?;
+                if !self.initialized[index as usize] {
+                    return Err(Error::other(format!("Uninitialized local {}", index)));
+                }
+                self.push(ty);
+            }
+            Operator::LocalSet(index) => {
+                let ty = self.local(index)?;
+                self.pop_expect(ty)?;
+                self.set_local(index);
+            }
+            Operator::LocalTee(index) => {
+                let ty = self.local(index)?;
+                self.op(&[ty], &[ty])?;
+                self.set_local(index);
+            }
+            Operator::GlobalGet(index) => {
+                let ty = self.context.global(index)?.content_type;
+                self.push(ty);
+            }
+            Operator::GlobalSet(index) => {
+                let global = self.context.global(index)?;
+                if global.mutability == 0 {
+                    return Err(Error::other(format!("Global {} is immutable", index)));
+                }
+                self.pop_expect(global.content_type)?;
+            }
+            Operator::TableGet(index) => {
+                let table = self.table(index)?;
+                self.op(&[table_index_type(table)], &[element_type(table)])?;
+            }
+            Operator::TableSet(index) => {
+                let table = self.table(index)?;
+                self.op(&[table_index_type(table), element_type(table)], &[])?;
+            }
+            Operator::I32Load(ref memarg) => self.load(memarg, 2, I32)?,
+            Operator::I64Load(ref memarg) => self.load(memarg, 3, I64)?,
+            Operator::F32Load(ref memarg) => self.load(memarg, 2, F32)?,
+            Operator::F64Load(ref memarg) => self.load(memarg, 3, F64)?,
+            Operator::I32Load8S(ref memarg) | Operator::I32Load8U(ref memarg) => {
+                self.load(memarg, 0, I32)?
+            }
+            Operator::I32Load16S(ref memarg) | Operator::I32Load16U(ref memarg) => {
+                self.load(memarg, 1, I32)?
+            }
+            Operator::I64Load8S(ref memarg) | Operator::I64Load8U(ref memarg) => {
+                self.load(memarg, 0, I64)?
+            }
+            Operator::I64Load16S(ref memarg) | Operator::I64Load16U(ref memarg) => {
+                self.load(memarg, 1, I64)?
+            }
+            Operator::I64Load32S(ref memarg) | Operator::I64Load32U(ref memarg) => {
+                self.load(memarg, 2, I64)?
+            }
+            Operator::I32Store(ref memarg) => self.store(memarg, 2, I32)?,
+            Operator::I64Store(ref memarg) => self.store(memarg, 3, I64)?,
+            Operator::F32Store(ref memarg) => self.store(memarg, 2, F32)?,
+            Operator::F64Store(ref memarg) => self.store(memarg, 3, F64)?,
+            Operator::I32Store8(ref memarg) => self.store(memarg, 0, I32)?,
+            Operator::I32Store16(ref memarg) => self.store(memarg, 1, I32)?,
+            Operator::I64Store8(ref memarg) => self.store(memarg, 0, I64)?,
+            Operator::I64Store16(ref memarg) => self.store(memarg, 1, I64)?,
+            Operator::I64Store32(ref memarg) => self.store(memarg, 2, I64)?,
+            Operator::MemorySize(memory) => {
+                let address = address_type(self.context.memory(memory)?);
+                self.push(address);
+            }
+            Operator::MemoryGrow(memory) => {
+                let address = address_type(self.context.memory(memory)?);
+                self.op(&[address], &[address])?;
+            }
+            Operator::I32Const(_) => self.push(I32),
+            Operator::I64Const(_) => self.push(I64),
+            Operator::F32Const(_) => self.push(F32),
+            Operator::F64Const(_) => self.push(F64),
+            Operator::I32Eqz => self.op(&[I32], &[I32])?,
+            Operator::I32Eq
+            | Operator::I32Ne
+            | Operator::I32LtS
+            | Operator::I32LtU
+            | Operator::I32GtS
+            | Operator::I32GtU
+            | Operator::I32LeS
+            | Operator::I32LeU
+            | Operator::I32GeS
+            | Operator::I32GeU => self.op(&[I32, I32], &[I32])?,
+            Operator::I64Eqz => self.op(&[I64], &[I32])?,
+            Operator::I64Eq
+            | Operator::I64Ne
+            | Operator::I64LtS
+            | Operator::I64LtU
+            | Operator::I64GtS
+            | Operator::I64GtU
+            | Operator::I64LeS
+            | Operator::I64LeU
+            | Operator::I64GeS
+            | Operator::I64GeU => self.op(&[I64, I64], &[I32])?,
+            Operator::F32Eq
+            | Operator::F32Ne
+            | Operator::F32Lt
+            | Operator::F32Gt
+            | Operator::F32Le
+            | Operator::F32Ge => self.op(&[F32, F32], &[I32])?,
+            Operator::F64Eq
+            | Operator::F64Ne
+            | Operator::F64Lt
+            | Operator::F64Gt
+            | Operator::F64Le
+            | Operator::F64Ge => self.op(&[F64, F64], &[I32])?,
+            Operator::I32Clz
+            | Operator::I32Ctz
+            | Operator::I32Popcnt
+            | Operator::I32Extend8S
+            | Operator::I32Extend16S => self.op(&[I32], &[I32])?,
+            Operator::I32Add
+            | Operator::I32Sub
+            | Operator::I32Mul
+            | Operator::I32DivS
+            | Operator::I32DivU
+            | Operator::I32RemS
+            | Operator::I32RemU
+            | Operator::I32And
+            | Operator::I32Or
+            | Operator::I32Xor
+            | Operator::I32Shl
+            | Operator::I32ShrS
+            | Operator::I32ShrU
+            | Operator::I32Rotl
+            | Operator::I32Rotr => self.op(&[I32, I32], &[I32])?,
+            Operator::I64Clz
+            | Operator::I64Ctz
+            | Operator::I64Popcnt
+            | Operator::I64Extend8S
+            | Operator::I64Extend16S
+            | Operator::I64Extend32S => self.op(&[I64], &[I64])?,
+            Operator::I64Add
+            | Operator::I64Sub
+            | Operator::I64Mul
+            | Operator::I64DivS
+            | Operator::I64DivU
+            | Operator::I64RemS
+            | Operator::I64RemU
+            | Operator::I64And
+            | Operator::I64Or
+            | Operator::I64Xor
+            | Operator::I64Shl
+            | Operator::I64ShrS
+            | Operator::I64ShrU
+            | Operator::I64Rotl
+            | Operator::I64Rotr => self.op(&[I64, I64], &[I64])?,
+            Operator::F32Abs
+            | Operator::F32Neg
+            | Operator::F32Ceil
+            | Operator::F32Floor
+            | Operator::F32Trunc
+            | Operator::F32Nearest
+            | Operator::F32Sqrt => self.op(&[F32], &[F32])?,
+            Operator::F32Add
+            | Operator::F32Sub
+            | Operator::F32Mul
+            | Operator::F32Div
+            | Operator::F32Min
+            | Operator::F32Max
+            | Operator::F32Copysign => self.op(&[F32, F32], &[F32])?,
+            Operator::F64Abs
+            | Operator::F64Neg
+            | Operator::F64Ceil
+            | Operator::F64Floor
+            | Operator::F64Trunc
+            | Operator::F64Nearest
+            | Operator::F64Sqrt => self.op(&[F64], &[F64])?,
+            Operator::F64Add
+            | Operator::F64Sub
+            | Operator::F64Mul
+            | Operator::F64Div
+            | Operator::F64Min
+            | Operator::F64Max
+            | Operator::F64Copysign => self.op(&[F64, F64], &[F64])?,
+            Operator::I32WrapI64 => self.op(&[I64], &[I32])?,
+            Operator::I32TruncF32S
+            | Operator::I32TruncF32U
+            | Operator::I32TruncSatF32S
+            | Operator::I32TruncSatF32U
+            | Operator::I32ReinterpretF32 => self.op(&[F32], &[I32])?,
+            Operator::I32TruncF64S
+            | Operator::I32TruncF64U
+            | Operator::I32TruncSatF64S
+            | Operator::I32TruncSatF64U => self.op(&[F64], &[I32])?,
+            Operator::I64ExtendI32S | Operator::I64ExtendI32U => self.op(&[I32], &[I64])?,
+            Operator::I64TruncF32S
+            | Operator::I64TruncF32U
+            | Operator::I64TruncSatF32S
+            | Operator::I64TruncSatF32U => self.op(&[F32], &[I64])?,
+            Operator::I64TruncF64S
+            | Operator::I64TruncF64U
+            | Operator::I64TruncSatF64S
+            | Operator::I64TruncSatF64U
+            | Operator::I64ReinterpretF64 => self.op(&[F64], &[I64])?,
+            Operator::F32ConvertI32S | Operator::F32ConvertI32U | Operator::F32ReinterpretI32 => {
+                self.op(&[I32], &[F32])?
+            }
+            Operator::F32ConvertI64S | Operator::F32ConvertI64U => self.op(&[I64], &[F32])?,
+            Operator::F32DemoteF64 => self.op(&[F64], &[F32])?,
+            Operator::F64ConvertI32S | Operator::F64ConvertI32U => self.op(&[I32], &[F64])?,
+            Operator::F64ConvertI64S | Operator::F64ConvertI64U | Operator::F64ReinterpretI64 => {
+                self.op(&[I64], &[F64])?
+            }
+            Operator::F64PromoteF32 => self.op(&[F32], &[F64])?,
+            Operator::MemoryInit { data_index, memory } => {
+                self.data(data_index)?;
+                let address = address_type(self.context.memory(memory)?);
+                self.op(&[address, I32, I32], &[])?;
+            }
+            Operator::DataDrop(index) => self.data(index)?,
+            Operator::MemoryCopy { dst, src } => {
+                let dst = address_type(self.context.memory(dst)?);
+                let src = address_type(self.context.memory(src)?);
+                // As with tables, the length is only 64-bit when both
+                // memories are.
+                let len = if dst == I64 && src == I64 { I64 } else { I32 };
+                self.op(&[dst, src, len], &[])?;
+            }
+            Operator::MemoryFill(memory) => {
+                let address = address_type(self.context.memory(memory)?);
+                self.op(&[address, I32, address], &[])?;
+            }
+            Operator::TableInit { elem_index, table } => {
+                self.elem(elem_index)?;
+                let index = table_index_type(self.table(table)?);
+                self.op(&[index, I32, I32], &[])?;
+            }
+            Operator::ElemDrop(index) => {
+                self.elem(index)?;
+            }
+            Operator::TableCopy { dst, src } => {
+                let dst = table_index_type(self.table(dst)?);
+                let src = table_index_type(self.table(src)?);
+                // The length is only 64-bit when both tables are.
+                let len = if dst == I64 && src == I64 { I64 } else { I32 };
+                self.op(&[dst, src, len], &[])?;
+            }
+            Operator::RefNull(heap_type) => {
+                self.context.check_heap_type(heap_type)?;
+                self.push(reference(true, heap_type));
+            }
+            Operator::RefIsNull => {
+                if let Some(ty) = self.pop()? {
+                    if !ty.is_reference() {
+                        return Err(Error::other("ref.is_null requires a reference operand"));
+                    }
+                }
+                self.push(I32);
+            }
+            Operator::RefFunc(index) => {
+                self.context.function_type(index)?;
+                let type_index = self.context.functions[index as usize];
+                self.push(reference(false, HeapType::Concrete(type_index)));
+            }
+            Operator::RefAsNonNull => match self.pop_ref()? {
+                Some(ref_type) => self.push(reference(false, ref_type.heap_type)),
+                None => self.operands.push(None),
+            },
+            Operator::BrOnNull(depth) => {
+                let operand = self.pop_ref()?;
+                let types = self.label_types(depth)?;
+                self.op(&types, &types)?;
+                match operand {
+                    Some(ref_type) => self.push(reference(false, ref_type.heap_type)),
+                    None => self.operands.push(None),
+                }
+            }
+            Operator::BrOnNonNull(depth) => {
+                let operand = self.pop_ref()?;
+                let mut types = self.label_types(depth)?;
+                let matches = match (types.pop(), operand) {
+                    (Some(last), Some(ref_type)) => self
+                        .context
+                        .is_subtype(reference(false, ref_type.heap_type), last),
+                    (Some(last), None) => last.is_reference(),
+                    (None, _) => false,
+                };
+                if !matches {
+                    return Err(Error::other(format!(
+                        "br_on_non_null does not match the type of label {}",
+                        depth
+                    )));
+                }
+                self.op(&types, &types)?;
+            }
+            Operator::RefEq => {
+                let eqref = reference(true, HeapType::Eq);
+                self.op(&[eqref, eqref], &[I32])?;
+            }
+            Operator::TableGrow(index) => {
+                let table = self.table(index)?;
+                let index = table_index_type(table);
+                self.op(&[element_type(table), index], &[index])?;
+            }
+            Operator::TableSize(index) => {
+                let index = table_index_type(self.table(index)?);
+                self.push(index);
+            }
+            Operator::TableFill(index) => {
+                let table = self.table(index)?;
+                let index = table_index_type(table);
+                self.op(&[index, element_type(table), index], &[])?;
+            }
+            Operator::V128Load(ref memarg) => self.load(memarg, 4, V128)?,
+            Operator::V128Load8x8S(ref memarg) => self.load(memarg, 3, V128)?,
+            Operator::V128Load8x8U(ref memarg) => self.load(memarg, 3, V128)?,
+            Operator::V128Load16x4S(ref memarg) => self.load(memarg, 3, V128)?,
+            Operator::V128Load16x4U(ref memarg) => self.load(memarg, 3, V128)?,
+            Operator::V128Load32x2S(ref memarg) => self.load(memarg, 3, V128)?,
+            Operator::V128Load32x2U(ref memarg) => self.load(memarg, 3, V128)?,
+            Operator::V128Load8Splat(ref memarg) => self.load(memarg, 0, V128)?,
+            Operator::V128Load16Splat(ref memarg) => self.load(memarg, 1, V128)?,
+            Operator::V128Load32Splat(ref memarg) => self.load(memarg, 2, V128)?,
+            Operator::V128Load64Splat(ref memarg) => self.load(memarg, 3, V128)?,
+            Operator::V128Store(ref memarg) => self.store(memarg, 4, V128)?,
+            Operator::V128Const(_) => self.push(V128),
+            Operator::I8x16Shuffle(ref lanes) => {
+                if lanes.iter().any(|&lane| lane >= 32) {
+                    return Err(Error::other("Shuffle lane index out of range"));
+                }
+                self.op(&[V128, V128], &[V128])?
+            }
+            Operator::V128Load8Lane { ref memarg, lane } => self.load_lane(memarg, 0, lane, 16)?,
+            Operator::V128Load16Lane { ref memarg, lane } => self.load_lane(memarg, 1, lane, 8)?,
+            Operator::V128Load32Lane { ref memarg, lane } => self.load_lane(memarg, 2, lane, 4)?,
+            Operator::V128Load64Lane { ref memarg, lane } => self.load_lane(memarg, 3, lane, 2)?,
+            Operator::V128Store8Lane { ref memarg, lane } => {
+                self.store_lane(memarg, 0, lane, 16)?
+            }
+            Operator::V128Store16Lane { ref memarg, lane } => {
+                self.store_lane(memarg, 1, lane, 8)?
+            }
+            Operator::V128Store32Lane { ref memarg, lane } => {
+                self.store_lane(memarg, 2, lane, 4)?
+            }
+            Operator::V128Store64Lane { ref memarg, lane } => {
+                self.store_lane(memarg, 3, lane, 2)?
+            }
+            Operator::V128Load32Zero(ref memarg) => self.load(memarg, 2, V128)?,
+            Operator::V128Load64Zero(ref memarg) => self.load(memarg, 3, V128)?,
+            Operator::I8x16Swizzle
+            | Operator::I8x16Eq
+            | Operator::I8x16Ne
+            | Operator::I8x16LtS
+            | Operator::I8x16LtU
+            | Operator::I8x16GtS
+            | Operator::I8x16GtU
+            | Operator::I8x16LeS
+            | Operator::I8x16LeU
+            | Operator::I8x16GeS
+            | Operator::I8x16GeU
+            | Operator::I16x8Eq
+            | Operator::I16x8Ne
+            | Operator::I16x8LtS
+            | Operator::I16x8LtU
+            | Operator::I16x8GtS
+            | Operator::I16x8GtU
+            | Operator::I16x8LeS
+            | Operator::I16x8LeU
+            | Operator::I16x8GeS
+            | Operator::I16x8GeU
+            | Operator::I32x4Eq
+            | Operator::I32x4Ne
+            | Operator::I32x4LtS
+            | Operator::I32x4LtU
+            | Operator::I32x4GtS
+            | Operator::I32x4GtU
+            | Operator::I32x4LeS
+            | Operator::I32x4LeU
+            | Operator::I32x4GeS
+            | Operator::I32x4GeU
+            | Operator::F32x4Eq
+            | Operator::F32x4Ne
+            | Operator::F32x4Lt
+            | Operator::F32x4Gt
+            | Operator::F32x4Le
+            | Operator::F32x4Ge
+            | Operator::F64x2Eq
+            | Operator::F64x2Ne
+            | Operator::F64x2Lt
+            | Operator::F64x2Gt
+            | Operator::F64x2Le
+            | Operator::F64x2Ge
+            | Operator::V128And
+            | Operator::V128Andnot
+            | Operator::V128Or
+            | Operator::V128Xor
+            | Operator::I8x16NarrowI16x8S
+            | Operator::I8x16NarrowI16x8U
+            | Operator::I8x16Add
+            | Operator::I8x16AddSatS
+            | Operator::I8x16AddSatU
+            | Operator::I8x16Sub
+            | Operator::I8x16SubSatS
+            | Operator::I8x16SubSatU
+            | Operator::I8x16MinS
+            | Operator::I8x16MinU
+            | Operator::I8x16MaxS
+            | Operator::I8x16MaxU
+            | Operator::I8x16AvgrU
+            | Operator::I16x8Q15mulrSatS
+            | Operator::I16x8NarrowI32x4S
+            | Operator::I16x8NarrowI32x4U
+            | Operator::I16x8Add
+            | Operator::I16x8AddSatS
+            | Operator::I16x8AddSatU
+            | Operator::I16x8Sub
+            | Operator::I16x8SubSatS
+            | Operator::I16x8SubSatU
+            | Operator::I16x8Mul
+            | Operator::I16x8MinS
+            | Operator::I16x8MinU
+            | Operator::I16x8MaxS
+            | Operator::I16x8MaxU
+            | Operator::I16x8AvgrU
+            | Operator::I16x8ExtmulLowI8x16S
+            | Operator::I16x8ExtmulHighI8x16S
+            | Operator::I16x8ExtmulLowI8x16U
+            | Operator::I16x8ExtmulHighI8x16U
+            | Operator::I32x4Add
+            | Operator::I32x4Sub
+            | Operator::I32x4Mul
+            | Operator::I32x4MinS
+            | Operator::I32x4MinU
+            | Operator::I32x4MaxS
+            | Operator::I32x4MaxU
+            | Operator::I32x4DotI16x8S
+            | Operator::I32x4ExtmulLowI16x8S
+            | Operator::I32x4ExtmulHighI16x8S
+            | Operator::I32x4ExtmulLowI16x8U
+            | Operator::I32x4ExtmulHighI16x8U
+            | Operator::I64x2Add
+            | Operator::I64x2Sub
+            | Operator::I64x2Mul
+            | Operator::I64x2Eq
+            | Operator::I64x2Ne
+            | Operator::I64x2LtS
+            | Operator::I64x2GtS
+            | Operator::I64x2LeS
+            | Operator::I64x2GeS
+            | Operator::I64x2ExtmulLowI32x4S
+            | Operator::I64x2ExtmulHighI32x4S
+            | Operator::I64x2ExtmulLowI32x4U
+            | Operator::I64x2ExtmulHighI32x4U
+            | Operator::F32x4Add
+            | Operator::F32x4Sub
+            | Operator::F32x4Mul
+            | Operator::F32x4Div
+            | Operator::F32x4Min
+            | Operator::F32x4Max
+            | Operator::F32x4Pmin
+            | Operator::F32x4Pmax
+            | Operator::F64x2Add
+            | Operator::F64x2Sub
+            | Operator::F64x2Mul
+            | Operator::F64x2Div
+            | Operator::F64x2Min
+            | Operator::F64x2Max
+            | Operator::F64x2Pmin
+            | Operator::F64x2Pmax
+            | Operator::I8x16RelaxedSwizzle
+            | Operator::F32x4RelaxedMin
+            | Operator::F32x4RelaxedMax
+            | Operator::F64x2RelaxedMin
+            | Operator::F64x2RelaxedMax
+            | Operator::I16x8RelaxedQ15mulrS
+            | Operator::I16x8RelaxedDotI8x16I7x16S => self.op(&[V128, V128], &[V128])?,
+            Operator::I8x16Splat | Operator::I16x8Splat | Operator::I32x4Splat => {
+                self.op(&[I32], &[V128])?
+            }
+            Operator::I64x2Splat => self.op(&[I64], &[V128])?,
+            Operator::F32x4Splat => self.op(&[F32], &[V128])?,
+            Operator::F64x2Splat => self.op(&[F64], &[V128])?,
+            Operator::I8x16ExtractLaneS(lane) | Operator::I8x16ExtractLaneU(lane) => {
+                self.lane(lane, 16)?;
+                self.op(&[V128], &[I32])?
+            }
+            Operator::I8x16ReplaceLane(lane) => {
+                self.lane(lane, 16)?;
+                self.op(&[V128, I32], &[V128])?
+            }
+            Operator::I16x8ExtractLaneS(lane) | Operator::I16x8ExtractLaneU(lane) => {
+                self.lane(lane, 8)?;
+                self.op(&[V128], &[I32])?
+            }
+            Operator::I16x8ReplaceLane(lane) => {
+                self.lane(lane, 8)?;
+                self.op(&[V128, I32], &[V128])?
+            }
+            Operator::I32x4ExtractLane(lane) => {
+                self.lane(lane, 4)?;
+                self.op(&[V128], &[I32])?
+            }
+            Operator::I32x4ReplaceLane(lane) => {
+                self.lane(lane, 4)?;
+                self.op(&[V128, I32], &[V128])?
+            }
+            Operator::I64x2ExtractLane(lane) => {
+                self.lane(lane, 2)?;
+                self.op(&[V128], &[I64])?
+            }
+            Operator::I64x2ReplaceLane(lane) => {
+                self.lane(lane, 2)?;
+                self.op(&[V128, I64], &[V128])?
+            }
+            Operator::F32x4ExtractLane(lane) => {
+                self.lane(lane, 4)?;
+                self.op(&[V128], &[F32])?
+            }
+            Operator::F32x4ReplaceLane(lane) => {
+                self.lane(lane, 4)?;
+                self.op(&[V128, F32], &[V128])?
+            }
+            Operator::F64x2ExtractLane(lane) => {
+                self.lane(lane, 2)?;
+                self.op(&[V128], &[F64])?
+            }
+            Operator::F64x2ReplaceLane(lane) => {
+                self.lane(lane, 2)?;
+                self.op(&[V128, F64], &[V128])?
+            }
+            Operator::V128Not
+            | Operator::F32x4DemoteF64x2Zero
+            | Operator::F64x2PromoteLowF32x4
+            | Operator::I8x16Abs
+            | Operator::I8x16Neg
+            | Operator::I8x16Popcnt
+            | Operator::F32x4Ceil
+            | Operator::F32x4Floor
+            | Operator::F32x4Trunc
+            | Operator::F32x4Nearest
+            | Operator::F64x2Ceil
+            | Operator::F64x2Floor
+            | Operator::F64x2Trunc
+            | Operator::I16x8ExtaddPairwiseI8x16S
+            | Operator::I16x8ExtaddPairwiseI8x16U
+            | Operator::I32x4ExtaddPairwiseI16x8S
+            | Operator::I32x4ExtaddPairwiseI16x8U
+            | Operator::I16x8Abs
+            | Operator::I16x8Neg
+            | Operator::I16x8ExtendLowI8x16S
+            | Operator::I16x8ExtendHighI8x16S
+            | Operator::I16x8ExtendLowI8x16U
+            | Operator::I16x8ExtendHighI8x16U
+            | Operator::F64x2Nearest
+            | Operator::I32x4Abs
+            | Operator::I32x4Neg
+            | Operator::I32x4ExtendLowI16x8S
+            | Operator::I32x4ExtendHighI16x8S
+            | Operator::I32x4ExtendLowI16x8U
+            | Operator::I32x4ExtendHighI16x8U
+            | Operator::I64x2Abs
+            | Operator::I64x2Neg
+            | Operator::I64x2ExtendLowI32x4S
+            | Operator::I64x2ExtendHighI32x4S
+            | Operator::I64x2ExtendLowI32x4U
+            | Operator::I64x2ExtendHighI32x4U
+            | Operator::F32x4Abs
+            | Operator::F32x4Neg
+            | Operator::F32x4Sqrt
+            | Operator::F64x2Abs
+            | Operator::F64x2Neg
+            | Operator::F64x2Sqrt
+            | Operator::I32x4TruncSatF32x4S
+            | Operator::I32x4TruncSatF32x4U
+            | Operator::F32x4ConvertI32x4S
+            | Operator::F32x4ConvertI32x4U
+            | Operator::I32x4TruncSatF64x2SZero
+            | Operator::I32x4TruncSatF64x2UZero
+            | Operator::F64x2ConvertLowI32x4S
+            | Operator::F64x2ConvertLowI32x4U
+            | Operator::I32x4RelaxedTruncF32x4S
+            | Operator::I32x4RelaxedTruncF32x4U
+            | Operator::I32x4RelaxedTruncF64x2SZero
+            | Operator::I32x4RelaxedTruncF64x2UZero => self.op(&[V128], &[V128])?,
+            Operator::V128Bitselect
+            | Operator::F32x4RelaxedMadd
+            | Operator::F32x4RelaxedNmadd
+            | Operator::F64x2RelaxedMadd
+            | Operator::F64x2RelaxedNmadd
+            | Operator::I8x16RelaxedLaneselect
+            | Operator::I16x8RelaxedLaneselect
+            | Operator::I32x4RelaxedLaneselect
+            | Operator::I64x2RelaxedLaneselect
+            | Operator::I32x4RelaxedDotI8x16I7x16AddS => self.op(&[V128, V128, V128], &[V128])?,
+            Operator::V128AnyTrue
+            | Operator::I8x16AllTrue
+            | Operator::I8x16Bitmask
+            | Operator::I16x8AllTrue
+            | Operator::I16x8Bitmask
+            | Operator::I32x4AllTrue
+            | Operator::I32x4Bitmask
+            | Operator::I64x2AllTrue
+            | Operator::I64x2Bitmask => self.op(&[V128], &[I32])?,
+            Operator::I8x16Shl
+            | Operator::I8x16ShrS
+            | Operator::I8x16ShrU
+            | Operator::I16x8Shl
+            | Operator::I16x8ShrS
+            | Operator::I16x8ShrU
+            | Operator::I32x4Shl
+            | Operator::I32x4ShrS
+            | Operator::I32x4ShrU
+            | Operator::I64x2Shl
+            | Operator::I64x2ShrS
+            | Operator::I64x2ShrU => self.op(&[V128, I32], &[V128])?,
+            Operator::MemoryAtomicNotify(ref memarg) => self.atomic(memarg, 2, &[I32], &[I32])?,
+            Operator::MemoryAtomicWait32(ref memarg) => {
+                self.atomic(memarg, 2, &[I32, I64], &[I32])?
+            }
+            Operator::MemoryAtomicWait64(ref memarg) => {
+                self.atomic(memarg, 3, &[I64, I64], &[I32])?
+            }
+            Operator::AtomicFence => {}
+            Operator::I32AtomicLoad(ref memarg) => self.atomic(memarg, 2, &[], &[I32])?,
+            Operator::I64AtomicLoad(ref memarg) => self.atomic(memarg, 3, &[], &[I64])?,
+            Operator::I32AtomicLoad8U(ref memarg) => self.atomic(memarg, 0, &[], &[I32])?,
+            Operator::I32AtomicLoad16U(ref memarg) => self.atomic(memarg, 1, &[], &[I32])?,
+            Operator::I64AtomicLoad8U(ref memarg) => self.atomic(memarg, 0, &[], &[I64])?,
+            Operator::I64AtomicLoad16U(ref memarg) => self.atomic(memarg, 1, &[], &[I64])?,
+            Operator::I64AtomicLoad32U(ref memarg) => self.atomic(memarg, 2, &[], &[I64])?,
+            Operator::I32AtomicStore(ref memarg) => self.atomic(memarg, 2, &[I32], &[])?,
+            Operator::I64AtomicStore(ref memarg) => self.atomic(memarg, 3, &[I64], &[])?,
+            Operator::I32AtomicStore8(ref memarg) => self.atomic(memarg, 0, &[I32], &[])?,
+            Operator::I32AtomicStore16(ref memarg) => self.atomic(memarg, 1, &[I32], &[])?,
+            Operator::I64AtomicStore8(ref memarg) => self.atomic(memarg, 0, &[I64], &[])?,
+            Operator::I64AtomicStore16(ref memarg) => self.atomic(memarg, 1, &[I64], &[])?,
+            Operator::I64AtomicStore32(ref memarg) => self.atomic(memarg, 2, &[I64], &[])?,
+            Operator::I32AtomicRmwAdd(ref memarg) => self.atomic(memarg, 2, &[I32], &[I32])?,
+            Operator::I64AtomicRmwAdd(ref memarg) => self.atomic(memarg, 3, &[I64], &[I64])?,
+            Operator::I32AtomicRmw8AddU(ref memarg) => self.atomic(memarg, 0, &[I32], &[I32])?,
+            Operator::I32AtomicRmw16AddU(ref memarg) => self.atomic(memarg, 1, &[I32], &[I32])?,
+            Operator::I64AtomicRmw8AddU(ref memarg) => self.atomic(memarg, 0, &[I64], &[I64])?,
+            Operator::I64AtomicRmw16AddU(ref memarg) => self.atomic(memarg, 1, &[I64], &[I64])?,
+            Operator::I64AtomicRmw32AddU(ref memarg) => self.atomic(memarg, 2, &[I64], &[I64])?,
+            Operator::I32AtomicRmwSub(ref memarg) => self.atomic(memarg, 2, &[I32], &[I32])?,
+            Operator::I64AtomicRmwSub(ref memarg) => self.atomic(memarg, 3, &[I64], &[I64])?,
+            Operator::I32AtomicRmw8SubU(ref memarg) => self.atomic(memarg, 0, &[I32], &[I32])?,
+            Operator::I32AtomicRmw16SubU(ref memarg) => self.atomic(memarg, 1, &[I32], &[I32])?,
+            Operator::I64AtomicRmw8SubU(ref memarg) => self.atomic(memarg, 0, &[I64], &[I64])?,
+            Operator::I64AtomicRmw16SubU(ref memarg) => self.atomic(memarg, 1, &[I64], &[I64])?,
+            Operator::I64AtomicRmw32SubU(ref memarg) => self.atomic(memarg, 2, &[I64], &[I64])?,
+            Operator::I32AtomicRmwAnd(ref memarg) => self.atomic(memarg, 2, &[I32], &[I32])?,
+            Operator::I64AtomicRmwAnd(ref memarg) => self.atomic(memarg, 3, &[I64], &[I64])?,
+            Operator::I32AtomicRmw8AndU(ref memarg) => self.atomic(memarg, 0, &[I32], &[I32])?,
+            Operator::I32AtomicRmw16AndU(ref memarg) => self.atomic(memarg, 1, &[I32], &[I32])?,
+            Operator::I64AtomicRmw8AndU(ref memarg) => self.atomic(memarg, 0, &[I64], &[I64])?,
+            Operator::I64AtomicRmw16AndU(ref memarg) => self.atomic(memarg, 1, &[I64], &[I64])?,
+            Operator::I64AtomicRmw32AndU(ref memarg) => self.atomic(memarg, 2, &[I64], &[I64])?,
+            Operator::I32AtomicRmwOr(ref memarg) => self.atomic(memarg, 2, &[I32], &[I32])?,
+            Operator::I64AtomicRmwOr(ref memarg) => self.atomic(memarg, 3, &[I64], &[I64])?,
+            Operator::I32AtomicRmw8OrU(ref memarg) => self.atomic(memarg, 0, &[I32], &[I32])?,
+            Operator::I32AtomicRmw16OrU(ref memarg) => self.atomic(memarg, 1, &[I32], &[I32])?,
+            Operator::I64AtomicRmw8OrU(ref memarg) => self.atomic(memarg, 0, &[I64], &[I64])?,
+            Operator::I64AtomicRmw16OrU(ref memarg) => self.atomic(memarg, 1, &[I64], &[I64])?,
+            Operator::I64AtomicRmw32OrU(ref memarg) => self.atomic(memarg, 2, &[I64], &[I64])?,
+            Operator::I32AtomicRmwXor(ref memarg) => self.atomic(memarg, 2, &[I32], &[I32])?,
+            Operator::I64AtomicRmwXor(ref memarg) => self.atomic(memarg, 3, &[I64], &[I64])?,
+            Operator::I32AtomicRmw8XorU(ref memarg) => self.atomic(memarg, 0, &[I32], &[I32])?,
+            Operator::I32AtomicRmw16XorU(ref memarg) => self.atomic(memarg, 1, &[I32], &[I32])?,
+            Operator::I64AtomicRmw8XorU(ref memarg) => self.atomic(memarg, 0, &[I64], &[I64])?,
+            Operator::I64AtomicRmw16XorU(ref memarg) => self.atomic(memarg, 1, &[I64], &[I64])?,
+            Operator::I64AtomicRmw32XorU(ref memarg) => self.atomic(memarg, 2, &[I64], &[I64])?,
+            Operator::I32AtomicRmwXchg(ref memarg) => self.atomic(memarg, 2, &[I32], &[I32])?,
+            Operator::I64AtomicRmwXchg(ref memarg) => self.atomic(memarg, 3, &[I64], &[I64])?,
+            Operator::I32AtomicRmw8XchgU(ref memarg) => self.atomic(memarg, 0, &[I32], &[I32])?,
+            Operator::I32AtomicRmw16XchgU(ref memarg) => self.atomic(memarg, 1, &[I32], &[I32])?,
+            Operator::I64AtomicRmw8XchgU(ref memarg) => self.atomic(memarg, 0, &[I64], &[I64])?,
+            Operator::I64AtomicRmw16XchgU(ref memarg) => self.atomic(memarg, 1, &[I64], &[I64])?,
+            Operator::I64AtomicRmw32XchgU(ref memarg) => self.atomic(memarg, 2, &[I64], &[I64])?,
+            Operator::I32AtomicRmwCmpxchg(ref memarg) => {
+                self.atomic(memarg, 2, &[I32, I32], &[I32])?
+            }
+            Operator::I64AtomicRmwCmpxchg(ref memarg) => {
+                self.atomic(memarg, 3, &[I64, I64], &[I64])?
+            }
+            Operator::I32AtomicRmw8CmpxchgU(ref memarg) => {
+                self.atomic(memarg, 0, &[I32, I32], &[I32])?
+            }
+            Operator::I32AtomicRmw16CmpxchgU(ref memarg) => {
+                self.atomic(memarg, 1, &[I32, I32], &[I32])?
+            }
+            Operator::I64AtomicRmw8CmpxchgU(ref memarg) => {
+                self.atomic(memarg, 0, &[I64, I64], &[I64])?
+            }
+            Operator::I64AtomicRmw16CmpxchgU(ref memarg) => {
+                self.atomic(memarg, 1, &[I64, I64], &[I64])?
+            }
+            Operator::I64AtomicRmw32CmpxchgU(ref memarg) => {
+                self.atomic(memarg, 2, &[I64, I64], &[I64])?
+            }
+            Operator::StructNew(type_index) => {
+                let params: Vec<ValueType> = self
+                    .context
+                    .struct_type(type_index)?
+                    .fields
+                    .iter()
+                    .map(|field| field.storage_type.unpacked())
+                    .collect();
+                let result = reference(false, HeapType::Concrete(type_index));
+                self.op(&params, &[result])?;
+            }
+            Operator::StructNewDefault(type_index) => {
+                let struct_type = self.context.struct_type(type_index)?;
+                if !struct_type
+                    .fields
+                    .iter()
+                    .all(|field| is_defaultable(field.storage_type))
+                {
+                    return Err(Error::other("Struct type has non-defaultable fields"));
+                }
+                self.push(reference(false, HeapType::Concrete(type_index)));
+            }
+            Operator::StructGet {
+                type_index,
+                field_index,
+            } => self.struct_get(type_index, field_index, None)?,
+            Operator::StructGetS {
+                type_index,
+                field_index,
+            } => self.struct_get(type_index, field_index, Some(true))?,
+            Operator::StructGetU {
+                type_index,
+                field_index,
+            } => self.struct_get(type_index, field_index, Some(false))?,
+            Operator::StructSet {
+                type_index,
+                field_index,
+            } => {
+                let field = self.struct_field(type_index, field_index)?;
+                check_mutable(field)?;
+                let object = reference(true, HeapType::Concrete(type_index));
+                self.op(&[object, field.storage_type.unpacked()], &[])?;
+            }
+            Operator::ArrayNew(type_index) => {
+                let field = self.array_field(type_index)?;
+                let result = reference(false, HeapType::Concrete(type_index));
+                self.op(&[field.storage_type.unpacked(), I32], &[result])?;
+            }
+            Operator::ArrayNewDefault(type_index) => {
+                if !is_defaultable(self.array_field(type_index)?.storage_type) {
+                    return Err(Error::other("Array type has a non-defaultable element"));
+                }
+                let result = reference(false, HeapType::Concrete(type_index));
+                self.op(&[I32], &[result])?;
+            }
+            Operator::ArrayNewFixed { type_index, size } => {
+                let element = self.array_field(type_index)?.storage_type.unpacked();
+                let height = self.frames[self.frames.len() - 1].height;
+
+                // The size is untrusted, so operands are popped one at a time
+                // instead of building the parameter list.
+                for _ in 0..size {
+                    if self.operands.len() == height {
+                        // The rest come from a polymorphic stack or underflow.
+                        self.pop()?;
+                        break;
+                    }
+                    self.pop_expect(element)?;
+                }
+                self.push(reference(false, HeapType::Concrete(type_index)));
+            }
+            Operator::ArrayNewData {
+                type_index,
+                data_index,
+            } => {
+                self.array_data(type_index, data_index)?;
+                let result = reference(false, HeapType::Concrete(type_index));
+                self.op(&[I32, I32], &[result])?;
+            }
+            Operator::ArrayNewElem {
+                type_index,
+                elem_index,
+            } => {
+                self.array_elem(type_index, elem_index)?;
+                let result = reference(false, HeapType::Concrete(type_index));
+                self.op(&[I32, I32], &[result])?;
+            }
+            Operator::ArrayGet(type_index) => self.array_get(type_index, None)?,
+            Operator::ArrayGetS(type_index) => self.array_get(type_index, Some(true))?,
+            Operator::ArrayGetU(type_index) => self.array_get(type_index, Some(false))?,
+            Operator::ArraySet(type_index) => {
+                let field = self.array_field(type_index)?;
+                check_mutable(field)?;
+                let array = reference(true, HeapType::Concrete(type_index));
+                self.op(&[array, I32, field.storage_type.unpacked()], &[])?;
+            }
+            Operator::ArrayLen => self.op(&[reference(true, HeapType::Array)], &[I32])?,
+            Operator::ArrayFill(type_index) => {
+                let field = self.array_field(type_index)?;
+                check_mutable(field)?;
+                let array = reference(true, HeapType::Concrete(type_index));
+                self.op(&[array, I32, field.storage_type.unpacked(), I32], &[])?;
+            }
+            Operator::ArrayCopy { dst, src } => {
+                let dst_field = self.array_field(dst)?;
+                let src_field = self.array_field(src)?;
+                check_mutable(dst_field)?;
+                if !self
+                    .context
+                    .is_storage_subtype(src_field.storage_type, dst_field.storage_type)
+                {
+                    return Err(Error::other("Array element types do not match"));
+                }
+                let dst = reference(true, HeapType::Concrete(dst));
+                let src = reference(true, HeapType::Concrete(src));
+                self.op(&[dst, I32, src, I32, I32], &[])?;
+            }
+            Operator::ArrayInitData {
+                type_index,
+                data_index,
+            } => {
+                check_mutable(self.array_data(type_index, data_index)?)?;
+                let array = reference(true, HeapType::Concrete(type_index));
+                self.op(&[array, I32, I32, I32], &[])?;
+            }
+            Operator::ArrayInitElem {
+                type_index,
+                elem_index,
+            } => {
+                check_mutable(self.array_elem(type_index, elem_index)?)?;
+                let array = reference(true, HeapType::Concrete(type_index));
+                self.op(&[array, I32, I32, I32], &[])?;
+            }
+            Operator::RefTest(ref_type) => {
+                self.pop_castable(ref_type)?;
+                self.push(I32);
+            }
+            Operator::RefCast(ref_type) => {
+                self.pop_castable(ref_type)?;
+                self.push(ValueType::Ref(ref_type));
+            }
+            Operator::BrOnCast { label, from, to } => {
+                let remainder = RefType {
+                    nullable: from.nullable && !to.nullable,
+                    heap_type: from.heap_type,
+                };
+                self.br_on_cast(label, from, to, to, remainder)?;
+            }
+            Operator::BrOnCastFail { label, from, to } => {
+                let remainder = RefType {
+                    nullable: from.nullable && !to.nullable,
+                    heap_type: from.heap_type,
+                };
+                self.br_on_cast(label, from, to, remainder, to)?;
+            }
+            Operator::AnyConvertExtern => {
+                let nullable = self.pop_nullable(HeapType::Extern)?;
+                self.push(reference(nullable, HeapType::Any));
+            }
+            Operator::ExternConvertAny => {
+                let nullable = self.pop_nullable(HeapType::Any)?;
+                self.push(reference(nullable, HeapType::Extern));
+            }
+            Operator::RefI31 => self.op(&[I32], &[reference(false, HeapType::I31)])?,
+            Operator::I31GetS | Operator::I31GetU => {
+                self.op(&[reference(true, HeapType::I31)], &[I32])?
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Besides constants and globals, the extended-const arithmetic and the GC
+/// allocation instructions may appear in constant expressions.
+fn is_constant(operator: &Operator) -> bool {
+    matches!(
+        *operator,
+        Operator::I32Const(_)
+            | Operator::I64Const(_)
+            | Operator::F32Const(_)
+            | Operator::F64Const(_)
+            | Operator::V128Const(_)
+            | Operator::RefNull(_)
+            | Operator::RefFunc(_)
+            | Operator::GlobalGet(_)
+            | Operator::I32Add
+            | Operator::I32Sub
+            | Operator::I32Mul
+            | Operator::I64Add
+            | Operator::I64Sub
+            | Operator::I64Mul
+            | Operator::StructNew(_)
+            | Operator::StructNewDefault(_)
+            | Operator::ArrayNew(_)
+            | Operator::ArrayNewDefault(_)
+            | Operator::ArrayNewFixed { .. }
+            | Operator::RefI31
+            | Operator::AnyConvertExtern
+            | Operator::ExternConvertAny
+            | Operator::End
+    )
+}
+
+pub(super) fn address_type(memory: &MemoryType) -> ValueType {
+    if memory.is_64() {
+        I64
+    } else {
+        I32
+    }
+}
+
+pub(super) fn table_index_type(table: &TableType) -> ValueType {
+    if table.table64 {
+        I64
+    } else {
+        I32
+    }
+}
+
+fn element_type(table: &TableType) -> ValueType {
+    ValueType::Ref(table.element_type)
+}
+
+fn reference(nullable: bool, heap_type: HeapType) -> ValueType {
+    ValueType::Ref(RefType {
+        nullable,
+        heap_type,
+    })
+}
+
+fn is_defaultable(storage_type: StorageType) -> bool {
+    match storage_type {
+        StorageType::Value(ValueType::Ref(ref_type)) => ref_type.nullable,
+        _ => true,
+    }
+}
+
+/// Checks that an access uses the `_s` or `_u` form exactly when the field
+/// is packed.
+fn check_packing(field: FieldType, signed: Option<bool>) -> Result<(), Error> {
+    match (field.storage_type, signed) {
+        (StorageType::Value(_), None)
+        | (StorageType::I8, Some(_))
+        | (StorageType::I16, Some(_)) => Ok(()),
+        (StorageType::Value(_), Some(_)) => Err(Error::other(
+            "Signed or unsigned access to an unpacked field",
+        )),
+        _ => Err(Error::other(
+            "Packed fields require a signed or unsigned access",
+        )),
+    }
+}
+
+fn check_mutable(field: FieldType) -> Result<(), Error> {
+    if !field.mutable {
+        return Err(Error::other("Field is immutable"));
+    }
+    Ok(())
+}
@@ -0,0 +1,324 @@
+//! Type equivalence and subtyping for the GC type system. Defined types are
+//! compared through canonical indices: two types are the same if they sit at
+//! the same position of structurally identical recursion groups.
+
+use std::collections::HashMap;
+use std::io::Error;
+use types::{
+    CompositeType, FieldType, FunctionType, HeapType, RefType, StorageType, SubType, TypeSection,
+    ValueType,
+};
+
+use super::Context;
+
+/// Assigns every type the index of the first type it is equivalent to.
+pub(super) fn canonicalize(section: &TypeSection) -> Vec<u32> {
+    let mut canonical: Vec<u32> = Vec::new();
+    let mut groups = HashMap::new();
+
+    for group in &section.entries {
+        let start = canonical.len() as u32;
+
+        let mut key = String::new();
+        for sub_type in &group.types {
+            sub_type_key(&mut key, sub_type, start, &canonical);
+            key.push(';');
+        }
+
+        let first = *groups.entry(key).or_insert(start);
+        canonical.extend((0..group.types.len() as u32).map(|offset| first + offset));
+    }
+
+    canonical
+}
+
+/// Writes a description of `sub_type` in which references into the group
+/// are relative to its `start` and all others are canonical.
+fn sub_type_key(key: &mut String, sub_type: &SubType, start: u32, canonical: &[u32]) {
+    let index_key = |index: u32| {
+        if index >= start {
+            format!("r{}", index - start)
+        } else {
+            match canonical.get(index as usize) {
+                Some(index) => format!("c{}", index),
+                None => format!("u{}", index),
+            }
+        }
+    };
+    let value_key = |ty: &ValueType| match *ty {
+        ValueType::Ref(RefType {
+            nullable,
+            heap_type: HeapType::Concrete(index),
+        }) => format!("ref {} {}", nullable, index_key(index)),
+        _ => format!("{:?}", ty),
+    };
+    let field_key = |field: &FieldType| match field.storage_type {
+        StorageType::Value(ref ty) => format!("{} {}", field.mutable, value_key(ty)),
+        _ => format!("{} {:?}", field.mutable, field.storage_type),
+    };
+
+    key.push_str(&format!("{} ", sub_type.is_final));
+    for &index in &sub_type.supertypes {
+        key.push_str(&index_key(index));
+    }
+
+    match sub_type.composite {
+        CompositeType::Func(ref func_type) => {
+            key.push_str(" func");
+            for ty in &func_type.param_types {
+                key.push_str(&format!(" {}", value_key(ty)));
+            }
+            key.push_str(" ->");
+            for ty in &func_type.return_types {
+                key.push_str(&format!(" {}", value_key(ty)));
+            }
+        }
+        CompositeType::Struct(ref struct_type) => {
+            key.push_str(" struct");
+            for field in &struct_type.fields {
+                key.push_str(&format!(" {}", field_key(field)));
+            }
+        }
+        CompositeType::Array(ref array_type) => {
+            key.push_str(&format!(" array {}", field_key(&array_type.field)));
+        }
+    }
+}
+
+impl<'a> Context<'a> {
+    pub(super) fn sub_type(&self, index: u32) -> Result<&'a SubType, Error> {
+        self.types
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| Error::other(format!("Unknown type {}", index)))
+    }
+
+    pub(super) fn check_value_type(&self, ty: ValueType) -> Result<(), Error> {
+        match ty {
+            ValueType::Ref(ref_type) => self.check_heap_type(ref_type.heap_type),
+            _ => Ok(()),
+        }
+    }
+
+    pub(super) fn check_heap_type(&self, heap_type: HeapType) -> Result<(), Error> {
+        match heap_type {
+            HeapType::Concrete(index) => self.sub_type(index).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    pub(super) fn is_subtype(&self, sub: ValueType, sup: ValueType) -> bool {
+        match (sub, sup) {
+            (ValueType::Ref(sub), ValueType::Ref(sup)) => self.is_ref_subtype(sub, sup),
+            _ => sub == sup,
+        }
+    }
+
+    pub(super) fn is_ref_subtype(&self, sub: RefType, sup: RefType) -> bool {
+        (!sub.nullable || sup.nullable) && self.is_heap_subtype(sub.heap_type, sup.heap_type)
+    }
+
+    pub(super) fn is_heap_subtype(&self, sub: HeapType, sup: HeapType) -> bool {
+        use types::HeapType::*;
+
+        match (sub, sup) {
+            (Concrete(sub), Concrete(sup)) => {
+                let target = match self.canonical(sup) {
+                    Some(target) => target,
+                    Option::None => return false,
+                };
+
+                // Declared supertypes always have lower indices, so the
+                // chain terminates.
+                let mut current = sub;
+                loop {
+                    if self.canonical(current) == Some(target) {
+                        return true;
+                    }
+                    match self
+                        .types
+                        .get(current as usize)
+                        .and_then(|sub_type| sub_type.supertypes.first().cloned())
+                    {
+                        Some(supertype) if supertype < current => current = supertype,
+                        _ => return false,
+                    }
+                }
+            }
+            (Concrete(index), _) => match self.types.get(index as usize) {
+                Some(sub_type) => match sub_type.composite {
+                    CompositeType::Func(_) => sup == Func,
+                    CompositeType::Struct(_) => sup == Struct || sup == Eq || sup == Any,
+                    CompositeType::Array(_) => sup == Array || sup == Eq || sup == Any,
+                },
+                Option::None => false,
+            },
+            (_, Concrete(index)) => match self.types.get(index as usize) {
+                Some(sub_type) => match sub_type.composite {
+                    CompositeType::Func(_) => sub == NoFunc,
+                    _ => sub == None,
+                },
+                Option::None => false,
+            },
+            _ if sub == sup => true,
+            (None, Any) | (None, Eq) | (None, I31) | (None, Struct) | (None, Array) => true,
+            (I31, Any) | (Struct, Any) | (Array, Any) | (Eq, Any) => true,
+            (I31, Eq) | (Struct, Eq) | (Array, Eq) => true,
+            (NoFunc, Func) | (NoExtern, Extern) | (NoExn, Exn) => true,
+            _ => false,
+        }
+    }
+
+    /// The top of the hierarchy `heap_type` belongs to.
+    pub(super) fn top_type(&self, heap_type: HeapType) -> HeapType {
+        match heap_type {
+            HeapType::Func | HeapType::NoFunc => HeapType::Func,
+            HeapType::Extern | HeapType::NoExtern => HeapType::Extern,
+            HeapType::Exn | HeapType::NoExn => HeapType::Exn,
+            HeapType::Concrete(index) => match self.types.get(index as usize) {
+                Some(sub_type) => match sub_type.composite {
+                    CompositeType::Func(_) => HeapType::Func,
+                    _ => HeapType::Any,
+                },
+                None => HeapType::Any,
+            },
+            _ => HeapType::Any,
+        }
+    }
+
+    fn canonical(&self, index: u32) -> Option<u32> {
+        self.canonical.get(index as usize).cloned()
+    }
+
+    fn is_field_subtype(&self, sub: &FieldType, sup: &FieldType) -> bool {
+        if sub.mutable != sup.mutable {
+            return false;
+        }
+
+        match (sub.storage_type, sup.storage_type) {
+            (StorageType::Value(sub_ty), StorageType::Value(sup_ty)) => {
+                if sub.mutable {
+                    self.is_subtype(sub_ty, sup_ty) && self.is_subtype(sup_ty, sub_ty)
+                } else {
+                    self.is_subtype(sub_ty, sup_ty)
+                }
+            }
+            (sub_storage, sup_storage) => sub_storage == sup_storage,
+        }
+    }
+
+    fn is_func_subtype(&self, sub: &FunctionType, sup: &FunctionType) -> bool {
+        sub.param_types.len() == sup.param_types.len()
+            && sub.return_types.len() == sup.return_types.len()
+            && sub
+                .param_types
+                .iter()
+                .zip(&sup.param_types)
+                .all(|(&sub_ty, &sup_ty)| self.is_subtype(sup_ty, sub_ty))
+            && sub
+                .return_types
+                .iter()
+                .zip(&sup.return_types)
+                .all(|(&sub_ty, &sup_ty)| self.is_subtype(sub_ty, sup_ty))
+    }
+
+    pub(super) fn is_storage_subtype(&self, sub: StorageType, sup: StorageType) -> bool {
+        match (sub, sup) {
+            (StorageType::Value(sub), StorageType::Value(sup)) => self.is_subtype(sub, sup),
+            _ => sub == sup,
+        }
+    }
+}
+
+/// Checks that types only refer to their own or earlier recursion groups
+/// and that every declared supertype can be extended by its subtype.
+pub(super) fn validate_types(context: &Context, section: &TypeSection) -> Result<(), Error> {
+    let mut index = 0;
+
+    for group in &section.entries {
+        let end = index + group.types.len() as u32;
+
+        for sub_type in &group.types {
+            validate_sub_type(context, sub_type, index, end)
+                .map_err(|error| Error::other(format!("Type {}: {}", index, error)))?;
+            index += 1;
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_sub_type(
+    context: &Context,
+    sub_type: &SubType,
+    index: u32,
+    end: u32,
+) -> Result<(), Error> {
+    let check = |ty: ValueType| match ty {
+        ValueType::Ref(RefType {
+            heap_type: HeapType::Concrete(target),
+            ..
+        }) if target >= end => Err(Error::other(format!("Unknown type {}", target))),
+        ValueType::Func | ValueType::EmptyBlockType => Err(Error::other("Invalid value type")),
+        _ => Ok(()),
+    };
+    let check_field = |field: &FieldType| match field.storage_type {
+        StorageType::Value(ty) => check(ty),
+        _ => Ok(()),
+    };
+
+    match sub_type.composite {
+        CompositeType::Func(ref func_type) => {
+            for &ty in func_type.param_types.iter().chain(&func_type.return_types) {
+                check(ty)?;
+            }
+        }
+        CompositeType::Struct(ref struct_type) => {
+            for field in &struct_type.fields {
+                check_field(field)?;
+            }
+        }
+        CompositeType::Array(ref array_type) => check_field(&array_type.field)?,
+    }
+
+    for &supertype in &sub_type.supertypes {
+        if supertype >= index {
+            return Err(Error::other(format!(
+                "Supertype {} must be declared before its subtypes",
+                supertype
+            )));
+        }
+
+        let parent = context.sub_type(supertype)?;
+        if parent.is_final {
+            return Err(Error::other(format!("Supertype {} is final", supertype)));
+        }
+
+        let matches = match (&sub_type.composite, &parent.composite) {
+            (CompositeType::Func(sub), CompositeType::Func(sup)) => {
+                context.is_func_subtype(sub, sup)
+            }
+            (CompositeType::Struct(sub), CompositeType::Struct(sup)) => {
+                sub.fields.len() >= sup.fields.len()
+                    && sub
+                        .fields
+                        .iter()
+                        .zip(&sup.fields)
+                        .all(|(sub, sup)| context.is_field_subtype(sub, sup))
+            }
+            (CompositeType::Array(sub), CompositeType::Array(sup)) => {
+                context.is_field_subtype(&sub.field, &sup.field)
+            }
+            _ => false,
+        };
+
+        if !matches {
+            return Err(Error::other(format!(
+                "Type does not match its supertype {}",
+                supertype
+            )));
+        }
+    }
+
+    Ok(())
+}